@@ -1,11 +1,17 @@
-use super::{Action, App, AppAction, AppState, InputMode};
+use super::{Action, App, AppAction, AppState, DownloadControl, InputMode, PendingPlayback};
+use crate::model::download::{DownloadStatus, PlaylistDownload, PlaylistListing};
+use crate::model::queue::{
+    self, PlaylistEntry, PlaylistPage, PlaylistSource, QueueItem, QueueMode,
+};
+use crate::model::{Video, VideoType};
+use crate::sys::config::DownloadProfile;
+use crate::sys::media::NowPlaying;
+use crate::sys::mpv_ipc::{Command, LoadMode, Osd, SeekMode};
 use crate::sys::{local, yt};
 use crossterm::event::KeyCode;
 
 pub fn refresh_local_files(app: &mut App) {
-    let download_path_buf = local::resolve_path(&app.download_directory);
-    let download_path = download_path_buf.as_path();
-    app.local_files = local::scan_local_files(download_path);
+    app.local_files = local::scan_local_files(&local::download_roots(&app.settings));
     if !app.local_files.is_empty() {
         if app.selected_local_file_index.is_none() {
             app.selected_local_file_index = Some(0);
@@ -49,6 +55,16 @@ pub fn get_available_actions(app: &App) -> Vec<Action> {
                         "Play (Audio)",
                         AppAction::PlayLocalAudio,
                     ));
+                    actions.push(Action::new(
+                        KeyCode::Char('e'),
+                        "Add to Queue",
+                        AppAction::AddToQueue,
+                    ));
+                    actions.push(Action::new(
+                        KeyCode::Char('n'),
+                        "Add to Queue (Audio)",
+                        AppAction::AddToQueueAudio,
+                    ));
                 }
                 actions.push(Action::new(
                     KeyCode::Char('x'),
@@ -62,8 +78,19 @@ pub fn get_available_actions(app: &App) -> Vec<Action> {
             if let Some(task_id) = app.download_manager.task_order.get(idx) {
                 if let Some(task) = app.download_manager.tasks.get(task_id) {
                     match task.status {
-                        crate::model::download::DownloadStatus::Downloading
-                        | crate::model::download::DownloadStatus::Pending => {
+                        crate::model::download::DownloadStatus::Pending => {
+                            actions.push(Action::new(
+                                KeyCode::Char('n'),
+                                "Download Next",
+                                AppAction::DownloadNext,
+                            ));
+                            actions.push(Action::new(
+                                KeyCode::Char('x'),
+                                "Cancel Download",
+                                AppAction::CancelDownload,
+                            ));
+                        }
+                        crate::model::download::DownloadStatus::Downloading => {
                             actions.push(Action::new(
                                 KeyCode::Char('p'),
                                 "Pause Download",
@@ -122,6 +149,18 @@ pub fn get_available_actions(app: &App) -> Vec<Action> {
                             }
                         }
                     }
+                    if matches!(
+                        task.status,
+                        crate::model::download::DownloadStatus::Pending
+                            | crate::model::download::DownloadStatus::Downloading
+                            | crate::model::download::DownloadStatus::Paused
+                    ) {
+                        actions.push(Action::new(
+                            KeyCode::Char('l'),
+                            "Cycle Speed Limit",
+                            AppAction::CycleRateLimit,
+                        ));
+                    }
                 }
             }
         }
@@ -239,6 +278,16 @@ pub fn get_available_actions(app: &App) -> Vec<Action> {
                     "Listen (Audio Only)",
                     AppAction::ListenAudio,
                 ));
+                actions.push(Action::new(
+                    KeyCode::Char('e'),
+                    "Add to Queue",
+                    AppAction::AddToQueue,
+                ));
+                actions.push(Action::new(
+                    KeyCode::Char('n'),
+                    "Add to Queue (Audio)",
+                    AppAction::AddToQueueAudio,
+                ));
                 actions.push(Action::new(
                     KeyCode::Char('d'),
                     "Download",
@@ -254,6 +303,17 @@ pub fn get_available_actions(app: &App) -> Vec<Action> {
                     "Copy URL/Channel ID",
                     AppAction::CopyUrlOrId,
                 ));
+                if !video.channel_id.is_empty() {
+                    actions.push(Action::new(
+                        KeyCode::Char('b'),
+                        if app.subscriptions.is_subscribed(&video.channel_id) {
+                            "Unsubscribe from Channel"
+                        } else {
+                            "Subscribe to Channel"
+                        },
+                        AppAction::Subscribe,
+                    ));
+                }
 
                 // If this video belongs to a playlist, add playlist options
                 if video.parent_playlist_id.is_some() {
@@ -278,6 +338,45 @@ pub fn get_available_actions(app: &App) -> Vec<Action> {
             "Download Selected",
             AppAction::DownloadSelected,
         ));
+        actions.push(Action::new(
+            KeyCode::Char('u'),
+            "Add Selected to Queue",
+            AppAction::AddSelectedToQueue,
+        ));
+        actions.push(Action::new(
+            KeyCode::Char('y'),
+            "Play Selected",
+            AppAction::PlayAll,
+        ));
+        actions.push(Action::new(
+            KeyCode::Char('z'),
+            "Shuffle Play Selected",
+            AppAction::ShufflePlay,
+        ));
+    } else if !app.playlist_stack.is_empty() {
+        actions.push(Action::new(
+            KeyCode::Char('u'),
+            "Add All to Queue (Current View)",
+            AppAction::AddSelectedToQueue,
+        ));
+        actions.push(Action::new(
+            KeyCode::Char('y'),
+            "Play All",
+            AppAction::PlayAll,
+        ));
+        actions.push(Action::new(
+            KeyCode::Char('z'),
+            "Shuffle Play",
+            AppAction::ShufflePlay,
+        ));
+    }
+
+    if app.is_feed_mode && app.playlist_stack.is_empty() {
+        actions.push(Action::new(
+            KeyCode::Char('m'),
+            "Mark All as Seen",
+            AppAction::MarkFeedSeen,
+        ));
     }
 
     if !app.playlist_stack.is_empty() {
@@ -287,7 +386,7 @@ pub fn get_available_actions(app: &App) -> Vec<Action> {
         {
             actions.push(Action::new(
                 KeyCode::Char('l'),
-                "Download All (Current View)",
+                "Download All (Playlist)",
                 AppAction::DownloadPlaylist,
             ));
         }
@@ -310,6 +409,7 @@ pub fn perform_search(app: &mut App) {
     app.search_progress = Some(0.0);
     app.is_searching = true;
     app.current_search_id += 1;
+    app.is_feed_mode = false;
     app.status_message = Some(format!("Searching for '{}'...", app.search_query));
     log::info!(
         "Searching for: '{}' (ID: {})",
@@ -340,6 +440,7 @@ pub fn perform_search(app: &mut App) {
     app.is_playlist_mode = is_direct_playlist_url;
 
     if is_url && is_direct_playlist_url {
+        app.search_end = app.playlist_limit;
         let _ = app.search_tx.send((
             app.search_query.clone(),
             1,
@@ -349,6 +450,7 @@ pub fn perform_search(app: &mut App) {
             app.show_playlists,
         ));
     } else if is_url {
+        app.search_end = 1;
         let _ = app.search_tx.send((
             app.search_query.clone(),
             1,
@@ -358,6 +460,7 @@ pub fn perform_search(app: &mut App) {
             app.show_playlists,
         ));
     } else {
+        app.search_end = app.search_limit;
         let _ = app.search_tx.send((
             app.search_query.clone(),
             1,
@@ -382,14 +485,20 @@ pub fn load_more(app: &mut App) {
     };
 
     app.is_searching = true;
-    app.search_offset = (app.search_results.len() as u32) + 1;
+    // Unavailable entries of a playlist are listed but not shown, so go on after the last one
+    app.search_offset = if app.is_playlist_mode {
+        app.search_end + 1
+    } else {
+        (app.search_results.len() as u32) + 1
+    };
+    app.search_end = app.search_offset + (limit - 1);
     app.search_progress = Some(0.0);
     app.status_message = Some("Loading more...".to_string());
 
     let _ = app.search_tx.send((
         query,
         app.search_offset,
-        app.search_offset + (limit - 1),
+        app.search_end,
         app.current_search_id,
         app.show_live,
         app.show_playlists,
@@ -411,67 +520,801 @@ pub fn handle_paste(app: &mut App, text: String) {
 }
 
 pub fn stop_playback(app: &mut App) {
+    save_history_position(app);
     if let Some(mut child) = app.playback_process.take() {
         log::info!("Stopping playback for: {:?}", app.playback_title);
         let _ = child.start_kill();
     }
-    app.playback_cmd_tx = None;
+    app.player = None;
     app.playback_title = None;
+    app.playback_video = None;
     app.playback_time = 0.0;
     app.playback_total = 0.0;
     app.playback_duration_str = None;
     app.is_paused = false;
     app.is_finishing = false;
+    app.player_subtitles.clear();
+    app.sub_visible = None;
+    app.player_audio_tracks.clear();
+    app.muted = false;
+    app.playback_speed = 1.0;
+    app.loop_file = false;
+    app.skip_segments.clear();
+    app.last_skip = None;
+    if matches!(app.state, AppState::SubtitlePicker | AppState::ChapterList) {
+        app.state = app.previous_app_state;
+    }
     app.terminal_loading = false;
     app.terminal_loading_error = None;
     app.terminal_ready_url = None;
+    app.terminal_video = None;
+    app.video_feed = None;
+    app.audio_player = false;
+    app.player_playlist.clear();
+    app.player_playlist_pos = 0;
+    app.playlist_source = None;
     app.status_message = Some("Stopped.".to_string());
     if let Some(mc) = &mut app.media_controller {
-        let _ = mc.set_playback_status(false);
+        let _ = mc.set_stopped();
     }
 }
 
-pub fn toggle_pause(app: &mut App) {
-    if app.playback_cmd_tx.is_some() {
-        app.is_paused = !app.is_paused;
-        log::info!(
-            "Playback {}",
-            if app.is_paused { "paused" } else { "resumed" }
+pub fn enqueue(app: &mut App, items: Vec<QueueItem>) {
+    if items.is_empty() {
+        app.status_message = Some("Nothing to add to queue.".to_string());
+        return;
+    }
+
+    let count = items.len();
+    for item in items {
+        app.play_queue.push(item);
+    }
+    app.play_queue.save();
+    extend_audio_playlist(app);
+    app.status_message = Some(format!(
+        "Added {} to queue ({} queued).",
+        if count == 1 {
+            "1 item".to_string()
+        } else {
+            format!("{} items", count)
+        },
+        app.play_queue.len()
+    ));
+}
+
+pub fn open_queue(app: &mut App) {
+    app.previous_app_state = app.state;
+    app.state = AppState::Queue;
+    app.selected_queue_index = if app.play_queue.is_empty() {
+        None
+    } else {
+        Some(app.play_queue.current.unwrap_or(0))
+    };
+}
+
+pub fn play_queue_item(app: &mut App, idx: usize) {
+    if let Some(item) = app.play_queue.items.get(idx).cloned() {
+        if item.mode == QueueMode::Audio && audio_player_active(app) {
+            // Already in the audio player's playlist, jump there
+            if let Some(pos) = app
+                .player_playlist
+                .iter()
+                .position(|t| t.queue_index == Some(idx))
+            {
+                log::info!("Playing queue item {} from the audio playlist", idx);
+                app.queue_playing = true;
+                send_command(app, Command::SetProperty("playlist-pos", pos.into()));
+                send_command(app, Command::SetProperty("pause", false.into()));
+                begin_playlist_entry(app, pos);
+                return;
+            }
+        } else {
+            stop_playback(app);
+        }
+
+        app.play_queue.current = Some(idx);
+        app.play_queue.save();
+
+        let action = match item.mode {
+            QueueMode::Video => AppAction::WatchExternal,
+            QueueMode::Audio => AppAction::ListenAudio,
+        };
+        log::info!("Playing queue item {}: {}", idx, item.video.title);
+        let mut pending = PendingPlayback::new(
+            action,
+            item.playback_url(),
+            item.video.title.clone(),
+            Some(item.video.clone()),
         );
-        send_command(app, "{\"command\": [\"cycle\", \"pause\"]}\n");
-        app.status_message = Some(if app.is_paused {
-            "Paused".to_string()
+        // Don't block an unattended queue on the resume prompt
+        pending.start = Some(
+            app.history
+                .get(&item.video.id)
+                .and_then(|e| e.resume_position())
+                .unwrap_or(0.0),
+        );
+        app.pending_action = Some(pending);
+    }
+}
+
+/// What a playlist view of `video` loads: the playlist itself, or the one the video is in.
+pub fn playlist_url(video: &Video) -> String {
+    if video.video_type == VideoType::Playlist {
+        format!("https://www.youtube.com/playlist?list={}", video.id)
+    } else {
+        video
+            .parent_playlist_url
+            .clone()
+            .unwrap_or_else(|| video.url.clone())
+    }
+}
+
+/// URL of the playlist shown in the results. Not the search text, which stays the
+/// same when a playlist is opened from search results.
+pub fn viewed_playlist_url(app: &App) -> Option<String> {
+    match app.playlist_stack.last() {
+        Some((parent, _, _)) => Some(playlist_url(parent)),
+        // A playlist URL entered directly, its videos know where they come from
+        None => app
+            .search_results
+            .iter()
+            .find_map(|v| v.parent_playlist_url.clone()),
+    }
+}
+
+/// Plays the selected videos, or the whole playlist in view, in one mpv instance.
+/// Entries of the playlist that aren't loaded yet follow as playback gets to them,
+/// or are listed first when shuffling, so the shuffle covers the whole playlist.
+pub fn play_playlist(app: &mut App, shuffle: bool) {
+    // Nothing selected means the whole current playlist view
+    let selection = !app.selected_playlist_indices.is_empty();
+    let mut indices: Vec<usize> = if selection {
+        app.selected_playlist_indices.iter().cloned().collect()
+    } else {
+        (0..app.search_results.len()).collect()
+    };
+    indices.sort_unstable();
+    let mut videos: Vec<Video> = indices
+        .iter()
+        .filter_map(|&idx| app.search_results.get(idx))
+        .filter(|v| v.video_type == VideoType::Video)
+        .cloned()
+        .collect();
+    if videos.is_empty() {
+        app.status_message = Some("Nothing to play.".to_string());
+        return;
+    }
+    app.selected_playlist_indices.clear();
+
+    let source = match viewed_playlist_url(app) {
+        Some(url) if !selection && app.is_playlist_mode => Some(PlaylistSource {
+            url,
+            next_start: app.search_end + 1,
+            total: app
+                .playlist_stack
+                .last()
+                .and_then(|(parent, _, _)| parent.playlist_count),
+            loading: false,
+            exhausted: false,
+        }),
+        _ => None,
+    };
+
+    if !shuffle {
+        play_videos(app, videos, source);
+        return;
+    }
+    match source {
+        Some(source)
+            if source
+                .total
+                .is_none_or(|total| source.next_start as u64 <= total) =>
+        {
+            let _ = app
+                .playlist_page_tx
+                .send((source.url.clone(), source.next_start, u32::MAX));
+            app.shuffle_pending = Some((source.url, videos));
+            app.status_message = Some("Fetching the whole playlist to shuffle...".to_string());
+        }
+        _ => {
+            queue::shuffle(&mut videos);
+            play_videos(app, videos, None);
+        }
+    }
+}
+
+fn play_videos(app: &mut App, mut videos: Vec<Video>, source: Option<PlaylistSource>) {
+    let count = source
+        .as_ref()
+        .and_then(|s| s.total)
+        .unwrap_or(videos.len() as u64);
+
+    let first = videos.remove(0);
+    let mut pending = PendingPlayback::new(
+        AppAction::WatchExternal,
+        first.url.clone(),
+        first.title.clone(),
+        Some(first),
+    );
+    // Several videos in a row are not resumed
+    pending.start = Some(0.0);
+    pending.playlist = videos
+        .into_iter()
+        .map(|v| PlaylistEntry {
+            title: v.title.clone(),
+            video: Some(v),
+            queue_index: None,
+        })
+        .collect();
+    pending.playlist_source = source;
+    app.status_message = Some(format!("Playing {} videos...", count));
+    app.pending_action = Some(pending);
+}
+
+/// Hands mpv the entries following the one it just started with.
+pub fn start_playlist(app: &mut App, entries: Vec<PlaylistEntry>, source: Option<PlaylistSource>) {
+    if entries.is_empty() && source.is_none() {
+        return;
+    }
+    app.player_playlist = vec![PlaylistEntry {
+        title: app.playback_title.clone().unwrap_or_default(),
+        video: app.playback_video.clone(),
+        queue_index: None,
+    }];
+    app.player_playlist_pos = 0;
+    app.playlist_source = source;
+    append_to_playlist(app, entries);
+    load_playlist_page(app);
+}
+
+fn append_to_playlist(app: &mut App, entries: Vec<PlaylistEntry>) {
+    for entry in entries {
+        if let Some(video) = &entry.video {
+            send_command(
+                app,
+                Command::LoadFile(video.url.clone(), LoadMode::Append, None),
+            );
+            app.player_playlist.push(entry);
+        }
+    }
+}
+
+/// Requests the next page of the playlist being played when playback gets close to
+/// the last loaded entry.
+fn load_playlist_page(app: &mut App) {
+    let remaining = app
+        .player_playlist
+        .len()
+        .saturating_sub(app.player_playlist_pos + 1);
+    let limit = app.playlist_limit.max(1);
+    let source = match &mut app.playlist_source {
+        Some(source) if !source.loading && !source.exhausted && remaining < 3 => source,
+        _ => return,
+    };
+    if source
+        .total
+        .is_some_and(|total| source.next_start as u64 > total)
+    {
+        source.exhausted = true;
+        return;
+    }
+    let (start, end) = (source.next_start, source.next_start + limit - 1);
+    source.next_start = end + 1;
+    source.loading = true;
+    let _ = app.playlist_page_tx.send((source.url.clone(), start, end));
+}
+
+/// Appends a page of the playlist being played, fetched by `load_playlist_page`, or
+/// starts the shuffle waiting for the rest of the playlist.
+pub fn add_playlist_page(app: &mut App, url: &str, page: Result<PlaylistPage, String>) {
+    if let Some((_, mut videos)) = app.shuffle_pending.take_if(|(u, _)| u == url) {
+        match page {
+            Ok(page) => {
+                for video in page.videos {
+                    if !videos.iter().any(|v| v.id == video.id) {
+                        videos.push(video);
+                    }
+                }
+            }
+            Err(e) => log::error!(
+                "Failed to list the rest of the playlist, shuffling the loaded part: {}",
+                e
+            ),
+        }
+        queue::shuffle(&mut videos);
+        play_videos(app, videos, None);
+        return;
+    }
+
+    let source = match &mut app.playlist_source {
+        Some(source) if source.url == url => source,
+        _ => return,
+    };
+    source.loading = false;
+    let page = match page {
+        Ok(page) => page,
+        Err(e) => {
+            source.exhausted = true;
+            log::error!("Failed to load more of the playlist: {}", e);
+            app.status_message = Some("Failed to load the rest of the playlist.".to_string());
+            return;
+        }
+    };
+    // Unavailable entries are still listed, only nothing listed means the end
+    source.exhausted = page.listed == 0;
+
+    // Entries already in the playlist, e.g. listed twice, are not played again
+    let entries: Vec<PlaylistEntry> = page
+        .videos
+        .into_iter()
+        .filter(|v| {
+            !app.player_playlist
+                .iter()
+                .any(|e| e.video.as_ref().is_some_and(|p| p.id == v.id))
+        })
+        .map(|v| PlaylistEntry {
+            title: v.title.clone(),
+            video: Some(v),
+            queue_index: None,
+        })
+        .collect();
+    log::info!("Appending {} playlist entries", entries.len());
+    append_to_playlist(app, entries);
+    // A page of unavailable entries adds nothing, so go on with the next one
+    load_playlist_page(app);
+}
+
+/// Plays the next queued item, or the next entry of a playlist played on its own.
+/// Returns false when the end of the queue is reached.
+pub fn play_next(app: &mut App) -> bool {
+    if !app.queue_playing && app.player_playlist.len() > 1 {
+        if app.player_playlist_pos + 1 < app.player_playlist.len() {
+            send_command(app, Command::PlaylistNext);
         } else {
-            "Resumed".to_string()
+            app.status_message = Some("End of playlist.".to_string());
+        }
+        return true;
+    }
+    match app.play_queue.next_index() {
+        Some(idx) => {
+            play_queue_item(app, idx);
+            true
+        }
+        None => {
+            app.status_message = Some("End of queue.".to_string());
+            false
+        }
+    }
+}
+
+pub fn play_previous(app: &mut App) -> bool {
+    if !app.queue_playing && app.player_playlist.len() > 1 {
+        if app.player_playlist_pos > 0 {
+            send_command(app, Command::PlaylistPrev);
+        } else {
+            app.status_message = Some("Start of playlist.".to_string());
+        }
+        return true;
+    }
+    match app.play_queue.previous_index() {
+        Some(idx) => {
+            play_queue_item(app, idx);
+            true
+        }
+        None => {
+            app.status_message = Some("Start of queue.".to_string());
+            false
+        }
+    }
+}
+
+/// Whether the long-lived audio player is running. It takes new tracks without restarting.
+pub fn audio_player_active(app: &App) -> bool {
+    app.audio_player && app.playback_process.is_some() && app.player.is_some()
+}
+
+/// Plays a track on the audio player, replacing its playlist. Audio items queued after
+/// it are appended, so they follow without a gap.
+pub fn load_audio(
+    app: &mut App,
+    url: &str,
+    title: String,
+    video: Option<Video>,
+    start: Option<f64>,
+) {
+    let queue_index = if app.queue_playing {
+        app.play_queue.current
+    } else {
+        None
+    };
+    send_command(
+        app,
+        Command::LoadFile(url.to_string(), LoadMode::Replace, start),
+    );
+    send_command(app, Command::SetProperty("pause", false.into()));
+    app.player_playlist = vec![PlaylistEntry {
+        title,
+        video,
+        queue_index,
+    }];
+    begin_playlist_entry(app, 0);
+    extend_audio_playlist(app);
+}
+
+/// Makes the playlist entry at `pos` the current playback, after mpv moved on to it.
+pub fn begin_playlist_entry(app: &mut App, pos: usize) {
+    let track = match app.player_playlist.get(pos) {
+        Some(track) => track.clone(),
+        None => return,
+    };
+    save_history_position(app);
+    app.player_playlist_pos = pos;
+    app.playback_title = Some(track.title.clone());
+    app.playback_time = 0.0;
+    app.playback_total = 0.0;
+    app.playback_duration_str = None;
+    app.is_finishing = false;
+    app.skip_segments.clear();
+    app.last_skip = None;
+    app.playback_video = track.video.clone();
+    if let Some(video) = &track.video {
+        record_history(app, video, app.audio_player);
+        request_segments(app, video);
+    }
+    if let Some(idx) = track.queue_index {
+        app.play_queue.current = Some(idx);
+        app.play_queue.save();
+    }
+    update_media_metadata(app);
+    if let Some(mc) = &mut app.media_controller {
+        let _ = mc.set_playback_status(true, None);
+    }
+    load_playlist_page(app);
+}
+
+/// Appends the audio items queued after the last playlist entry, so mpv can fetch
+/// the next one ahead of time.
+pub fn extend_audio_playlist(app: &mut App) {
+    if !audio_player_active(app) || !app.queue_playing {
+        return;
+    }
+    let mut next = match app.player_playlist.last().and_then(|t| t.queue_index) {
+        Some(idx) => idx + 1,
+        None => return,
+    };
+    while let Some(item) = app.play_queue.items.get(next).cloned()
+        && item.mode == QueueMode::Audio
+    {
+        send_command(
+            app,
+            Command::LoadFile(item.playback_url(), LoadMode::Append, None),
+        );
+        app.player_playlist.push(PlaylistEntry {
+            title: item.video.title.clone(),
+            video: Some(item.video),
+            queue_index: Some(next),
         });
-        if let Some(mc) = &mut app.media_controller {
-            let _ = mc.set_playback_status(!app.is_paused);
+        next += 1;
+    }
+}
+
+/// Rebuilds the audio playlist from the queue after it was edited. The current track
+/// keeps playing, the entries after it follow the new queue order.
+pub fn rebuild_audio_playlist(app: &mut App) {
+    if !audio_player_active(app) || app.player_playlist.is_empty() {
+        return;
+    }
+    send_command(app, Command::PlaylistClear);
+    let pos = app.player_playlist_pos.min(app.player_playlist.len() - 1);
+    let mut current = app.player_playlist[pos].clone();
+    if current.queue_index.is_some() {
+        current.queue_index = app.play_queue.current;
+    }
+    app.player_playlist = vec![current];
+    app.player_playlist_pos = 0;
+    extend_audio_playlist(app);
+}
+
+/// The audio player went idle after its last track. It stays open for the next one.
+pub fn finish_audio_playlist(app: &mut App) {
+    save_history_position(app);
+    app.player_playlist.clear();
+    app.player_playlist_pos = 0;
+    app.playback_title = None;
+    app.playback_video = None;
+    app.playback_time = 0.0;
+    app.playback_total = 0.0;
+    app.playback_duration_str = None;
+    app.is_finishing = false;
+    app.skip_segments.clear();
+    app.last_skip = None;
+    app.status_message = Some("Playback finished.".to_string());
+    if let Some(mc) = &mut app.media_controller {
+        let _ = mc.set_stopped();
+    }
+
+    if app.queue_playing {
+        app.queue_playing = false;
+        if !play_next(app) {
+            app.status_message = Some("Queue finished.".to_string());
+        }
+    }
+}
+
+pub fn record_history(app: &mut App, video: &Video, is_audio: bool) {
+    app.history.record(video, is_audio);
+    app.history.save();
+    app.history_saved_at = std::time::Instant::now();
+
+    if app.subscriptions.is_subscribed(&video.channel_id) && app.subscriptions.mark_seen(&video.id)
+    {
+        app.subscriptions.save();
+    }
+}
+
+/// Stores the current playback position of the tracked video.
+pub fn save_history_position(app: &mut App) {
+    if let Some(video) = &app.playback_video
+        && app.playback_time > 0.0
+    {
+        app.history
+            .update_position(&video.id, app.playback_time, app.playback_total);
+        app.history.save();
+    }
+    app.history_saved_at = std::time::Instant::now();
+}
+
+/// Returns the playback if it can start right away. Otherwise the resume
+/// prompt is shown and the playback waits there for an answer.
+pub fn check_resume(app: &mut App, pending: PendingPlayback) -> Option<PendingPlayback> {
+    if pending.start.is_some()
+        || !matches!(
+            pending.action,
+            AppAction::WatchExternal | AppAction::ListenAudio
+        )
+    {
+        return Some(pending);
+    }
+
+    let position = pending
+        .video
+        .as_ref()
+        .and_then(|v| app.history.get(&v.id))
+        .and_then(|e| e.resume_position());
+
+    match position {
+        Some(pos) => {
+            if app.state != AppState::ResumePrompt {
+                app.previous_app_state = app.state;
+            }
+            app.state = AppState::ResumePrompt;
+            app.resume_prompt = Some((pending, pos));
+            None
+        }
+        None => Some(pending),
+    }
+}
+
+pub fn answer_resume_prompt(app: &mut App, resume: Option<bool>) {
+    if let Some((mut pending, pos)) = app.resume_prompt.take() {
+        match resume {
+            Some(true) => pending.start = Some(pos),
+            Some(false) => pending.start = Some(0.0),
+            None => {
+                app.status_message = Some("Playback canceled.".to_string());
+            }
+        }
+        if resume.is_some() {
+            app.pending_action = Some(pending);
+        }
+    }
+    app.state = app.previous_app_state;
+}
+
+pub fn open_history(app: &mut App) {
+    app.previous_app_state = app.state;
+    app.state = AppState::History;
+    app.selected_history_index = if app.history.is_empty() {
+        None
+    } else {
+        Some(0)
+    };
+}
+
+pub fn play_history_entry(app: &mut App, idx: usize) {
+    if let Some(entry) = app.history.entries.get(idx).cloned() {
+        stop_playback(app);
+
+        let (action, url) = if entry.is_audio {
+            (AppAction::ListenAudio, entry.video.url.clone())
+        } else if entry.is_local() {
+            (AppAction::WatchExternal, entry.video.url.clone())
+        } else {
+            (
+                AppAction::WatchExternal,
+                format!("{}::best", entry.video.url),
+            )
+        };
+        app.pending_action = Some(PendingPlayback::new(
+            action,
+            url,
+            entry.video.title.clone(),
+            Some(entry.video),
+        ));
+        app.state = app.previous_app_state;
+    }
+}
+
+/// Cancels a download. Queued ones never reached yt-dlp, and paused ones restored
+/// from the queue file have no process left, so they are dropped here.
+pub fn cancel_download(app: &mut App, id: &str) {
+    match app.download_manager.tasks.get_mut(id) {
+        Some(task)
+            if task.status == DownloadStatus::Pending
+                || (task.status == DownloadStatus::Paused && task.pid.is_none()) =>
+        {
+            task.status = DownloadStatus::Canceled;
+        }
+        Some(_) => {
+            let _ = app
+                .download_control_tx
+                .send(DownloadControl::Cancel(id.to_string()));
+        }
+        None => {}
+    }
+}
+
+/// Moves the selected queued download to the front so it starts next.
+pub fn download_next(app: &mut App) {
+    if let Some(idx) = app.selected_download_index
+        && let Some(id) = app.download_manager.task_order.get(idx)
+        && app
+            .download_manager
+            .tasks
+            .get(id)
+            .is_some_and(|t| t.status == DownloadStatus::Pending)
+        && let Some(new_idx) = app.download_manager.move_to_front(idx)
+    {
+        app.selected_download_index = Some(new_idx);
+        app.selected_download_indices.clear();
+        app.status_message = Some("Download moved to the front of the queue.".to_string());
+    }
+}
+
+/// Starts queued downloads while there are free slots.
+pub fn start_pending_downloads(app: &mut App) {
+    let max = app.max_concurrent_downloads.max(1) as usize;
+    while app.download_manager.active_count() < max {
+        let next = app
+            .download_manager
+            .next_pending()
+            .and_then(|id| app.download_manager.tasks.get_mut(&id));
+        match next {
+            Some(task) => {
+                // Counts as running right away so the slot is taken until Started arrives
+                task.status = DownloadStatus::Downloading;
+                let _ = app.new_download_tx.send(task.request());
+            }
+            None => break,
         }
     }
 }
 
+/// Continues a paused download if a slot is free. Otherwise it is stopped and queued
+/// again, yt-dlp picks up its .part file once a slot frees up.
+pub fn resume_download(app: &mut App, id: &str) {
+    let max = app.max_concurrent_downloads.max(1) as usize;
+    if app.download_manager.active_count() < max {
+        if let Some(task) = app.download_manager.tasks.get_mut(id) {
+            // Takes the slot right away, like a started download
+            task.status = DownloadStatus::Downloading;
+        }
+        let _ = app
+            .download_control_tx
+            .send(DownloadControl::Resume(id.to_string()));
+    } else {
+        let _ = app
+            .download_control_tx
+            .send(DownloadControl::Restart(id.to_string()));
+        app.status_message = Some("No free download slot, queued to continue later.".to_string());
+    }
+}
+
+pub fn toggle_subscription(app: &mut App, video: &Video) {
+    let subscribed = app.subscriptions.toggle(video);
+    app.subscriptions.save();
+    app.status_message = Some(if subscribed {
+        format!("Subscribed to {}.", video.channel)
+    } else {
+        format!("Unsubscribed from {}.", video.channel)
+    });
+}
+
+/// Shows the latest uploads of all subscribed channels in the results list.
+pub fn open_feed(app: &mut App) {
+    if app.subscriptions.is_empty() {
+        app.status_message =
+            Some("No subscriptions yet. Subscribe from a video's action menu.".to_string());
+        return;
+    }
+
+    app.input_mode = InputMode::Normal;
+    app.state = AppState::Results;
+    app.search_results.clear();
+    app.pending_resolution_ids.clear();
+    app.selected_result_index = None;
+    app.playlist_stack.clear();
+    app.selected_playlist_indices.clear();
+    app.search_progress = Some(0.0);
+    app.is_searching = true;
+    app.current_search_id += 1;
+    // The feed has no further pages, so behave like a direct URL
+    app.is_feed_mode = true;
+    app.is_url_mode = true;
+    app.is_playlist_mode = false;
+    app.status_message = Some(format!(
+        "Fetching feed from {} channels...",
+        app.subscriptions.len()
+    ));
+
+    let _ = app.feed_tx.send((
+        app.subscriptions.channels.clone(),
+        app.playlist_limit,
+        app.current_search_id,
+    ));
+}
+
+pub fn mark_feed_seen(app: &mut App) {
+    for video in &app.search_results {
+        app.subscriptions.mark_seen(&video.id);
+    }
+    app.subscriptions.save();
+    app.status_message = Some("Feed marked as seen.".to_string());
+}
+
+/// The state follows once mpv reports the change, see `set_paused`.
+pub fn toggle_pause(app: &mut App) {
+    send_command(app, Command::Cycle("pause", Osd::Default));
+}
+
+/// Applies a pause change reported by mpv, whoever caused it.
+pub fn set_paused(app: &mut App, paused: bool) {
+    if app.is_paused != paused {
+        log::info!("Playback {}", if paused { "paused" } else { "resumed" });
+        app.status_message = Some(if paused { "Paused" } else { "Resumed" }.to_string());
+    }
+    app.is_paused = paused;
+    sync_media_status(app);
+}
+
+/// Tells MPRIS clients whether we play and where. souvlaki always reports a rate
+/// of 1.0, so this runs every second while playing to keep other speeds in line.
+pub fn sync_media_status(app: &mut App) {
+    app.media_synced_at = std::time::Instant::now();
+    let position = std::time::Duration::from_secs_f64(app.playback_time.max(0.0));
+    if let Some(mc) = &mut app.media_controller {
+        let _ = mc.set_playback_status(!app.is_paused, Some(position));
+    }
+}
+
 pub fn seek(app: &mut App, seconds: i32) {
-    if app.playback_cmd_tx.is_some() {
-        let cmd = format!(
-            "{{\"command\": [\"osd-msg-bar\", \"seek\", {}, \"relative\"]}}\n",
-            seconds
+    if app.player.is_some() {
+        send_command(
+            app,
+            Command::Seek(seconds as f64, SeekMode::Relative, Osd::MsgBar),
         );
-        send_command(app, &cmd);
         app.status_message = Some(format!("Seeked {}s", seconds));
     }
 }
 
-pub fn send_command(app: &App, cmd: &str) {
-    if let Some(tx) = &app.playback_cmd_tx {
-        let mut command = cmd.to_string();
-        if !command.ends_with('\n') {
-            command.push('\n');
-        }
-        let _ = tx.send(command);
+pub fn send_command(app: &App, command: Command) {
+    if let Some(player) = &app.player {
+        player.send(command);
     }
 }
 
-pub fn start_terminal_loading(app: &mut App, url: String, _title: String) {
+pub fn start_terminal_loading(app: &mut App, url: String, video: Video) {
+    app.terminal_video = Some(video);
     app.terminal_loading = true;
     app.terminal_loading_progress = 0.0;
     app.terminal_loading_error = None;
@@ -502,3 +1345,573 @@ pub fn start_terminal_loading(app: &mut App, url: String, _title: String) {
         }
     });
 }
+
+/// Restarts running downloads that follow the global limit once it changes, e.g. when
+/// a schedule window starts or ends. yt-dlp can't change its rate while running.
+pub fn apply_rate_schedule(app: &mut App) {
+    let limit =
+        crate::sys::download::scheduled_rate_limit(&app.settings, chrono::Local::now().time());
+    if limit == app.active_rate_limit {
+        return;
+    }
+    log::info!(
+        "Download rate limit changed from {:?} to {:?}",
+        app.active_rate_limit,
+        limit
+    );
+    app.active_rate_limit = limit;
+
+    for (id, task) in &app.download_manager.tasks {
+        if task.status == DownloadStatus::Downloading
+            && task.pid.is_some()
+            && task.rate_limit.is_none()
+        {
+            let _ = app
+                .download_control_tx
+                .send(DownloadControl::Restart(id.clone()));
+        }
+    }
+}
+
+const TASK_RATE_LIMITS: [&str; 5] = ["", "500K", "1M", "2M", "5M"];
+
+/// Cycles the speed limit of the selected download: global, unlimited, then a few presets.
+pub fn cycle_task_rate_limit(app: &mut App) {
+    if let Some(idx) = app.selected_download_index
+        && let Some(id) = app.download_manager.task_order.get(idx).cloned()
+        && let Some(task) = app.download_manager.tasks.get_mut(&id)
+    {
+        let next = match &task.rate_limit {
+            None => Some(TASK_RATE_LIMITS[0]),
+            Some(current) => TASK_RATE_LIMITS
+                .iter()
+                .position(|r| r == current)
+                .and_then(|i| TASK_RATE_LIMITS.get(i + 1))
+                .copied(),
+        };
+        task.rate_limit = next.map(str::to_string);
+        app.status_message = Some(format!(
+            "Speed limit for this download: {}",
+            task.rate_limit_label()
+        ));
+
+        if task.status == DownloadStatus::Downloading && task.pid.is_some() {
+            let _ = app.download_control_tx.send(DownloadControl::Restart(id));
+        }
+    }
+}
+
+/// Marks a download as failed and, while attempts are left, schedules another try.
+/// The wait doubles with every attempt, capped at an hour.
+pub fn schedule_retry(app: &mut App, id: &str, error: String) {
+    let max_attempts = app.settings.retry_max_attempts;
+    let backoff = app.settings.retry_backoff_seconds;
+    if let Some(task) = app.download_manager.tasks.get_mut(id) {
+        if task.status == DownloadStatus::Canceled {
+            return;
+        }
+
+        if task.attempts < max_attempts {
+            task.attempts += 1;
+            let delay = backoff
+                .saturating_mul(1 << (task.attempts - 1).min(16))
+                .min(3600);
+            task.retry_at = Some(chrono::Utc::now().timestamp() + delay as i64);
+            log::warn!(
+                "Download {} failed ({}), retry {}/{} in {}s",
+                id,
+                error,
+                task.attempts,
+                max_attempts,
+                delay
+            );
+        } else {
+            log::error!(
+                "Download {} failed after {} retries: {}",
+                id,
+                task.attempts,
+                error
+            );
+            task.retry_at = None;
+        }
+        task.status = DownloadStatus::Error(error);
+        task.stats.speed = None;
+        task.stats.eta = None;
+    }
+}
+
+/// Queues failed downloads again once their retry time has come.
+pub fn retry_due_downloads(app: &mut App) {
+    let now = chrono::Utc::now().timestamp();
+    for task in app.download_manager.tasks.values_mut() {
+        if matches!(task.status, DownloadStatus::Error(_))
+            && task.retry_at.is_some_and(|at| at <= now)
+        {
+            task.status = DownloadStatus::Pending;
+            task.retry_at = None;
+        }
+    }
+}
+
+/// Highlights a row on the format selection screen. Profile rows bring their own
+/// post-processing, which the toggles then show and can still change.
+pub fn select_format_row(app: &mut App, idx: usize) {
+    let was_profile = app
+        .selected_format_index
+        .is_some_and(|i| i < app.format_profiles().len());
+    app.selected_format_index = Some(idx);
+
+    if let Some(profile) = app.format_profiles().get(idx) {
+        app.format_postprocess = profile
+            .postprocess
+            .clone()
+            .unwrap_or_else(|| app.settings.postprocess.clone());
+    } else if was_profile {
+        app.format_postprocess = app.settings.postprocess.clone();
+    }
+}
+
+/// Queues several videos at once. With profiles configured, asks which one to use first.
+pub fn queue_batch(app: &mut App, videos: Vec<Video>) {
+    if videos.is_empty() {
+        app.status_message = Some("No videos selected.".to_string());
+        return;
+    }
+
+    if app.settings.profiles.is_empty() {
+        let count = videos
+            .iter()
+            .filter(|video| {
+                app.download_manager
+                    .add_task(video, "best", &app.settings.postprocess)
+            })
+            .count();
+        app.status_message = Some(format!("Queued {} downloads.", count));
+        app.state = app.previous_app_state;
+        return;
+    }
+
+    app.batch_videos = videos;
+    app.batch_playlist = None;
+    app.status_message = Some(format!(
+        "Choose a profile for {} downloads.",
+        app.batch_videos.len()
+    ));
+    open_batch_profiles(app);
+}
+
+fn open_batch_profiles(app: &mut App) {
+    app.format_selection_mode = crate::app::state::FormatSelectionMode::Batch;
+    app.format_postprocess = app.settings.postprocess.clone();
+    app.selected_format_index = None;
+    select_format_row(app, 0);
+    app.state = AppState::FormatSelection;
+}
+
+/// Queues the pending batch with the profile at `profile_idx`, or best quality if `None`.
+pub fn finish_batch(app: &mut App, profile_idx: Option<usize>) {
+    let profile = profile_idx.and_then(|i| app.settings.profiles.get(i).cloned());
+    if let Some(url) = app.batch_playlist.take() {
+        fetch_playlist_download(app, url, profile);
+        return;
+    }
+
+    let videos = std::mem::take(&mut app.batch_videos);
+    let mut count = 0;
+    for video in videos {
+        let queued = match &profile {
+            Some(profile) => {
+                app.download_manager
+                    .add_profile_task(&video, profile, &app.format_postprocess)
+            }
+            None => app
+                .download_manager
+                .add_task(&video, "best", &app.format_postprocess),
+        };
+        if queued {
+            count += 1;
+        }
+    }
+    app.status_message = Some(match &profile {
+        Some(profile) => format!("Queued {} downloads with {}.", count, profile.name),
+        None => format!("Queued {} downloads.", count),
+    });
+}
+
+/// Downloads a whole playlist. The profile is chosen first, the playlist is then listed
+/// in the background and its videos queued once it arrives.
+pub fn download_playlist(app: &mut App, url: String) {
+    if app.settings.profiles.is_empty() {
+        app.format_postprocess = app.settings.postprocess.clone();
+        fetch_playlist_download(app, url, None);
+        app.state = app.previous_app_state;
+        return;
+    }
+
+    app.batch_videos.clear();
+    app.batch_playlist = Some(url);
+    app.status_message = Some("Choose a profile for the playlist.".to_string());
+    open_batch_profiles(app);
+}
+
+fn fetch_playlist_download(app: &mut App, url: String, profile: Option<DownloadProfile>) {
+    log::info!("Downloading playlist {}", url);
+    let _ = app.playlist_download_tx.send(PlaylistDownload {
+        url,
+        profile,
+        postprocess: app.format_postprocess.clone(),
+    });
+    app.status_message = Some("Fetching the whole playlist...".to_string());
+}
+
+/// Queues the videos of a playlist listed for `download_playlist`.
+pub fn add_playlist_downloads(app: &mut App, download: PlaylistDownload, listing: PlaylistListing) {
+    let items = match listing {
+        Ok(items) if !items.is_empty() => items,
+        Ok(_) => {
+            app.status_message = Some("The playlist has no videos to download.".to_string());
+            return;
+        }
+        Err(e) => {
+            log::error!("Failed to fetch playlist {}: {}", download.url, e);
+            app.status_message = Some(format!("Failed to fetch playlist: {}", e));
+            return;
+        }
+    };
+
+    let title = items[0].1.title.clone();
+    let mut count = 0;
+    for (video, item) in items {
+        // A video already downloading keeps its task, it isn't moved into the playlist
+        let queued = match &download.profile {
+            Some(profile) => {
+                app.download_manager
+                    .add_profile_task(&video, profile, &download.postprocess)
+            }
+            None => app
+                .download_manager
+                .add_task(&video, "best", &download.postprocess),
+        };
+        if queued {
+            app.download_manager.set_playlist(&video.id, Some(item));
+            count += 1;
+        }
+    }
+    app.download_manager.persist();
+    app.status_message = Some(format!("Queued {} downloads from {}.", count, title));
+}
+
+/// Rewrites the `.m3u` of the playlist a finished download belongs to, listing the
+/// files of its finished downloads in playlist order.
+pub fn write_playlist_m3u(app: &mut App, id: &str) {
+    let request = match app.download_manager.tasks.get(id) {
+        Some(task) if task.playlist.is_some() => task.request(),
+        _ => return,
+    };
+    let item = match &request.playlist {
+        Some(item) => item.clone(),
+        None => return,
+    };
+    let download_dir = local::resolve_path(&app.settings.download_directory);
+    let folder = match crate::sys::download::playlist_folder(
+        &request,
+        &download_dir,
+        &app.settings.output,
+    ) {
+        Some(folder) => folder,
+        None => return,
+    };
+
+    let mut finished: Vec<(u64, std::path::PathBuf)> = app
+        .download_manager
+        .playlist_tasks(&item.id)
+        .filter(|t| t.status == DownloadStatus::Finished)
+        .filter_map(|t| Some((t.playlist.as_ref()?.index, t.output_file.clone()?)))
+        .collect();
+    finished.sort();
+    let files: Vec<std::path::PathBuf> = finished.into_iter().map(|(_, file)| file).collect();
+
+    let content = local::m3u_content(&folder, &files);
+    let file = folder.join(format!("{}.m3u", local::folder_name(&item.title)));
+    tokio::spawn(async move {
+        match tokio::fs::write(&file, content).await {
+            Ok(()) => log::info!("Updated playlist file {}", file.display()),
+            Err(e) => log::error!("Failed to write playlist file: {}", e),
+        }
+    });
+}
+
+/// Adds or removes the highlighted subtitle from the download being set up.
+/// Returns false if the highlighted row is not a subtitle.
+pub fn toggle_format_subtitle(app: &mut App) -> bool {
+    let track = app
+        .selected_format_index
+        .and_then(|idx| app.format_subtitle_at(idx))
+        .cloned();
+    match track {
+        Some(track) => {
+            if let Some(pos) = app.selected_subtitles.iter().position(|t| *t == track) {
+                app.selected_subtitles.remove(pos);
+            } else {
+                app.selected_subtitles.push(track);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn cycle_subtitles(app: &mut App) {
+    if app.player.is_some() {
+        send_command(app, Command::Cycle("sub", Osd::Msg));
+        app.announce_subtitle = true;
+    }
+}
+
+pub fn toggle_subtitles(app: &mut App) {
+    send_command(app, Command::Cycle("sub-visibility", Osd::Msg));
+}
+
+pub fn open_subtitle_picker(app: &mut App) {
+    if app.player.is_none() {
+        app.status_message = Some("Nothing is playing.".to_string());
+        return;
+    }
+    app.selected_player_subtitle_index = Some(0);
+    app.previous_app_state = app.state;
+    app.state = AppState::SubtitlePicker;
+}
+
+/// Switches to the subtitle at `idx` in the picker, where the first row turns subtitles off.
+pub fn select_player_subtitle(app: &mut App, idx: usize) {
+    let (sid, label) = match idx.checked_sub(1) {
+        None => (serde_json::json!("no"), "Off".to_string()),
+        Some(i) => match app.player_subtitles.get(i) {
+            Some(track) => (serde_json::json!(track.id), track.label()),
+            None => return,
+        },
+    };
+    send_command(app, Command::SetProperty("sid", sid));
+    app.status_message = Some(format!("Subtitles: {}", label));
+    app.state = app.previous_app_state;
+}
+
+fn seek_to(app: &mut App, seconds: f64) {
+    send_command(app, Command::Seek(seconds, SeekMode::Absolute, Osd::MsgBar));
+    app.playback_time = seconds;
+}
+
+/// Jumps to the next or previous chapter. Going back restarts the current chapter
+/// first, unless it only just began.
+pub fn jump_chapter(app: &mut App, forward: bool) {
+    if app.player.is_none() {
+        return;
+    }
+
+    let chapters = app.playback_chapters();
+    if chapters.is_empty() {
+        // mpv may still know chapters we did not get from yt-dlp, e.g. embedded ones
+        let step = if forward { 1.0 } else { -1.0 };
+        send_command(app, Command::Add("chapter", step, Osd::Msg));
+        return;
+    }
+
+    let current = crate::model::chapter_at(chapters, app.playback_time);
+    let target = match current {
+        Some(i) if forward => i + 1,
+        None if forward => 0,
+        Some(i) if app.playback_time - chapters[i].start_time > 3.0 => i,
+        Some(i) => i.saturating_sub(1),
+        None => 0,
+    };
+
+    if let Some(chapter) = chapters.get(target).cloned() {
+        let count = chapters.len();
+        seek_to(app, chapter.start_time);
+        app.status_message = Some(format!(
+            "Chapter {}/{}: {}",
+            target + 1,
+            count,
+            chapter.title
+        ));
+    }
+}
+
+pub fn open_chapter_list(app: &mut App) {
+    if app.playback_chapters().is_empty() {
+        app.status_message = Some("No chapters.".to_string());
+        return;
+    }
+    app.selected_chapter_index =
+        Some(crate::model::chapter_at(app.playback_chapters(), app.playback_time).unwrap_or(0));
+    app.previous_app_state = app.state;
+    app.state = AppState::ChapterList;
+}
+
+pub fn play_chapter(app: &mut App, idx: usize) {
+    if let Some(chapter) = app.playback_chapters().get(idx).cloned() {
+        seek_to(app, chapter.start_time);
+        app.status_message = Some(format!("Chapter {}: {}", idx + 1, chapter.title));
+    }
+    app.state = app.previous_app_state;
+}
+
+/// Looks up SponsorBlock segments for a streamed video, the reply is picked up in `on_tick`.
+pub fn request_segments(app: &mut App, video: &Video) {
+    app.skip_segments.clear();
+    app.last_skip = None;
+    app.unskipped_segments.clear();
+    if app.settings.sponsorblock.enabled
+        && video.video_type == VideoType::Video
+        && video.url.starts_with("http")
+    {
+        let _ = app.segments_tx.send(video.id.clone());
+    }
+}
+
+/// Seeks past the segment playback just entered, unless the user went back into it.
+pub fn skip_segment(app: &mut App) {
+    let time = app.playback_time;
+    if let Some(idx) = app.skip_segments.iter().position(|s| s.contains(time))
+        && !app.unskipped_segments.contains(&idx)
+    {
+        let segment = app.skip_segments[idx].clone();
+        seek_to(app, segment.end);
+        app.last_skip = Some(idx);
+        app.status_message = Some(format!(
+            "Skipped {} ({}) | z: Unskip",
+            segment.label(),
+            yt::format_duration(segment.end - segment.start)
+        ));
+    }
+}
+
+/// Jumps back to the start of the last skipped segment and plays it this time.
+pub fn unskip_segment(app: &mut App) {
+    let skipped = app
+        .last_skip
+        .take()
+        .and_then(|idx| Some((idx, app.skip_segments.get(idx)?.clone())));
+    match skipped {
+        Some((idx, segment)) => {
+            app.unskipped_segments.insert(idx);
+            seek_to(app, segment.start);
+            app.status_message = Some(format!("Playing {}", segment.label()));
+        }
+        None => {
+            app.status_message = Some("Nothing skipped.".to_string());
+        }
+    }
+}
+
+/// Speeds `step_speed` moves between.
+pub const SPEED_PRESETS: &[f64] = &[0.5, 0.75, 1.0, 1.25, 1.5, 1.75, 2.0, 3.0];
+
+pub fn change_volume(app: &mut App, step: f64) {
+    send_command(app, Command::Add("volume", step, Osd::MsgBar));
+}
+
+pub fn toggle_mute(app: &mut App) {
+    send_command(app, Command::Cycle("mute", Osd::Msg));
+}
+
+/// Moves to the next faster or slower preset speed.
+pub fn step_speed(app: &mut App, faster: bool) {
+    if app.player.is_none() {
+        return;
+    }
+    let current = app.playback_speed;
+    let next = if faster {
+        SPEED_PRESETS.iter().find(|&&s| s > current + 0.01)
+    } else {
+        SPEED_PRESETS.iter().rev().find(|&&s| s < current - 0.01)
+    };
+    match next {
+        Some(&speed) => {
+            send_command(app, Command::SetProperty("speed", speed.into()));
+            app.status_message = Some(format!("Speed: {}x", speed));
+        }
+        None => {
+            app.status_message = Some(format!("Speed: {}x (limit)", current));
+        }
+    }
+}
+
+pub fn toggle_loop(app: &mut App) {
+    if app.player.is_some() {
+        let value = if app.loop_file { "no" } else { "inf" };
+        send_command(app, Command::SetProperty("loop-file", value.into()));
+        app.status_message = Some(format!(
+            "Loop: {}",
+            if app.loop_file { "off" } else { "on" }
+        ));
+    }
+}
+
+/// Switches to the next audio track, wrapping around. Unlike mpv's own cycling
+/// this never turns audio off.
+pub fn cycle_audio_track(app: &mut App) {
+    if app.player.is_none() {
+        return;
+    }
+    if app.player_audio_tracks.len() < 2 {
+        app.status_message = Some("No other audio tracks.".to_string());
+        return;
+    }
+    let current = app.player_audio_tracks.iter().position(|t| t.selected);
+    let next = current.map_or(0, |i| (i + 1) % app.player_audio_tracks.len());
+    let track = app.player_audio_tracks[next].clone();
+    send_command(app, Command::SetProperty("aid", track.id.into()));
+    app.status_message = Some(format!("Audio: {}", track.label()));
+}
+
+/// Shows the current playback on desktop widgets, see `set_media_metadata`.
+pub fn update_media_metadata(app: &mut App) {
+    let title = app
+        .playback_title
+        .clone()
+        .unwrap_or_else(|| "Unknown".to_string());
+    let video = app.playback_video.clone();
+    set_media_metadata(app, &title, video.as_ref());
+}
+
+/// Title, channel, playlist, duration and cover for MPRIS. A thumbnail that is not
+/// downloaded yet is requested, and the metadata is sent again once it arrives.
+pub fn set_media_metadata(app: &mut App, title: &str, video: Option<&Video>) {
+    let mut cover_url = None;
+    // Local files have no thumbnail
+    if let Some(video) = video
+        && video.url.starts_with("http")
+    {
+        cover_url = crate::sys::image::cover_url(&video.id);
+        if cover_url.is_none() {
+            match app.image_cache.get(&video.id) {
+                Some(img) => match crate::sys::image::write_cover(&video.id, img) {
+                    Ok(url) => cover_url = Some(url),
+                    Err(e) => log::warn!("Failed to write cover for {}: {}", video.id, e),
+                },
+                None => {
+                    if let Some(url) = &video.thumbnail_url {
+                        let _ = app.image_tx.send((video.id.clone(), url.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    let duration = if app.playback_total > 0.0 {
+        Some(app.playback_total)
+    } else {
+        video.and_then(|v| yt::parse_duration(&v.duration_string))
+    };
+    let now_playing = NowPlaying {
+        title: title.to_string(),
+        artist: video.map(|v| v.channel.clone()).filter(|c| !c.is_empty()),
+        album: video.and_then(|v| v.parent_playlist_title.clone()),
+        duration: duration.map(std::time::Duration::from_secs_f64),
+        cover_url,
+    };
+    if let Some(mc) = &mut app.media_controller {
+        let _ = mc.set_metadata(&now_playing);
+    }
+}
@@ -1,7 +1,14 @@
 use crate::model::Video;
+use crate::model::download::{PlaylistDownload, PlaylistListing};
+use crate::model::history::History;
 use crate::model::local::LocalFile;
+use crate::model::queue::{PlayQueue, PlaylistEntry, PlaylistPage, PlaylistSource};
+use crate::model::subscription::{Feed, Subscription, Subscriptions};
 use crate::sys::media::{MediaController, MediaEvent};
-use crate::sys::{image as sys_image, local, yt};
+use crate::sys::mpv_ipc::MpvClient;
+use crate::sys::sponsorblock::{self, Segment};
+use crate::sys::video::VideoFeed;
+use crate::sys::{cache, image as sys_image, local, yt};
 use image::DynamicImage;
 use ratatui::layout::Rect;
 use ratatui::widgets::{ListState, TableState};
@@ -13,7 +20,7 @@ use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use crate::tui::components::logo::AnimationMode;
 use crate::tui::components::theme::Theme;
 
-use super::{AppAction, AppState, DownloadControl, DownloadManager, InputMode};
+use super::{AppState, DownloadControl, DownloadManager, InputMode, PendingPlayback};
 use crate::model::settings::Settings;
 
 pub struct App {
@@ -28,6 +35,9 @@ pub struct App {
     pub settings_cursor_position: usize,
     pub search_limit: u32,
     pub playlist_limit: u32,
+    pub max_concurrent_downloads: u32,
+    pub auto_resume_downloads: bool,
+    pub active_rate_limit: String, // Global limit currently applied, see actions::apply_rate_schedule
     // UI Layout Areas for Mouse Interaction
     pub search_bar_area: Rect,
     pub main_content_area: Rect,
@@ -36,6 +46,8 @@ pub struct App {
     pub action_menu_area: Option<Rect>,
     pub format_selection_area: Option<Rect>,
     pub settings_area: Option<Rect>,
+    pub queue_area: Option<Rect>,
+    pub history_area: Option<Rect>,
     pub settings_editing_item: Option<crate::tui::components::settings::SettingItem>,
 
     // UI States (persisted for scroll offset tracking)
@@ -45,6 +57,8 @@ pub struct App {
     pub action_menu_state: ListState,
     pub format_selection_state: TableState,
     pub settings_state: ListState,
+    pub queue_state: ListState,
+    pub history_state: ListState,
 
     // Mouse Tracking
     pub last_click_time: Option<Instant>,
@@ -75,29 +89,41 @@ pub struct App {
     // Search Progress
     pub search_progress: Option<f32>,
     pub search_offset: u32,
+    pub search_end: u32, // last entry requested for the view, some are filtered out
     pub is_searching: bool,
     pub current_search_id: usize,
     // If the current search was a direct URL
     pub is_url_mode: bool,
     pub is_playlist_mode: bool,
+    pub is_feed_mode: bool,
     // Messages/Status
     pub status_message: Option<String>,
     // Actions
-    pub pending_action: Option<(AppAction, String, String)>, // (Action, URL, Title)
+    pub pending_action: Option<PendingPlayback>,
+    pub resume_prompt: Option<(PendingPlayback, f64)>, // (Playback, resume position)
     // Images
     pub image_tx: UnboundedSender<(String, String)>, // (ID, URL)
     pub image_rx: UnboundedReceiver<(String, DynamicImage)>,
     pub image_cache: std::collections::HashMap<String, DynamicImage>,
     // Download / Formats
     pub format_tx: UnboundedSender<String>, // URL
-    pub format_rx: UnboundedReceiver<Result<Vec<crate::model::VideoFormat>, String>>,
+    pub format_rx: UnboundedReceiver<Result<crate::model::MediaFormats, String>>,
+    pub segments_tx: UnboundedSender<String>, // video id
+    pub segments_rx: UnboundedReceiver<(String, Vec<Segment>)>,
     pub formats: Vec<crate::model::VideoFormat>,
+    pub subtitles: Vec<crate::model::SubtitleTrack>, // offered for the video on the format screen
+    pub selected_subtitles: Vec<crate::model::SubtitleTrack>, // to download alongside
     pub action_video: Option<Video>,
     pub selected_format_index: Option<usize>,
     pub format_selection_mode: crate::app::state::FormatSelectionMode,
+    pub format_postprocess: crate::sys::config::PostProcessing, // for the download being set up
+    pub batch_videos: Vec<Video>, // waiting for a profile in FormatSelectionMode::Batch
+    pub batch_playlist: Option<String>, // playlist URL, listed once the profile is chosen
+    pub playlist_download_tx: UnboundedSender<PlaylistDownload>,
+    pub playlist_download_rx: UnboundedReceiver<(PlaylistDownload, PlaylistListing)>,
     // Background Download
     pub download_manager: DownloadManager,
-    pub new_download_tx: UnboundedSender<(Video, String)>, // Video, FormatID
+    pub new_download_tx: UnboundedSender<crate::model::download::DownloadRequest>,
     pub download_event_rx: UnboundedReceiver<crate::model::download::DownloadEvent>,
     pub download_control_tx: UnboundedSender<DownloadControl>,
     pub selected_download_index: Option<usize>,
@@ -109,21 +135,64 @@ pub struct App {
 
     // Playback
     pub playback_process: Option<tokio::process::Child>,
-    pub playback_cmd_tx: Option<UnboundedSender<String>>,
-    pub playback_res_rx: UnboundedReceiver<String>,
+    pub player: Option<MpvClient>,
+    pub player_events: UnboundedReceiver<crate::sys::mpv_ipc::Event>,
     pub playback_title: Option<String>,
+    pub playback_video: Option<Video>, // Video being tracked in history
     pub playback_time: f64,
     pub playback_total: f64,
     pub playback_duration_str: Option<String>,
     pub is_paused: bool,
     pub is_finishing: bool,
+    pub media_synced_at: Instant, // last position pushed to MPRIS
+    pub player_subtitles: Vec<crate::sys::mpv_ipc::Track>, // from the last track-list change
+    pub announce_subtitle: bool,  // show the selected track once the change arrives
+    pub sub_visible: Option<bool>,
+    pub player_audio_tracks: Vec<crate::sys::mpv_ipc::Track>,
+    pub volume: f64, // percent
+    pub muted: bool,
+    pub playback_speed: f64,
+    pub loop_file: bool,
+    pub selected_player_subtitle_index: Option<usize>,
+    pub subtitle_picker_state: ListState,
+    pub selected_chapter_index: Option<usize>,
+    pub chapter_list_state: ListState,
+    pub skip_segments: Vec<Segment>, // SponsorBlock segments of the stream
+    pub last_skip: Option<usize>,
+    pub unskipped_segments: std::collections::HashSet<usize>, // jumped back into, left alone
     pub terminal_loading: bool,
     pub terminal_loading_progress: f32,
     pub terminal_ready_url: Option<String>,
+    pub terminal_video: Option<Video>, // for MPRIS metadata
+    pub video_feed: Option<VideoFeed>, // terminal playback, shown in the video pane
+    pub video_area: Option<Rect>,      // inside the video pane, for kitty and sixel pictures
+    pub audio_player: bool,            // playback_process is the long-lived audio player
+    pub audio_player_idle: bool,       // mpv's idle-active, false while it has a track
+    pub player_playlist: Vec<PlaylistEntry>,
+    pub player_playlist_pos: usize,
+    pub playlist_source: Option<PlaylistSource>,
+    pub playlist_page_tx: UnboundedSender<(String, u32, u32)>, // url, start, end
+    pub playlist_page_rx: UnboundedReceiver<(String, Result<PlaylistPage, String>)>,
+    pub shuffle_pending: Option<(String, Vec<Video>)>, // playlist url, videos in view
     pub terminal_loading_error: Option<String>,
     pub terminal_ready_tx: UnboundedSender<Result<String, String>>,
     pub terminal_ready_rx: UnboundedReceiver<Result<String, String>>,
 
+    // Play Queue
+    pub play_queue: PlayQueue,
+    pub selected_queue_index: Option<usize>,
+    pub queue_playing: bool, // Current playback was started from the queue
+
+    // Watch History
+    pub history: History,
+    pub selected_history_index: Option<usize>,
+    pub history_saved_at: Instant,
+
+    // Subscriptions
+    pub subscriptions: Subscriptions,
+    pub feed_tx: UnboundedSender<(Vec<Subscription>, u32, usize)>, // channels, per-channel limit, search_id
+    pub feed_rx: UnboundedReceiver<(Result<Feed, String>, usize)>, // feed, search_id
+
     // Details Resolution
     pub details_tx: UnboundedSender<Vec<String>>,
     pub details_rx: UnboundedReceiver<Result<Video, String>>,
@@ -194,11 +263,69 @@ impl App {
         }
     }
 
+    /// Profiles listed above the formats. Watching has no use for them.
+    pub fn format_profiles(&self) -> &[crate::sys::config::DownloadProfile] {
+        match self.format_selection_mode {
+            crate::app::state::FormatSelectionMode::Watch => &[],
+            _ => &self.settings.profiles,
+        }
+    }
+
+    /// Subtitles listed below the formats, only when downloading a single video.
+    pub fn format_subtitles(&self) -> &[crate::model::SubtitleTrack] {
+        match self.format_selection_mode {
+            crate::app::state::FormatSelectionMode::Download => &self.subtitles,
+            _ => &[],
+        }
+    }
+
+    /// Rows on the format selection screen: profiles, then formats or the batch default,
+    /// then subtitles.
+    pub fn format_row_count(&self) -> usize {
+        let rest = match self.format_selection_mode {
+            crate::app::state::FormatSelectionMode::Batch => 1,
+            _ => self.formats.len(),
+        };
+        self.format_profiles().len() + rest + self.format_subtitles().len()
+    }
+
+    /// The subtitle at row `idx` of the format selection screen, if it is one.
+    pub fn format_subtitle_at(&self, idx: usize) -> Option<&crate::model::SubtitleTrack> {
+        let offset = self.format_profiles().len() + self.formats.len();
+        idx.checked_sub(offset)
+            .and_then(|i| self.format_subtitles().get(i))
+    }
+
+    /// Chapters of whatever is playing, empty if it has none.
+    pub fn playback_chapters(&self) -> &[crate::model::Chapter] {
+        self.playback_video
+            .as_ref()
+            .map(|v| v.chapters.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn toggle_auto_resume(&mut self) {
+        self.auto_resume_downloads = !self.auto_resume_downloads;
+        self.status_message = Some(format!(
+            "Resume Downloads: {}",
+            if self.auto_resume_downloads {
+                "On"
+            } else {
+                "Off"
+            }
+        ));
+        self.save_config();
+        self.reload_config();
+    }
+
     pub fn save_config(&self) {
         let config = crate::sys::config::Config {
             theme: self.theme.name.to_string(),
             search_limit: self.search_limit,
             playlist_limit: self.playlist_limit,
+            max_concurrent_downloads: self.max_concurrent_downloads,
+            auto_resume_downloads: self.auto_resume_downloads,
+            download_rate_limit: self.settings.download_rate_limit.clone(),
             download_directory: self.download_directory.clone(),
             animation: self.animation_mode,
             show_live: self.show_live,
@@ -278,6 +405,22 @@ impl App {
                 enabled: self.settings.enable_logging,
                 path: self.settings.log_path.clone(),
             },
+            cache: crate::sys::config::Cache {
+                enabled: self.settings.cache_enabled,
+                ttl_seconds: self.settings.cache_ttl_seconds,
+                max_entries: self.settings.cache_max_entries,
+            },
+            retry: crate::sys::config::Retry {
+                max_attempts: self.settings.retry_max_attempts,
+                backoff_seconds: self.settings.retry_backoff_seconds,
+            },
+            postprocess: self.settings.postprocess.clone(),
+            output: self.settings.output.clone(),
+            subtitles: self.settings.subtitles.clone(),
+            sponsorblock: self.settings.sponsorblock.clone(),
+            terminal_video: self.settings.terminal_video.clone(),
+            rate_schedule: self.settings.rate_schedule.clone(),
+            profiles: self.settings.profiles.clone(),
         };
         let _ = config.save();
     }
@@ -301,6 +444,8 @@ impl App {
 
                 self.search_limit = config.search_limit;
                 self.playlist_limit = config.playlist_limit;
+                self.max_concurrent_downloads = config.max_concurrent_downloads;
+                self.auto_resume_downloads = config.auto_resume_downloads;
                 self.download_directory = config.download_directory.clone();
                 self.animation_mode = config.animation;
                 self.show_live = config.show_live;
@@ -341,6 +486,7 @@ impl App {
         log::info!("Starting application cleanup...");
 
         // Stop playback
+        crate::app::actions::save_history_position(self);
         if let Some(mut child) = self.playback_process.take() {
             log::info!("Killing playback process...");
             let _ = child.start_kill();
@@ -400,8 +546,24 @@ impl App {
                 let tx = result_tx.clone();
                 let current_settings = task_settings.read().unwrap().clone();
                 tokio::spawn(async move {
-                    let (item_tx, mut item_rx) = mpsc::unbounded_channel();
+                    let cache_key =
+                        cache::search_key(&query, start, end, show_live, show_playlists);
+                    let cached = cache::get_search(&current_settings, &cache_key);
+
+                    // Serve cached results right away; stale ones are refreshed below
+                    let revalidating = cached.is_some();
+                    if let Some(cached) = cached {
+                        for video in cached.value {
+                            let _ = tx.send(Ok((yt::SearchResult::Video(video), id)));
+                        }
+                        let _ = tx.send(Ok((yt::SearchResult::Progress(1.0), id)));
+                        if cached.fresh {
+                            return;
+                        }
+                    }
 
+                    let (item_tx, mut item_rx) = mpsc::unbounded_channel();
+                    let task_settings = current_settings.clone();
                     let search_handle = tokio::spawn(async move {
                         if let Err(e) = yt::search_videos_flat(
                             &query,
@@ -409,7 +571,7 @@ impl App {
                             end,
                             show_live,
                             show_playlists,
-                            current_settings,
+                            task_settings,
                             item_tx.clone(),
                         )
                         .await
@@ -418,17 +580,39 @@ impl App {
                         }
                     });
 
+                    let mut videos = Vec::new();
+                    let mut failed = false;
                     while let Some(res) = item_rx.recv().await {
                         match res {
+                            Ok(yt::SearchResult::Video(video)) => {
+                                videos.push(video.clone());
+                                if !revalidating {
+                                    let _ = tx.send(Ok((yt::SearchResult::Video(video), id)));
+                                }
+                            }
                             Ok(item) => {
-                                let _ = tx.send(Ok((item, id)));
+                                if !revalidating {
+                                    let _ = tx.send(Ok((item, id)));
+                                }
                             }
                             Err(e) => {
-                                let _ = tx.send(Err(e));
+                                failed = true;
+                                if revalidating {
+                                    log::warn!("Failed to refresh cached search: {}", e);
+                                } else {
+                                    let _ = tx.send(Err(e));
+                                }
                             }
                         }
                     }
                     let _ = search_handle.await;
+
+                    if !failed && !videos.is_empty() {
+                        cache::put_search(&current_settings, &cache_key, &videos);
+                        if revalidating {
+                            let _ = tx.send(Ok((yt::SearchResult::Refresh(videos), id)));
+                        }
+                    }
                 });
             }
         });
@@ -456,8 +640,16 @@ impl App {
         let format_task = tokio::spawn(async move {
             while let Some(url) = format_req_rx.recv().await {
                 let current_settings = task_settings.read().unwrap().clone();
+                // Formats are only reused while fresh, stream availability changes too often
+                if let Some(cached) = cache::get_formats(&current_settings, &url)
+                    && cached.fresh
+                {
+                    let _ = format_res_tx.send(Ok(cached.value));
+                    continue;
+                }
                 match yt::get_video_formats(&url, &current_settings).await {
                     Ok(formats) => {
+                        cache::put_formats(&current_settings, &url, &formats);
                         let _ = format_res_tx.send(Ok(formats));
                     }
                     Err(e) => {
@@ -468,8 +660,59 @@ impl App {
         });
         abort_handles.push(format_task.abort_handle());
 
+        let (segments_tx, mut segments_req_rx) = mpsc::unbounded_channel::<String>();
+        let (segments_res_tx, segments_rx) = mpsc::unbounded_channel();
+
+        let task_settings = shared_settings.clone();
+        let segments_task = tokio::spawn(async move {
+            while let Some(id) = segments_req_rx.recv().await {
+                let current_settings = task_settings.read().unwrap().clone();
+                match sponsorblock::fetch_segments(&id, &current_settings).await {
+                    Ok(segments) => {
+                        let _ = segments_res_tx.send((id, segments));
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to fetch SponsorBlock segments for {}: {}", id, e);
+                    }
+                }
+            }
+        });
+        abort_handles.push(segments_task.abort_handle());
+
+        let (playlist_page_tx, mut playlist_page_req_rx) =
+            mpsc::unbounded_channel::<(String, u32, u32)>();
+        let (playlist_page_res_tx, playlist_page_rx) = mpsc::unbounded_channel();
+
+        let task_settings = shared_settings.clone();
+        let playlist_page_task = tokio::spawn(async move {
+            while let Some((url, start, end)) = playlist_page_req_rx.recv().await {
+                let current_settings = task_settings.read().unwrap().clone();
+                let result = yt::fetch_playlist_page(&url, start, end, &current_settings)
+                    .await
+                    .map_err(|e| e.to_string());
+                let _ = playlist_page_res_tx.send((url, result));
+            }
+        });
+        abort_handles.push(playlist_page_task.abort_handle());
+
+        let (playlist_download_tx, mut playlist_download_req_rx) =
+            mpsc::unbounded_channel::<PlaylistDownload>();
+        let (playlist_download_res_tx, playlist_download_rx) = mpsc::unbounded_channel();
+
+        let task_settings = shared_settings.clone();
+        let playlist_download_task = tokio::spawn(async move {
+            while let Some(download) = playlist_download_req_rx.recv().await {
+                let current_settings = task_settings.read().unwrap().clone();
+                let result = yt::fetch_playlist_items(&download.url, &current_settings)
+                    .await
+                    .map_err(|e| e.to_string());
+                let _ = playlist_download_res_tx.send((download, result));
+            }
+        });
+        abort_handles.push(playlist_download_task.abort_handle());
+
         let (new_download_tx, mut new_download_cmd_rx) =
-            mpsc::unbounded_channel::<(Video, String)>();
+            mpsc::unbounded_channel::<crate::model::download::DownloadRequest>();
         let (download_event_tx, download_event_rx) = mpsc::unbounded_channel();
         let (download_control_tx, mut download_control_rx) =
             mpsc::unbounded_channel::<DownloadControl>();
@@ -484,12 +727,19 @@ impl App {
         let download_manager_task = tokio::spawn(async move {
             // Map video_id to its PID for control (pause/resume/cancel)
             let mut active_downloads_pids: HashMap<String, u32> = HashMap::new();
+            // Downloads killed on purpose, so their exit is not reported as an error.
+            // The value tells whether to requeue the task once the process is gone.
+            let stopped: Arc<std::sync::Mutex<HashMap<String, bool>>> = Arc::default();
 
             loop {
                 tokio::select! {
+                    // New requests go first so a cancel sent right after one finds its PID
+                    biased;
+
                     // Handle new download requests
                     res = new_download_cmd_rx.recv() => {
-                        if let Some((video, format_id)) = res {
+                        if let Some(request) = res {
+                            let video = &request.video;
                             let event_tx = download_event_tx.clone();
                             let video_id = video.id.clone();
 
@@ -498,7 +748,7 @@ impl App {
                                 .to_string_lossy()
                                 .to_string();
 
-                            let mut child = match crate::sys::download::start_download(&video, &format_id, &resolved_download_dir, &current_settings).await {
+                            let mut child = match crate::sys::download::start_download(&request, &resolved_download_dir, &current_settings).await {
                                 Ok(child) => child,
                                 Err(e) => {
                                     log::error!("Failed to start download for video {}: {}", video_id, e);
@@ -514,6 +764,7 @@ impl App {
                             // Spawn a separate task to monitor this specific download's stdout/stderr and status
                             let monitor_event_tx = event_tx.clone();
                             let monitor_active_handles = task_active_handles.clone();
+                            let monitor_stopped = stopped.clone();
                             let v_id = video_id.clone();
                             let monitor_task = tokio::spawn(async move {
                                 let stdout = child
@@ -531,20 +782,21 @@ impl App {
 
                                 let mut last_progress_update = Instant::now();
                                 let min_update_interval = Duration::from_millis(500);
+                                let mut last_phase = crate::model::download::DownloadPhase::Downloading;
+                                let mut stderr_log = crate::sys::download::ErrorLog::default();
 
                                 loop {
                                     tokio::select! {
                                         Ok(Some(line)) = stdout_reader.next_line() => {
-                                            if let Some((progress, total_size, speed, eta)) =
-                                                crate::sys::download::parse_progress(&line)
-                                            {
-                                                if last_progress_update.elapsed() >= min_update_interval {
+                                            if let Some(path) = crate::sys::download::parse_output_file(&line) {
+                                                let _ = monitor_event_tx.send(crate::model::download::DownloadEvent::Output(v_id.clone(), path));
+                                            } else if let Some(progress) = crate::sys::download::parse_progress(&line) {
+                                                // Phase changes always go through, they are rare and easy to miss
+                                                if last_progress_update.elapsed() >= min_update_interval || progress.phase != last_phase {
+                                                    last_phase = progress.phase;
                                                     let _ = monitor_event_tx.send(crate::model::download::DownloadEvent::Update(
                                                         v_id.clone(),
                                                         progress,
-                                                        speed,
-                                                        eta,
-                                                        total_size,
                                                     ));
                                                     last_progress_update = Instant::now();
                                                 }
@@ -554,19 +806,40 @@ impl App {
                                         }
                                         Ok(Some(line)) = stderr_reader.next_line() => {
                                             log::warn!("yt-dlp stderr for {}: {}", v_id, line);
+                                            stderr_log.push(&line);
                                         }
                                         status = child.wait() => {
+                                            // The final file and ERROR lines may still be buffered in the pipes
+                                            while let Ok(Ok(Some(line))) = tokio::time::timeout(Duration::from_millis(200), stdout_reader.next_line()).await {
+                                                if let Some(path) = crate::sys::download::parse_output_file(&line) {
+                                                    let _ = monitor_event_tx.send(crate::model::download::DownloadEvent::Output(v_id.clone(), path));
+                                                }
+                                            }
+                                            while let Ok(Ok(Some(line))) = tokio::time::timeout(Duration::from_millis(200), stderr_reader.next_line()).await {
+                                                log::warn!("yt-dlp stderr for {}: {}", v_id, line);
+                                                stderr_log.push(&line);
+                                            }
+                                            let requeue = monitor_stopped.lock().ok().and_then(|mut s| s.remove(&v_id));
                                             match status {
+                                                _ if requeue == Some(true) => {
+                                                    let _ = monitor_event_tx.send(crate::model::download::DownloadEvent::Requeued(v_id.clone()));
+                                                }
+                                                _ if requeue.is_some() => {}
                                                 Ok(exit_status) => {
                                                     if exit_status.success() {
                                                         log::info!("Download finished successfully for video: {}", v_id);
                                                         let _ = monitor_event_tx.send(crate::model::download::DownloadEvent::Finished(v_id.clone()));
                                                     } else {
                                                         log::error!("Download failed for video {}: exit code {:?}", v_id, exit_status.code());
-                                                        let _ = monitor_event_tx.send(crate::model::download::DownloadEvent::Error(
-                                                            v_id.clone(),
-                                                            format!("Download failed with exit code: {:?}", exit_status.code()),
-                                                        ));
+                                                        let message = stderr_log.last_error.clone().unwrap_or_else(|| {
+                                                            format!("Download failed with exit code: {:?}", exit_status.code())
+                                                        });
+                                                        let event = if stderr_log.is_transient() {
+                                                            crate::model::download::DownloadEvent::TransientError(v_id.clone(), message)
+                                                        } else {
+                                                            crate::model::download::DownloadEvent::Error(v_id.clone(), message)
+                                                        };
+                                                        let _ = monitor_event_tx.send(event);
                                                     }
                                                 }
                                                 Err(e) => {
@@ -613,12 +886,27 @@ impl App {
                                 }
                                 DownloadControl::Cancel(id) => {
                                     if let Some(pid) = active_downloads_pids.remove(&id) {
+                                        if let Ok(mut s) = stopped.lock() {
+                                            s.insert(id.clone(), false);
+                                        }
                                         let _ = unsafe { kill(pid as i32, SIGTERM) };
-                                        // The monitor task for this child will eventually send the Error/Finished event
-                                        // and clean up its own child process.
+                                        let _ = unsafe { kill(pid as i32, SIGCONT) };
+                                        // The monitor task for this child cleans up its own child process
+                                        // and stays quiet about the exit, Canceled is all the app needs.
                                         let _ = download_event_tx.send(crate::model::download::DownloadEvent::Canceled(id));
                                     }
                                 }
+                                DownloadControl::Restart(id) => {
+                                    // yt-dlp continues from the .part file when started again
+                                    if let Some(pid) = active_downloads_pids.remove(&id) {
+                                        if let Ok(mut s) = stopped.lock() {
+                                            s.insert(id, true);
+                                        }
+                                        let _ = unsafe { kill(pid as i32, SIGTERM) };
+                                        // A paused process only acts on SIGTERM once continued
+                                        let _ = unsafe { kill(pid as i32, SIGCONT) };
+                                    }
+                                }
                             }
                         } else {
                             break;
@@ -629,7 +917,7 @@ impl App {
         });
         abort_handles.push(download_manager_task.abort_handle());
 
-        let (_, playback_res_rx) = mpsc::unbounded_channel();
+        let (_, player_events) = mpsc::unbounded_channel();
         let (terminal_ready_tx, terminal_ready_rx) =
             mpsc::unbounded_channel::<Result<String, String>>();
 
@@ -641,8 +929,21 @@ impl App {
             while let Some(ids) = details_req_rx.recv().await {
                 let res_tx = details_res_tx.clone();
                 let current_settings = task_settings.read().unwrap().clone();
-                if let Err(e) =
-                    yt::resolve_video_details(ids, current_settings, res_tx.clone()).await
+                let (item_tx, mut item_rx) = mpsc::unbounded_channel();
+                let result =
+                    yt::resolve_video_details(ids, current_settings.clone(), item_tx).await;
+
+                let mut reported = false;
+                while let Ok(res) = item_rx.try_recv() {
+                    match &res {
+                        Ok(video) => cache::put_video(&current_settings, video),
+                        Err(_) => reported = true,
+                    }
+                    let _ = res_tx.send(res);
+                }
+                // A yt-dlp that failed to run is only reported through the result
+                if let Err(e) = result
+                    && !reported
                 {
                     let _ = res_tx.send(Err(e.to_string()));
                 }
@@ -650,24 +951,85 @@ impl App {
         });
         abort_handles.push(details_task.abort_handle());
 
+        let (feed_tx, mut feed_req_rx) =
+            mpsc::unbounded_channel::<(Vec<Subscription>, u32, usize)>();
+        let (feed_res_tx, feed_rx) = mpsc::unbounded_channel();
+
+        let task_settings = shared_settings.clone();
+        let feed_task = tokio::spawn(async move {
+            while let Some((channels, limit, id)) = feed_req_rx.recv().await {
+                let current_settings = task_settings.read().unwrap().clone();
+                let mut set = tokio::task::JoinSet::new();
+                for sub in channels {
+                    let settings = current_settings.clone();
+                    set.spawn(async move {
+                        let res =
+                            yt::fetch_channel_uploads(&sub.channel_id, limit, &settings).await;
+                        (sub, res)
+                    });
+                }
+
+                let mut uploads = Vec::new();
+                let mut errors = Vec::new();
+                while let Some(Ok((sub, res))) = set.join_next().await {
+                    match res {
+                        Ok(mut videos) => {
+                            // Flat channel listings rarely name the uploader
+                            for v in &mut videos {
+                                v.channel = sub.channel.clone();
+                                v.channel_id = sub.channel_id.clone();
+                            }
+                            uploads.push(videos);
+                        }
+                        Err(e) => {
+                            log::error!("Failed to fetch feed for {}: {}", sub.channel, e);
+                            errors.push(sub.channel);
+                        }
+                    }
+                }
+
+                let res = if uploads.is_empty() && !errors.is_empty() {
+                    Err(format!("Failed to fetch feed for {}", errors.join(", ")))
+                } else {
+                    Ok(Feed {
+                        videos: crate::model::subscription::merge_feed(uploads),
+                        complete: errors.is_empty(),
+                    })
+                };
+                let _ = feed_res_tx.send((res, id));
+            }
+        });
+        abort_handles.push(feed_task.abort_handle());
+
         // Scan local files initially
-        let download_path_buf = local::resolve_path(&config.download_directory);
-        let download_path = download_path_buf.as_path();
-        let local_files = local::scan_local_files(download_path);
+        let download_roots = local::download_roots(&settings);
+        let local_files = local::scan_local_files(&download_roots);
         let mut download_manager = DownloadManager::new();
+        download_manager.restore(
+            crate::model::download::DownloadQueue::load(),
+            config.auto_resume_downloads,
+        );
 
         // Scan for download tasks (incomplete or finished/recoverable)
-        let scanned_tasks = local::scan_download_tasks(download_path);
+        let scanned_tasks = local::scan_download_tasks(&download_roots);
         for (video, format_id, status, path) in scanned_tasks {
             let id = video.id.clone();
-            if !download_manager.tasks.contains_key(&id) {
-                let mut task = crate::model::download::DownloadTask::new(video.clone(), format_id);
+            if let Some(task) = download_manager.tasks.get_mut(&id) {
+                // Already restored from the queue file, which knows more than the leftovers on disk
+                if task.info_json_path.is_none() {
+                    task.info_json_path = Some(path);
+                }
+            } else {
+                let mut task = crate::model::download::DownloadTask::new(
+                    video.clone(),
+                    format_id,
+                    config.postprocess.clone(),
+                );
                 task.status = status;
                 task.info_json_path = Some(path);
 
                 if task.status == crate::model::download::DownloadStatus::Finished {
                     task.progress = 100.0;
-                    task.total_size = "Cached".to_string();
                 }
 
                 download_manager.tasks.insert(id.clone(), task);
@@ -689,6 +1051,12 @@ impl App {
             settings_cursor_position: 0,
             search_limit: config.search_limit,
             playlist_limit: config.playlist_limit,
+            max_concurrent_downloads: config.max_concurrent_downloads,
+            auto_resume_downloads: config.auto_resume_downloads,
+            active_rate_limit: crate::sys::download::scheduled_rate_limit(
+                &settings,
+                chrono::Local::now().time(),
+            ),
             search_bar_area: Rect::default(),
             main_content_area: Rect::default(),
             downloads_area: None,
@@ -696,6 +1064,8 @@ impl App {
             action_menu_area: None,
             format_selection_area: None,
             settings_area: None,
+            queue_area: None,
+            history_area: None,
             settings_editing_item: None,
 
             main_list_state: ListState::default(),
@@ -704,6 +1074,8 @@ impl App {
             action_menu_state: ListState::default(),
             format_selection_state: TableState::default(),
             settings_state: ListState::default(),
+            queue_state: ListState::default(),
+            history_state: ListState::default(),
 
             last_click_time: None,
             last_click_pos: None,
@@ -728,21 +1100,33 @@ impl App {
             result_rx,
             search_progress: None,
             search_offset: 1,
+            search_end: 0,
             is_searching: false,
             current_search_id: 0,
             is_url_mode: false,
             is_playlist_mode: false,
+            is_feed_mode: false,
             status_message: None,
             pending_action: None,
+            resume_prompt: None,
             image_tx,
             image_rx,
             image_cache: std::collections::HashMap::new(),
             format_tx,
             format_rx,
+            segments_tx,
+            segments_rx,
             formats: Vec::new(),
+            subtitles: Vec::new(),
+            selected_subtitles: Vec::new(),
             action_video: None,
             selected_format_index: None,
             format_selection_mode: crate::app::state::FormatSelectionMode::default(),
+            format_postprocess: config.postprocess.clone(),
+            batch_videos: Vec::new(),
+            batch_playlist: None,
+            playlist_download_tx,
+            playlist_download_rx,
             download_manager,
             new_download_tx,
             download_event_rx,
@@ -753,20 +1137,57 @@ impl App {
             selected_local_file_index: None,
             selected_local_file_indices: std::collections::HashSet::new(),
             playback_process: None,
-            playback_cmd_tx: None,
-            playback_res_rx,
+            player: None,
+            player_events,
             playback_title: None,
+            playback_video: None,
             playback_time: 0.0,
             playback_total: 0.0,
             playback_duration_str: None,
             is_paused: false,
             is_finishing: false,
+            media_synced_at: Instant::now(),
+            player_subtitles: Vec::new(),
+            announce_subtitle: false,
+            sub_visible: None,
+            player_audio_tracks: Vec::new(),
+            volume: 100.0,
+            muted: false,
+            playback_speed: 1.0,
+            loop_file: false,
+            selected_player_subtitle_index: None,
+            subtitle_picker_state: ListState::default(),
+            selected_chapter_index: None,
+            chapter_list_state: ListState::default(),
+            skip_segments: Vec::new(),
+            last_skip: None,
+            unskipped_segments: std::collections::HashSet::new(),
             terminal_loading: false,
             terminal_loading_progress: 0.0,
             terminal_loading_error: None,
             terminal_ready_url: None,
+            terminal_video: None,
+            video_feed: None,
+            video_area: None,
+            audio_player: false,
+            audio_player_idle: true,
+            player_playlist: Vec::new(),
+            player_playlist_pos: 0,
+            playlist_source: None,
+            playlist_page_tx,
+            playlist_page_rx,
+            shuffle_pending: None,
             terminal_ready_tx,
             terminal_ready_rx,
+            play_queue: PlayQueue::load(),
+            selected_queue_index: None,
+            queue_playing: false,
+            history: History::load(),
+            selected_history_index: None,
+            history_saved_at: Instant::now(),
+            subscriptions: Subscriptions::load(),
+            feed_tx,
+            feed_rx,
             details_tx,
             details_rx,
             pending_resolution_ids: Vec::new(),
@@ -1,7 +1,8 @@
 use super::actions;
 use super::updates;
-use super::{App, AppAction, AppState, DownloadControl, InputMode};
+use super::{App, AppAction, AppState, DownloadControl, DownloadRow, InputMode, PendingPlayback};
 use crate::model::Video;
+use crate::model::queue::{QueueItem, QueueMode};
 use crate::sys::local;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use std::time::{Duration, Instant};
@@ -42,8 +43,8 @@ pub fn handle_mouse_event(app: &mut App, mouse: MouseEvent) {
                         if y >= list_start_y {
                             let relative_y = y - list_start_y;
                             let idx = app.format_selection_state.offset() + relative_y as usize;
-                            if idx < app.formats.len() {
-                                app.selected_format_index = Some(idx);
+                            if idx < app.format_row_count() {
+                                actions::select_format_row(app, idx);
                                 if double_click {
                                     handle_key_event(
                                         app,
@@ -82,6 +83,50 @@ pub fn handle_mouse_event(app: &mut App, mouse: MouseEvent) {
                 return;
             }
 
+            if app.state == AppState::History {
+                if let Some(area) = app.history_area {
+                    if is_in_rect(x, y, area) {
+                        // Entries are 2 lines tall
+                        let relative_y = y.saturating_sub(area.y).saturating_sub(1);
+                        let idx = app.history_state.offset() + (relative_y / 2) as usize;
+                        if idx < app.history.len() {
+                            app.selected_history_index = Some(idx);
+                            if double_click {
+                                actions::play_history_entry(app, idx);
+                            }
+                        }
+                    } else {
+                        app.state = app.previous_app_state;
+                    }
+                }
+                return;
+            }
+
+            if matches!(
+                app.state,
+                AppState::ResumePrompt | AppState::SubtitlePicker | AppState::ChapterList
+            ) {
+                return;
+            }
+
+            if app.state == AppState::Queue {
+                if let Some(area) = app.queue_area {
+                    if is_in_rect(x, y, area) {
+                        let relative_y = y.saturating_sub(area.y).saturating_sub(1);
+                        let idx = app.queue_state.offset() + relative_y as usize;
+                        if idx < app.play_queue.len() {
+                            app.selected_queue_index = Some(idx);
+                            if double_click {
+                                actions::play_queue_item(app, idx);
+                            }
+                        }
+                    } else {
+                        app.state = app.previous_app_state;
+                    }
+                }
+                return;
+            }
+
             // Playback Bar
             if let Some(area) = app.playback_bar_area {
                 if is_in_rect(x, y, area) {
@@ -119,8 +164,11 @@ pub fn handle_mouse_event(app: &mut App, mouse: MouseEvent) {
                             let list_start_y = area.y + 1 + 1; // Border + Header
                             if y >= list_start_y {
                                 let relative_y = y - list_start_y;
-                                let idx = app.downloads_active_state.offset() + relative_y as usize;
-                                if idx < app.download_manager.task_order.len() {
+                                let row = app.downloads_active_state.offset() + relative_y as usize;
+                                // Playlist summary rows can't be selected
+                                if let Some(DownloadRow::Task(idx)) =
+                                    app.download_manager.rows().get(row).copied()
+                                {
                                     app.selected_download_index = Some(idx);
                                     app.selected_local_file_index = None;
                                     if double_click {
@@ -184,6 +232,19 @@ pub fn handle_mouse_event(app: &mut App, mouse: MouseEvent) {
                 return;
             }
         }
+        // The wheel over the playback bar changes the volume
+        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+            if app
+                .playback_bar_area
+                .is_some_and(|area| is_in_rect(mouse.column, mouse.row, area)) =>
+        {
+            let step = if mouse.kind == MouseEventKind::ScrollUp {
+                5.0
+            } else {
+                -5.0
+            };
+            actions::change_volume(app, step);
+        }
         MouseEventKind::ScrollUp => match app.state {
             AppState::Results => updates::move_selection(app, -1),
             AppState::Downloads => {
@@ -210,10 +271,24 @@ pub fn handle_mouse_event(app: &mut App, mouse: MouseEvent) {
             AppState::FormatSelection => {
                 if let Some(idx) = app.selected_format_index {
                     if idx > 0 {
-                        app.selected_format_index = Some(idx - 1);
+                        actions::select_format_row(app, idx - 1);
                     }
                 }
             }
+            AppState::Queue => {
+                if let Some(idx) = app.selected_queue_index
+                    && idx > 0
+                {
+                    app.selected_queue_index = Some(idx - 1);
+                }
+            }
+            AppState::History => {
+                if let Some(idx) = app.selected_history_index
+                    && idx > 0
+                {
+                    app.selected_history_index = Some(idx - 1);
+                }
+            }
             _ => {}
         },
         MouseEventKind::ScrollDown => match app.state {
@@ -240,11 +315,25 @@ pub fn handle_mouse_event(app: &mut App, mouse: MouseEvent) {
             }
             AppState::FormatSelection => {
                 if let Some(idx) = app.selected_format_index {
-                    if idx < app.formats.len().saturating_sub(1) {
-                        app.selected_format_index = Some(idx + 1);
+                    if idx < app.format_row_count().saturating_sub(1) {
+                        actions::select_format_row(app, idx + 1);
                     }
                 }
             }
+            AppState::Queue => {
+                if let Some(idx) = app.selected_queue_index
+                    && idx < app.play_queue.len().saturating_sub(1)
+                {
+                    app.selected_queue_index = Some(idx + 1);
+                }
+            }
+            AppState::History => {
+                if let Some(idx) = app.selected_history_index
+                    && idx < app.history.len().saturating_sub(1)
+                {
+                    app.selected_history_index = Some(idx + 1);
+                }
+            }
             _ => {}
         },
         _ => {}
@@ -355,6 +444,9 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                     crate::tui::components::settings::SettingItem::ShowPlaylists => {
                                         app.toggle_playlists();
                                     }
+                                    crate::tui::components::settings::SettingItem::AutoResume => {
+                                        app.toggle_auto_resume();
+                                    }
                                     crate::tui::components::settings::SettingItem::SearchLimit => {
                                         app.input_mode = InputMode::Editing;
                                         app.settings_editing_item = Some(*item);
@@ -369,6 +461,20 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                         app.settings_cursor_position = app.settings_input.len();
                                         app.status_message = Some("Enter new Playlist Limit: ".to_string());
                                     }
+                                    crate::tui::components::settings::SettingItem::MaxDownloads => {
+                                        app.input_mode = InputMode::Editing;
+                                        app.settings_editing_item = Some(*item);
+                                        app.settings_input = app.max_concurrent_downloads.to_string();
+                                        app.settings_cursor_position = app.settings_input.len();
+                                        app.status_message = Some("Enter new Parallel Downloads limit: ".to_string());
+                                    }
+                                    crate::tui::components::settings::SettingItem::RateLimit => {
+                                        app.input_mode = InputMode::Editing;
+                                        app.settings_editing_item = Some(*item);
+                                        app.settings_input = app.settings.download_rate_limit.clone();
+                                        app.settings_cursor_position = app.settings_input.len();
+                                        app.status_message = Some("Enter new Speed Limit (e.g. 500K, 2M, empty for unlimited): ".to_string());
+                                    }
                                     crate::tui::components::settings::SettingItem::DownloadDirectory => {
                                         app.input_mode = InputMode::Editing;
                                         app.settings_editing_item = Some(*item);
@@ -427,27 +533,293 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                     }
                     _ => {}
                 },
+                AppState::Queue => {
+                    // Shifted J/K reorder instead of navigate
+                    let shifted = matches!(key.code, KeyCode::Char(c) if c.is_uppercase());
+                    match code {
+                        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('u') => {
+                            app.state = app.previous_app_state;
+                        }
+                        KeyCode::Char('k') if shifted => {
+                            if let Some(idx) = app.selected_queue_index
+                                && let Some(new_idx) = app.play_queue.move_item(idx, true)
+                            {
+                                app.selected_queue_index = Some(new_idx);
+                                app.play_queue.save();
+                                actions::rebuild_audio_playlist(app);
+                            }
+                        }
+                        KeyCode::Char('j') if shifted => {
+                            if let Some(idx) = app.selected_queue_index
+                                && let Some(new_idx) = app.play_queue.move_item(idx, false)
+                            {
+                                app.selected_queue_index = Some(new_idx);
+                                app.play_queue.save();
+                                actions::rebuild_audio_playlist(app);
+                            }
+                        }
+                        KeyCode::Up | KeyCode::Char('k') => {
+                            if let Some(idx) = app.selected_queue_index
+                                && idx > 0
+                            {
+                                app.selected_queue_index = Some(idx - 1);
+                            }
+                        }
+                        KeyCode::Down | KeyCode::Char('j') => {
+                            if let Some(idx) = app.selected_queue_index
+                                && idx < app.play_queue.len().saturating_sub(1)
+                            {
+                                app.selected_queue_index = Some(idx + 1);
+                            }
+                        }
+                        KeyCode::Enter => {
+                            if let Some(idx) = app.selected_queue_index {
+                                actions::play_queue_item(app, idx);
+                            }
+                        }
+                        KeyCode::Char('x') | KeyCode::Delete => {
+                            if let Some(idx) = app.selected_queue_index
+                                && app.play_queue.remove(idx).is_some()
+                            {
+                                app.play_queue.save();
+                                app.selected_queue_index = if app.play_queue.is_empty() {
+                                    None
+                                } else {
+                                    Some(idx.min(app.play_queue.len() - 1))
+                                };
+                                app.status_message = Some("Removed from queue.".to_string());
+                                actions::rebuild_audio_playlist(app);
+                            }
+                        }
+                        KeyCode::Char('c') => {
+                            app.play_queue.clear();
+                            app.play_queue.save();
+                            app.selected_queue_index = None;
+                            app.queue_playing = false;
+                            actions::rebuild_audio_playlist(app);
+                            app.status_message = Some("Queue cleared.".to_string());
+                        }
+                        KeyCode::Char('>') => {
+                            actions::play_next(app);
+                        }
+                        KeyCode::Char('<') => {
+                            actions::play_previous(app);
+                        }
+                        KeyCode::Char('p') => {
+                            actions::toggle_pause(app);
+                        }
+                        _ => {}
+                    }
+                }
+                AppState::History => match code {
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('h') => {
+                        app.state = app.previous_app_state;
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        if let Some(idx) = app.selected_history_index
+                            && idx > 0
+                        {
+                            app.selected_history_index = Some(idx - 1);
+                        }
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        if let Some(idx) = app.selected_history_index
+                            && idx < app.history.len().saturating_sub(1)
+                        {
+                            app.selected_history_index = Some(idx + 1);
+                        }
+                    }
+                    KeyCode::Enter => {
+                        if let Some(idx) = app.selected_history_index {
+                            actions::play_history_entry(app, idx);
+                        }
+                    }
+                    KeyCode::Char('x') | KeyCode::Delete => {
+                        if let Some(idx) = app.selected_history_index {
+                            app.history.remove(idx);
+                            app.history.save();
+                            app.selected_history_index = if app.history.is_empty() {
+                                None
+                            } else {
+                                Some(idx.min(app.history.len() - 1))
+                            };
+                        }
+                    }
+                    KeyCode::Char('c') => {
+                        app.history.clear();
+                        app.history.save();
+                        app.selected_history_index = None;
+                        app.status_message = Some("History cleared.".to_string());
+                    }
+                    _ => {}
+                },
+                AppState::ChapterList => match code {
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('g') => {
+                        app.state = app.previous_app_state;
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        if let Some(idx) = app.selected_chapter_index
+                            && idx > 0
+                        {
+                            app.selected_chapter_index = Some(idx - 1);
+                        }
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        if let Some(idx) = app.selected_chapter_index
+                            && idx + 1 < app.playback_chapters().len()
+                        {
+                            app.selected_chapter_index = Some(idx + 1);
+                        }
+                    }
+                    KeyCode::Enter => {
+                        if let Some(idx) = app.selected_chapter_index {
+                            actions::play_chapter(app, idx);
+                        }
+                    }
+                    _ => {}
+                },
+                AppState::SubtitlePicker => match code {
+                    KeyCode::Esc | KeyCode::Char('q') => {
+                        app.state = app.previous_app_state;
+                    }
+                    KeyCode::Char('c') if matches!(key.code, KeyCode::Char('C')) => {
+                        app.state = app.previous_app_state;
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        if let Some(idx) = app.selected_player_subtitle_index
+                            && idx > 0
+                        {
+                            app.selected_player_subtitle_index = Some(idx - 1);
+                        }
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        // The first row turns subtitles off
+                        if let Some(idx) = app.selected_player_subtitle_index
+                            && idx < app.player_subtitles.len()
+                        {
+                            app.selected_player_subtitle_index = Some(idx + 1);
+                        }
+                    }
+                    KeyCode::Enter => {
+                        if let Some(idx) = app.selected_player_subtitle_index {
+                            actions::select_player_subtitle(app, idx);
+                        }
+                    }
+                    _ => {}
+                },
+                AppState::ResumePrompt => match code {
+                    KeyCode::Char('y') | KeyCode::Enter => {
+                        actions::answer_resume_prompt(app, Some(true));
+                    }
+                    KeyCode::Char('n') => {
+                        actions::answer_resume_prompt(app, Some(false));
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') => {
+                        actions::answer_resume_prompt(app, None);
+                    }
+                    _ => {}
+                },
                 AppState::FormatSelection => match code {
                     KeyCode::Esc | KeyCode::Char('q') => {
                         app.state = AppState::ActionMenu;
                     }
+                    // Post-processing for this download (or batch) only
+                    KeyCode::Char('t')
+                        if app.format_selection_mode
+                            != crate::app::state::FormatSelectionMode::Watch =>
+                    {
+                        app.format_postprocess.embed_thumbnail =
+                            !app.format_postprocess.embed_thumbnail;
+                    }
+                    KeyCode::Char('m')
+                        if app.format_selection_mode
+                            != crate::app::state::FormatSelectionMode::Watch =>
+                    {
+                        app.format_postprocess.embed_metadata =
+                            !app.format_postprocess.embed_metadata;
+                    }
+                    KeyCode::Char('u')
+                        if app.format_selection_mode
+                            != crate::app::state::FormatSelectionMode::Watch =>
+                    {
+                        app.format_postprocess.embed_subtitles =
+                            !app.format_postprocess.embed_subtitles;
+                    }
+                    KeyCode::Char('s')
+                        if app.format_selection_mode
+                            != crate::app::state::FormatSelectionMode::Watch =>
+                    {
+                        app.format_postprocess.sponsorblock =
+                            app.format_postprocess.sponsorblock.next();
+                    }
+                    KeyCode::Char(' ') => {
+                        actions::toggle_format_subtitle(app);
+                    }
                     KeyCode::Up | KeyCode::Char('k') => {
                         if let Some(idx) = app.selected_format_index {
                             if idx > 0 {
-                                app.selected_format_index = Some(idx - 1);
+                                actions::select_format_row(app, idx - 1);
                             }
                         }
                     }
                     KeyCode::Down | KeyCode::Char('j') => {
                         if let Some(idx) = app.selected_format_index {
-                            if idx < app.formats.len().saturating_sub(1) {
-                                app.selected_format_index = Some(idx + 1);
+                            if idx < app.format_row_count().saturating_sub(1) {
+                                actions::select_format_row(app, idx + 1);
                             }
                         }
                     }
                     KeyCode::Enter => {
+                        let profile_count = app.format_profiles().len();
+
+                        if app.format_selection_mode
+                            == crate::app::state::FormatSelectionMode::Batch
+                        {
+                            let idx = app.selected_format_index.unwrap_or(profile_count);
+                            actions::finish_batch(app, (idx < profile_count).then_some(idx));
+                            app.state = app.previous_app_state;
+                            return;
+                        }
+
+                        if actions::toggle_format_subtitle(app) {
+                            return;
+                        }
+
+                        if let Some(idx) = app.selected_format_index
+                            && idx < profile_count
+                        {
+                            if let Some(video) = app.action_video.take() {
+                                let profile = app.settings.profiles[idx].clone();
+                                if app.download_manager.add_profile_task(
+                                    &video,
+                                    &profile,
+                                    &app.format_postprocess,
+                                ) {
+                                    if let Some(task) =
+                                        app.download_manager.tasks.get_mut(&video.id)
+                                    {
+                                        task.subtitles = app.selected_subtitles.clone();
+                                    }
+                                    app.status_message =
+                                        Some(format!("Download queued with {}.", profile.name));
+                                } else {
+                                    app.status_message =
+                                        Some("Already in the download queue.".to_string());
+                                }
+                            }
+                            if app.previous_app_state == AppState::Downloads {
+                                app.state = AppState::Downloads;
+                            } else {
+                                app.state = AppState::Results;
+                            }
+                            return;
+                        }
+
                         let selected_format_id = if let Some(idx) = app.selected_format_index {
-                            if let Some(fmt) = app.formats.get(idx) {
+                            if let Some(fmt) = idx
+                                .checked_sub(profile_count)
+                                .and_then(|i| app.formats.get(i))
+                            {
                                 // Construct format string for mpv --ytdl-format
                                 // Logic:
                                 // 1. If format has no audio, we MUST merge with bestaudio.
@@ -459,7 +831,7 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                     format!("{}/bestvideo+bestaudio/best", fmt.format_id)
                                 };
 
-                                Some(format_expr)
+                                Some((format_expr, fmt.resolution == "audio only"))
                             } else {
                                 None
                             }
@@ -467,22 +839,34 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                             None
                         };
 
-                        if let Some(format_id) = selected_format_id {
+                        if let Some((format_id, audio_only)) = selected_format_id {
                             if let Some(video) = app.action_video.clone() {
                                 match app.format_selection_mode {
                                     crate::app::state::FormatSelectionMode::Download => {
-                                        // Add to manager and start download
-                                        app.download_manager.add_task(&video, &format_id);
-                                        let _ =
-                                            app.new_download_tx.send((video.clone(), format_id));
+                                        // Queue it, the scheduler starts it once a slot is free
+                                        let queued = app.download_manager.add_task(
+                                            &video,
+                                            &format_id,
+                                            &app.format_postprocess,
+                                        );
+                                        if queued
+                                            && let Some(task) =
+                                                app.download_manager.tasks.get_mut(&video.id)
+                                        {
+                                            task.audio_only = audio_only;
+                                            task.subtitles = app.selected_subtitles.clone();
+                                        }
 
                                         if app.previous_app_state == AppState::Downloads {
                                             app.state = AppState::Downloads;
                                         } else {
                                             app.state = AppState::Results;
                                         }
-                                        app.status_message =
-                                            Some("Download started...".to_string());
+                                        app.status_message = Some(if queued {
+                                            "Download queued.".to_string()
+                                        } else {
+                                            "Already in the download queue.".to_string()
+                                        });
                                         app.action_video = None;
                                         return;
                                     }
@@ -493,8 +877,12 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                         actions::stop_playback(app);
 
                                         let stored_url = format!("{}::{}", url, format_id);
-                                        app.pending_action =
-                                            Some((AppAction::WatchExternal, stored_url, title));
+                                        app.pending_action = Some(PendingPlayback::new(
+                                            AppAction::WatchExternal,
+                                            stored_url,
+                                            title,
+                                            Some(video),
+                                        ));
 
                                         if app.previous_app_state == AppState::Downloads {
                                             app.state = AppState::Downloads;
@@ -504,6 +892,8 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                         app.action_video = None;
                                         return;
                                     }
+                                    // Batches are queued above
+                                    crate::app::state::FormatSelectionMode::Batch => {}
                                 }
                             }
                         }
@@ -531,18 +921,37 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                     KeyCode::Char('/') | KeyCode::Char('s') => {
                         app.input_mode = InputMode::Editing;
                     }
+                    // Shifted K/J reorder the download queue
+                    KeyCode::Char('k') if matches!(key.code, KeyCode::Char('K')) => {
+                        if let Some(idx) = app.selected_download_index
+                            && let Some(new_idx) = app.download_manager.move_task(idx, true)
+                        {
+                            app.selected_download_index = Some(new_idx);
+                            app.selected_download_indices.clear();
+                        }
+                    }
+                    KeyCode::Char('j') if matches!(key.code, KeyCode::Char('J')) => {
+                        if let Some(idx) = app.selected_download_index
+                            && let Some(new_idx) = app.download_manager.move_task(idx, false)
+                        {
+                            app.selected_download_index = Some(new_idx);
+                            app.selected_download_indices.clear();
+                        }
+                    }
+                    KeyCode::Char('n') => {
+                        actions::download_next(app);
+                    }
                     KeyCode::Up | KeyCode::Char('k') => {
                         if let Some(idx) = app.selected_local_file_index {
                             if idx > 0 {
                                 app.selected_local_file_index = Some(idx - 1);
-                            } else if !app.download_manager.task_order.is_empty() {
+                            } else if let Some(last) = app.download_manager.last_task() {
                                 app.selected_local_file_index = None;
-                                app.selected_download_index =
-                                    Some(app.download_manager.task_order.len() - 1);
+                                app.selected_download_index = Some(last);
                             }
                         } else if let Some(idx) = app.selected_download_index {
-                            if idx > 0 {
-                                app.selected_download_index = Some(idx - 1);
+                            if let Some(prev) = app.download_manager.neighbour_task(idx, false) {
+                                app.selected_download_index = Some(prev);
                             }
                         } else {
                             if !app.local_files.is_empty() {
@@ -554,8 +963,8 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                     }
                     KeyCode::Down | KeyCode::Char('j') => {
                         if let Some(idx) = app.selected_download_index {
-                            if idx < app.download_manager.task_order.len() - 1 {
-                                app.selected_download_index = Some(idx + 1);
+                            if let Some(next) = app.download_manager.neighbour_task(idx, true) {
+                                app.selected_download_index = Some(next);
                             } else if !app.local_files.is_empty() {
                                 app.selected_download_index = None;
                                 app.selected_local_file_index = Some(0);
@@ -606,22 +1015,27 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                                 .download_control_tx
                                                 .send(DownloadControl::Pause(task_id.clone()));
                                         }
+                                        // Restored from the queue file, there is no process to continue
+                                        crate::model::download::DownloadStatus::Paused
+                                            if task.pid.is_none() =>
+                                        {
+                                            if let Some(t) =
+                                                app.download_manager.tasks.get_mut(task_id)
+                                            {
+                                                t.status =
+                                                    crate::model::download::DownloadStatus::Pending;
+                                            }
+                                        }
                                         crate::model::download::DownloadStatus::Paused => {
-                                            let _ = app
-                                                .download_control_tx
-                                                .send(DownloadControl::Resume(task_id.clone()));
+                                            let task_id = task_id.clone();
+                                            actions::resume_download(app, &task_id);
                                         }
                                         crate::model::download::DownloadStatus::Canceled
                                         | crate::model::download::DownloadStatus::Error(_) => {
-                                            let video = task.video.clone();
-                                            let format_id = task.format_id.clone();
-                                            let _ = app.new_download_tx.send((video, format_id));
-
                                             if let Some(t) =
                                                 app.download_manager.tasks.get_mut(task_id)
                                             {
-                                                t.status =
-                                                    crate::model::download::DownloadStatus::Pending;
+                                                t.restart();
                                             }
                                         }
                                         _ => {}
@@ -637,10 +1051,9 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                     KeyCode::Char('x') => {
                         let mut handled = false;
                         if let Some(idx) = app.selected_download_index {
-                            if let Some(task_id) = app.download_manager.task_order.get(idx) {
-                                let _ = app
-                                    .download_control_tx
-                                    .send(DownloadControl::Cancel(task_id.clone()));
+                            if let Some(task_id) = app.download_manager.task_order.get(idx).cloned()
+                            {
+                                actions::cancel_download(app, &task_id);
                                 handled = true;
                             }
                         }
@@ -656,9 +1069,14 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                             if let Some(file) = app.local_files.get(idx) {
                                 let path = file.path.to_string_lossy().to_string();
                                 let name = file.name.clone();
+                                let video = file.to_video();
                                 actions::stop_playback(app);
-                                app.pending_action =
-                                    Some((crate::app::AppAction::WatchExternal, path, name));
+                                app.pending_action = Some(PendingPlayback::new(
+                                    crate::app::AppAction::WatchExternal,
+                                    path,
+                                    name,
+                                    Some(video),
+                                ));
                                 return;
                             }
                         }
@@ -670,6 +1088,63 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                     KeyCode::Char(']') => {
                         actions::seek(app, 30);
                     }
+                    KeyCode::Char('u') => {
+                        actions::open_queue(app);
+                    }
+                    KeyCode::Char('h') => {
+                        actions::open_history(app);
+                    }
+                    KeyCode::Char('f') => {
+                        actions::open_feed(app);
+                    }
+                    KeyCode::Char('c') if matches!(key.code, KeyCode::Char('C')) => {
+                        actions::open_subtitle_picker(app);
+                    }
+                    KeyCode::Char('c') => {
+                        actions::cycle_subtitles(app);
+                    }
+                    KeyCode::Char('v') => {
+                        actions::toggle_subtitles(app);
+                    }
+                    KeyCode::Char(',') => {
+                        actions::jump_chapter(app, false);
+                    }
+                    KeyCode::Char('.') => {
+                        actions::jump_chapter(app, true);
+                    }
+                    KeyCode::Char('g') => {
+                        actions::open_chapter_list(app);
+                    }
+                    KeyCode::Char('z') => {
+                        actions::unskip_segment(app);
+                    }
+                    KeyCode::Char('+') | KeyCode::Char('=') => {
+                        actions::change_volume(app, 5.0);
+                    }
+                    KeyCode::Char('-') => {
+                        actions::change_volume(app, -5.0);
+                    }
+                    KeyCode::Char('m') if matches!(key.code, KeyCode::Char('M')) => {
+                        actions::toggle_mute(app);
+                    }
+                    KeyCode::Char('}') => {
+                        actions::step_speed(app, true);
+                    }
+                    KeyCode::Char('{') => {
+                        actions::step_speed(app, false);
+                    }
+                    KeyCode::Char('l') if matches!(key.code, KeyCode::Char('L')) => {
+                        actions::toggle_loop(app);
+                    }
+                    KeyCode::Char('a') if matches!(key.code, KeyCode::Char('A')) => {
+                        actions::cycle_audio_track(app);
+                    }
+                    KeyCode::Char('>') => {
+                        actions::play_next(app);
+                    }
+                    KeyCode::Char('<') => {
+                        actions::play_previous(app);
+                    }
                     _ => {}
                 },
                 AppState::ActionMenu => {
@@ -692,12 +1167,22 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                         match action.action {
                             AppAction::PlayLocalExternal => {
                                 if let Some(idx) = app.selected_local_file_index {
-                                    if let Some((path, name)) = app.local_files.get(idx).map(|f| {
-                                        (f.path.to_string_lossy().to_string(), f.name.clone())
-                                    }) {
+                                    if let Some((path, name, video)) =
+                                        app.local_files.get(idx).map(|f| {
+                                            (
+                                                f.path.to_string_lossy().to_string(),
+                                                f.name.clone(),
+                                                f.to_video(),
+                                            )
+                                        })
+                                    {
                                         actions::stop_playback(app);
-                                        app.pending_action =
-                                            Some((AppAction::WatchExternal, path, name));
+                                        app.pending_action = Some(PendingPlayback::new(
+                                            AppAction::WatchExternal,
+                                            path,
+                                            name,
+                                            Some(video),
+                                        ));
                                         app.state = app.previous_app_state;
                                     }
                                 }
@@ -708,14 +1193,21 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                         let path = file.path.to_string_lossy().to_string();
                                         let name = file.name.clone();
                                         let is_audio = file.is_audio();
+                                        let video = file.to_video();
 
                                         actions::stop_playback(app);
 
                                         if is_audio {
-                                            app.pending_action =
-                                                Some((AppAction::ListenAudio, path, name));
+                                            app.pending_action = Some(PendingPlayback::new(
+                                                AppAction::ListenAudio,
+                                                path,
+                                                name,
+                                                Some(video),
+                                            ));
                                         } else {
+                                            actions::record_history(app, &video, false);
                                             app.terminal_ready_url = Some(path);
+                                            app.terminal_video = Some(video);
                                         }
                                         app.state = app.previous_app_state;
                                     }
@@ -723,12 +1215,22 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                             }
                             AppAction::PlayLocalAudio => {
                                 if let Some(idx) = app.selected_local_file_index {
-                                    if let Some((path, name)) = app.local_files.get(idx).map(|f| {
-                                        (f.path.to_string_lossy().to_string(), f.name.clone())
-                                    }) {
+                                    if let Some((path, name, video)) =
+                                        app.local_files.get(idx).map(|f| {
+                                            (
+                                                f.path.to_string_lossy().to_string(),
+                                                f.name.clone(),
+                                                f.to_video(),
+                                            )
+                                        })
+                                    {
                                         actions::stop_playback(app);
-                                        app.pending_action =
-                                            Some((AppAction::ListenAudio, path, name));
+                                        app.pending_action = Some(PendingPlayback::new(
+                                            AppAction::ListenAudio,
+                                            path,
+                                            name,
+                                            Some(video),
+                                        ));
                                         app.state = app.previous_app_state;
                                     }
                                 }
@@ -778,15 +1280,18 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                                  crate::model::download::DownloadStatus::Downloading => {
                                                      let _ = app.download_control_tx.send(DownloadControl::Pause(task_id.clone()));
                                                  }
+                                                 crate::model::download::DownloadStatus::Paused if task.pid.is_none() => {
+                                                     if let Some(t) = app.download_manager.tasks.get_mut(task_id) {
+                                                         t.status = crate::model::download::DownloadStatus::Pending;
+                                                     }
+                                                 }
                                                  crate::model::download::DownloadStatus::Paused => {
-                                                     let _ = app.download_control_tx.send(DownloadControl::Resume(task_id.clone()));
+                                                     let task_id = task_id.clone();
+                                                     actions::resume_download(app, &task_id);
                                                  }
                                                  crate::model::download::DownloadStatus::Canceled | crate::model::download::DownloadStatus::Error(_) => {
-                                                     let video = task.video.clone();
-                                                     let format_id = task.format_id.clone();
-                                                     let _ = app.new_download_tx.send((video, format_id));
                                                      if let Some(t) = app.download_manager.tasks.get_mut(task_id) {
-                                                         t.status = crate::model::download::DownloadStatus::Pending;
+                                                         t.restart();
                                                      }
                                                  }
                                                  _ => {}
@@ -805,15 +1310,18 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                         if let Some(task) = app.download_manager.tasks.get(task_id)
                                         {
                                             match task.status {
+                                                 crate::model::download::DownloadStatus::Paused if task.pid.is_none() => {
+                                                     if let Some(t) = app.download_manager.tasks.get_mut(task_id) {
+                                                         t.status = crate::model::download::DownloadStatus::Pending;
+                                                     }
+                                                 }
                                                  crate::model::download::DownloadStatus::Paused => {
-                                                     let _ = app.download_control_tx.send(DownloadControl::Resume(task_id.clone()));
+                                                     let task_id = task_id.clone();
+                                                     actions::resume_download(app, &task_id);
                                                  }
                                                  crate::model::download::DownloadStatus::Canceled | crate::model::download::DownloadStatus::Error(_) => {
-                                                     let video = task.video.clone();
-                                                     let format_id = task.format_id.clone();
-                                                     let _ = app.new_download_tx.send((video, format_id));
                                                      if let Some(t) = app.download_manager.tasks.get_mut(task_id) {
-                                                         t.status = crate::model::download::DownloadStatus::Pending;
+                                                         t.restart();
                                                      }
                                                  }
                                                  _ => {}
@@ -838,9 +1346,7 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                         };
 
                                         if is_active {
-                                            let _ = app
-                                                .download_control_tx
-                                                .send(DownloadControl::Cancel(task_id));
+                                            actions::cancel_download(app, &task_id);
                                         } else {
                                             // Delete files (json + part)
                                             if let Some(path) = json_path {
@@ -873,9 +1379,7 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                         };
 
                                         if is_active {
-                                            let _ = app
-                                                .download_control_tx
-                                                .send(DownloadControl::Cancel(task_id));
+                                            actions::cancel_download(app, &task_id);
                                         } else {
                                             if let Some(path) = json_path {
                                                 let _ = crate::sys::local::delete_task_files(&path);
@@ -891,11 +1395,71 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                 app.selected_download_index = None;
                                 app.state = app.previous_app_state;
                             }
+                            AppAction::AddToQueue | AppAction::AddToQueueAudio => {
+                                let mode = if action.action == AppAction::AddToQueueAudio {
+                                    QueueMode::Audio
+                                } else {
+                                    QueueMode::Video
+                                };
+                                let item = if app.previous_app_state == AppState::Downloads {
+                                    app.selected_local_file_index
+                                        .and_then(|idx| app.local_files.get(idx))
+                                        .map(|f| {
+                                            let mode =
+                                                if f.is_audio() { QueueMode::Audio } else { mode };
+                                            QueueItem::local(f, mode)
+                                        })
+                                } else {
+                                    app.selected_result_index
+                                        .and_then(|idx| app.search_results.get(idx))
+                                        .map(|v| QueueItem::new(v.clone(), mode))
+                                };
+                                if let Some(item) = item {
+                                    actions::enqueue(app, vec![item]);
+                                }
+                                app.state = app.previous_app_state;
+                            }
+                            AppAction::AddSelectedToQueue => {
+                                // Nothing selected means the whole current playlist view
+                                let mut indices: Vec<usize> =
+                                    if app.selected_playlist_indices.is_empty() {
+                                        (0..app.search_results.len()).collect()
+                                    } else {
+                                        app.selected_playlist_indices.iter().cloned().collect()
+                                    };
+                                indices.sort_unstable();
+                                let items: Vec<QueueItem> = indices
+                                    .iter()
+                                    .filter_map(|&idx| app.search_results.get(idx))
+                                    .filter(|v| v.video_type == crate::model::VideoType::Video)
+                                    .map(|v| QueueItem::new(v.clone(), QueueMode::Video))
+                                    .collect();
+                                actions::enqueue(app, items);
+                                app.selected_playlist_indices.clear();
+                                app.state = app.previous_app_state;
+                            }
+                            AppAction::PlayAll | AppAction::ShufflePlay => {
+                                actions::play_playlist(
+                                    app,
+                                    action.action == AppAction::ShufflePlay,
+                                );
+                                app.state = app.previous_app_state;
+                            }
+                            AppAction::DownloadNext => {
+                                actions::download_next(app);
+                                app.state = app.previous_app_state;
+                            }
+                            AppAction::CycleRateLimit => {
+                                actions::cycle_task_rate_limit(app);
+                                app.state = app.previous_app_state;
+                            }
+                            AppAction::MarkFeedSeen => {
+                                actions::mark_feed_seen(app);
+                                app.state = app.previous_app_state;
+                            }
                             AppAction::CleanupLocalGarbage => {
-                                let download_path_buf =
-                                    local::resolve_path(&app.download_directory);
-                                let download_path = download_path_buf.as_path();
-                                match local::cleanup_garbage(download_path) {
+                                let roots = local::download_roots(&app.settings);
+                                match local::cleanup_garbage(&roots) {
                                     Ok(count) => {
                                         app.status_message =
                                             Some(format!("Cleaned {} garbage files.", count));
@@ -946,26 +1510,17 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                         AppAction::ViewPlaylist => {
                                             app.status_message =
                                                 Some("Attempting to view playlist...".to_string());
-                                            let (query, title) = if video.video_type
-                                                == crate::model::VideoType::Playlist
+                                            let query = actions::playlist_url(&video);
+                                            let title = if video.video_type
+                                                != crate::model::VideoType::Playlist
+                                                && video.parent_playlist_url.is_some()
                                             {
-                                                (
-                                                    format!(
-                                                        "https://www.youtube.com/playlist?list={}",
-                                                        video.id
-                                                    ),
-                                                    video.title.clone(),
-                                                )
-                                            } else if video.parent_playlist_url.is_some() {
-                                                (
-                                                    video.parent_playlist_url.clone().unwrap(),
-                                                    video
-                                                        .parent_playlist_title
-                                                        .clone()
-                                                        .unwrap_or_else(|| "Playlist".to_string()),
-                                                )
+                                                video
+                                                    .parent_playlist_title
+                                                    .clone()
+                                                    .unwrap_or_else(|| "Playlist".to_string())
                                             } else {
-                                                (video.url.clone(), video.title.clone())
+                                                video.title.clone()
                                             };
 
                                             let parent = video.clone();
@@ -979,6 +1534,7 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                             app.search_results.clear();
                                             app.selected_result_index = Some(0);
                                             app.search_offset = 1;
+                                            app.search_end = app.playlist_limit;
                                             app.is_playlist_mode = true;
                                             app.is_url_mode = true; // Viewing a specific playlist is effectively URL mode
 
@@ -1005,6 +1561,8 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                                 Some("Fetching formats...".to_string());
                                             app.format_selection_mode =
                                                 crate::app::state::FormatSelectionMode::Download;
+                                            app.format_postprocess =
+                                                app.settings.postprocess.clone();
                                         }
                                         AppAction::WatchExternal => {
                                             // Start Format Selection instead of direct play
@@ -1018,7 +1576,8 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                         }
                                         AppAction::WatchInTerminal => {
                                             actions::stop_playback(app);
-                                            actions::start_terminal_loading(app, url, title);
+                                            actions::record_history(app, &video, false);
+                                            actions::start_terminal_loading(app, url, video);
                                             app.state = app.previous_app_state;
                                         }
                                         AppAction::DownloadSelected => {
@@ -1029,38 +1588,29 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                                     app.search_results.get(idx).cloned()
                                                 })
                                                 .collect();
-
-                                            if selected_videos.is_empty() {
-                                                app.status_message =
-                                                    Some("No videos selected.".to_string());
-                                            } else {
-                                                for video in selected_videos {
-                                                    app.download_manager.add_task(&video, "best");
-                                                    let _ = app
-                                                        .new_download_tx
-                                                        .send((video, "best".to_string()));
-                                                }
-                                                app.status_message =
-                                                    Some("Starting downloads...".to_string());
-                                                app.state = app.previous_app_state;
-                                            }
+                                            actions::queue_batch(app, selected_videos);
                                         }
                                         AppAction::DownloadPlaylist => {
-                                            if let Some(_parent_url) = &video.parent_playlist_url {
-                                                app.status_message = Some("Playlist download from this context is not fully implemented. Downloading current view.".to_string());
-                                            }
-                                            let videos: Vec<Video> =
-                                                app.search_results.iter().cloned().collect();
-                                            for video in videos {
-                                                app.download_manager.add_task(&video, "best");
-                                                let _ = app
-                                                    .new_download_tx
-                                                    .send((video, "best".to_string()));
+                                            let playlist_url = if video.video_type
+                                                == crate::model::VideoType::Playlist
+                                            {
+                                                Some(actions::playlist_url(&video))
+                                            } else {
+                                                video
+                                                    .parent_playlist_url
+                                                    .clone()
+                                                    .or_else(|| actions::viewed_playlist_url(app))
+                                            };
+                                            match playlist_url {
+                                                Some(playlist_url) => {
+                                                    actions::download_playlist(app, playlist_url)
+                                                }
+                                                None => {
+                                                    app.status_message =
+                                                        Some("Not part of a playlist.".to_string());
+                                                    app.state = app.previous_app_state;
+                                                }
                                             }
-                                            app.status_message =
-                                                Some("Starting playlist download...".to_string());
-
-                                            app.state = app.previous_app_state;
                                         }
                                         AppAction::CopyUrlOrId => {
                                             let text_to_copy =
@@ -1106,8 +1656,17 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                             }
                                             app.state = app.previous_app_state;
                                         }
+                                        AppAction::Subscribe => {
+                                            actions::toggle_subscription(app, &video);
+                                            app.state = app.previous_app_state;
+                                        }
                                         _ => {
-                                            app.pending_action = Some((action.action, url, title));
+                                            app.pending_action = Some(PendingPlayback::new(
+                                                action.action,
+                                                url,
+                                                title,
+                                                Some(video),
+                                            ));
                                             app.state = app.previous_app_state;
                                         }
                                     }
@@ -1169,7 +1728,8 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                             app.selected_result_index = prev_idx;
                             app.selected_playlist_indices.clear();
                             app.is_playlist_mode = false;
-                            app.is_url_mode = app.search_query.starts_with("http");
+                            app.is_url_mode =
+                                app.is_feed_mode || app.search_query.starts_with("http");
                             app.status_message = Some("Returned to search results.".to_string());
                         }
                     }
@@ -1199,10 +1759,11 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                 if let Some(video) = app.search_results.get(idx).cloned() {
                                     if video.video_type != crate::model::VideoType::Playlist {
                                         actions::stop_playback(app);
-                                        app.pending_action = Some((
+                                        app.pending_action = Some(PendingPlayback::new(
                                             crate::app::AppAction::WatchExternal,
                                             format!("{}::best", video.url),
-                                            video.title,
+                                            video.title.clone(),
+                                            Some(video),
                                         ));
                                         return;
                                     }
@@ -1217,6 +1778,63 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                     KeyCode::Char(']') => {
                         actions::seek(app, 30);
                     }
+                    KeyCode::Char('u') => {
+                        actions::open_queue(app);
+                    }
+                    KeyCode::Char('h') => {
+                        actions::open_history(app);
+                    }
+                    KeyCode::Char('f') => {
+                        actions::open_feed(app);
+                    }
+                    KeyCode::Char('c') if matches!(key.code, KeyCode::Char('C')) => {
+                        actions::open_subtitle_picker(app);
+                    }
+                    KeyCode::Char('c') => {
+                        actions::cycle_subtitles(app);
+                    }
+                    KeyCode::Char('v') => {
+                        actions::toggle_subtitles(app);
+                    }
+                    KeyCode::Char(',') => {
+                        actions::jump_chapter(app, false);
+                    }
+                    KeyCode::Char('.') => {
+                        actions::jump_chapter(app, true);
+                    }
+                    KeyCode::Char('g') => {
+                        actions::open_chapter_list(app);
+                    }
+                    KeyCode::Char('z') => {
+                        actions::unskip_segment(app);
+                    }
+                    KeyCode::Char('+') | KeyCode::Char('=') => {
+                        actions::change_volume(app, 5.0);
+                    }
+                    KeyCode::Char('-') => {
+                        actions::change_volume(app, -5.0);
+                    }
+                    KeyCode::Char('m') if matches!(key.code, KeyCode::Char('M')) => {
+                        actions::toggle_mute(app);
+                    }
+                    KeyCode::Char('}') => {
+                        actions::step_speed(app, true);
+                    }
+                    KeyCode::Char('{') => {
+                        actions::step_speed(app, false);
+                    }
+                    KeyCode::Char('l') if matches!(key.code, KeyCode::Char('L')) => {
+                        actions::toggle_loop(app);
+                    }
+                    KeyCode::Char('a') if matches!(key.code, KeyCode::Char('A')) => {
+                        actions::cycle_audio_track(app);
+                    }
+                    KeyCode::Char('>') => {
+                        actions::play_next(app);
+                    }
+                    KeyCode::Char('<') => {
+                        actions::play_previous(app);
+                    }
                     _ => {}
                 },
             }
@@ -1245,6 +1863,36 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                                     app.reload_config();
                                 }
                             }
+                            Some(crate::tui::components::settings::SettingItem::MaxDownloads) => {
+                                if let Ok(n) = val.parse::<u32>()
+                                    && n > 0
+                                {
+                                    app.max_concurrent_downloads = n;
+                                    app.status_message =
+                                        Some(format!("Parallel Downloads set to {}", n));
+                                    app.save_config();
+                                    app.reload_config();
+                                }
+                            }
+                            Some(crate::tui::components::settings::SettingItem::RateLimit) => {
+                                let rate = val.trim().to_string();
+                                if crate::sys::download::is_valid_rate(&rate) {
+                                    app.settings.download_rate_limit = rate;
+                                    app.status_message = Some(format!(
+                                        "Download Speed Limit set to {}",
+                                        if app.settings.download_rate_limit.is_empty() {
+                                            "Unlimited"
+                                        } else {
+                                            &app.settings.download_rate_limit
+                                        }
+                                    ));
+                                    app.save_config();
+                                    app.reload_config();
+                                } else {
+                                    app.status_message =
+                                        Some(format!("Invalid speed limit: {}", rate));
+                                }
+                            }
                             Some(
                                 crate::tui::components::settings::SettingItem::DownloadDirectory,
                             ) => {
@@ -7,5 +7,8 @@ pub mod updates;
 pub use self::actions::{get_available_actions, handle_paste, perform_search, stop_playback};
 pub use self::app::App;
 pub use self::handlers::{handle_key_event, handle_mouse_event};
-pub use self::state::{Action, AppAction, AppState, DownloadControl, DownloadManager, InputMode};
+pub use self::state::{
+    Action, AppAction, AppState, DownloadControl, DownloadManager, DownloadRow, InputMode,
+    PendingPlayback,
+};
 pub use self::updates::on_tick;
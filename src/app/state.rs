@@ -1,5 +1,9 @@
 use crate::model::Video;
-use crate::model::download::DownloadTask;
+use crate::model::download::{
+    DownloadQueue, DownloadStatus, DownloadTask, PlaylistItem, QueuedDownload,
+};
+use crate::model::queue::{PlaylistEntry, PlaylistSource};
+use crate::sys::config::{DownloadProfile, PostProcessing};
 use crossterm::event::KeyCode;
 use std::collections::HashMap;
 
@@ -15,6 +19,7 @@ pub enum FormatSelectionMode {
     #[default]
     Download,
     Watch,
+    Batch, // profile picker for several videos at once
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -39,6 +44,17 @@ pub enum AppAction {
     ResumeSelectedDownloads,
     CopyUrlOrId,
     OpenInBrowser,
+    // Play Queue
+    AddToQueue,
+    AddToQueueAudio,
+    AddSelectedToQueue,
+    PlayAll,
+    ShufflePlay,
+    DownloadNext,
+    CycleRateLimit,
+    // Subscriptions
+    Subscribe,
+    MarkFeedSeen,
 }
 
 pub struct Action {
@@ -53,6 +69,32 @@ impl Action {
     }
 }
 
+/// Playback request picked up by the main loop.
+#[derive(Debug, Clone)]
+pub struct PendingPlayback {
+    pub action: AppAction,
+    pub url: String, // May carry a format as "url::format"
+    pub title: String,
+    pub video: Option<Video>,         // Used for history tracking
+    pub start: Option<f64>,           // Start position, None means "ask if resumable"
+    pub playlist: Vec<PlaylistEntry>, // Entries played after this one in the same mpv
+    pub playlist_source: Option<PlaylistSource>,
+}
+
+impl PendingPlayback {
+    pub fn new(action: AppAction, url: String, title: String, video: Option<Video>) -> Self {
+        Self {
+            action,
+            url,
+            title,
+            video,
+            start: None,
+            playlist: Vec::new(),
+            playlist_source: None,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum AppState {
     Search,
@@ -61,6 +103,11 @@ pub enum AppState {
     FormatSelection,
     Downloads,
     Settings,
+    Queue,
+    History,
+    ResumePrompt,
+    SubtitlePicker,
+    ChapterList,
 }
 
 #[derive(Debug)]
@@ -68,11 +115,20 @@ pub enum DownloadControl {
     Pause(String),
     Resume(String),
     Cancel(String),
+    Restart(String),
+}
+
+/// A row of the downloads table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadRow {
+    Playlist(usize), // summary above the tasks of a playlist, index of its first task
+    Task(usize),     // index into `task_order`
 }
 
 pub struct DownloadManager {
     pub tasks: HashMap<String, DownloadTask>,
     pub task_order: Vec<String>,
+    saved_queue: String, // last queue written to disk, to skip redundant writes
 }
 
 impl DownloadManager {
@@ -80,12 +136,248 @@ impl DownloadManager {
         Self {
             tasks: HashMap::new(),
             task_order: Vec::new(),
+            saved_queue: String::new(),
         }
     }
 
-    pub fn add_task(&mut self, video: &Video, format_id: &str) {
-        let task = DownloadTask::new(video.clone(), format_id.to_string());
+    /// Rebuilds tasks from the queue file. Downloads that were running when the app
+    /// closed are queued again with `auto_resume`, otherwise they come back paused.
+    pub fn restore(&mut self, queue: DownloadQueue, auto_resume: bool) {
+        for entry in queue.tasks {
+            let id = entry.video.id.clone();
+            if self.tasks.contains_key(&id) {
+                continue;
+            }
+
+            let mut task = DownloadTask::new(entry.video, entry.format_id, entry.postprocess);
+            task.status = match entry.status {
+                DownloadStatus::Downloading if auto_resume => DownloadStatus::Pending,
+                DownloadStatus::Downloading => DownloadStatus::Paused,
+                status => status,
+            };
+            task.info_json_path = entry.info_json_path;
+            task.rate_limit = entry.rate_limit;
+            task.attempts = entry.attempts;
+            task.retry_at = entry.retry_at;
+            task.profile = entry.profile;
+            task.audio_only = entry.audio_only;
+            task.subtitles = entry.subtitles;
+            task.playlist = entry.playlist;
+            task.output_file = entry.output_file;
+            if task.status == DownloadStatus::Finished {
+                task.progress = 100.0;
+            }
+
+            self.tasks.insert(id.clone(), task);
+            self.task_order.push(id);
+        }
+    }
+
+    pub fn snapshot(&self) -> DownloadQueue {
+        DownloadQueue {
+            tasks: self
+                .task_order
+                .iter()
+                .filter_map(|id| self.tasks.get(id))
+                .map(|t| QueuedDownload {
+                    video: t.video.clone(),
+                    format_id: t.format_id.clone(),
+                    status: t.status.clone(),
+                    info_json_path: t.info_json_path.clone(),
+                    rate_limit: t.rate_limit.clone(),
+                    attempts: t.attempts,
+                    retry_at: t.retry_at,
+                    postprocess: t.postprocess.clone(),
+                    profile: t.profile.clone(),
+                    audio_only: t.audio_only,
+                    subtitles: t.subtitles.clone(),
+                    playlist: t.playlist.clone(),
+                    output_file: t.output_file.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Writes the queue file if anything besides progress changed since the last write.
+    pub fn persist(&mut self) {
+        let queue = self.snapshot();
+        let serialized = serde_json::to_string(&queue).unwrap_or_default();
+        if serialized != self.saved_queue {
+            queue.save();
+            self.saved_queue = serialized;
+        }
+    }
+
+    /// Queues a download. A video already queued, running or paused is left alone and
+    /// `false` returned, only finished, failed or canceled tasks are replaced.
+    pub fn add_task(
+        &mut self,
+        video: &Video,
+        format_id: &str,
+        postprocess: &PostProcessing,
+    ) -> bool {
+        if self.tasks.get(&video.id).is_some_and(|t| {
+            matches!(
+                t.status,
+                DownloadStatus::Pending | DownloadStatus::Downloading | DownloadStatus::Paused
+            )
+        }) {
+            return false;
+        }
+        let task = DownloadTask::new(video.clone(), format_id.to_string(), postprocess.clone());
         self.tasks.insert(video.id.clone(), task);
-        self.task_order.push(video.id.clone());
+        if !self.task_order.contains(&video.id) {
+            self.task_order.push(video.id.clone());
+        }
+        true
+    }
+
+    pub fn add_profile_task(
+        &mut self,
+        video: &Video,
+        profile: &DownloadProfile,
+        postprocess: &PostProcessing,
+    ) -> bool {
+        if !self.add_task(video, &profile.format, postprocess) {
+            return false;
+        }
+        if let Some(task) = self.tasks.get_mut(&video.id) {
+            task.profile = Some(profile.clone());
+            task.audio_only = !profile.audio_format.is_empty();
+        }
+        true
+    }
+
+    /// Tasks in queue order, the tasks of each playlist downloaded as a whole grouped
+    /// under a summary row.
+    pub fn rows(&self) -> Vec<DownloadRow> {
+        let playlists: Vec<Option<&str>> = self
+            .task_order
+            .iter()
+            .map(|id| {
+                self.tasks
+                    .get(id)
+                    .and_then(|t| t.playlist.as_ref())
+                    .map(|p| p.id.as_str())
+            })
+            .collect();
+
+        // A playlist is listed where its first task is, wherever the others are
+        let mut rows = Vec::new();
+        let mut listed: Vec<&str> = Vec::new();
+        for (i, playlist) in playlists.iter().enumerate() {
+            match playlist {
+                None => rows.push(DownloadRow::Task(i)),
+                Some(id) if !listed.contains(id) => {
+                    listed.push(id);
+                    rows.push(DownloadRow::Playlist(i));
+                    rows.extend(
+                        (i..playlists.len())
+                            .filter(|&j| playlists[j] == Some(id))
+                            .map(DownloadRow::Task),
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+        rows
+    }
+
+    /// The task shown below or above the one at `idx`, following the table.
+    pub fn neighbour_task(&self, idx: usize, down: bool) -> Option<usize> {
+        let tasks: Vec<usize> = self
+            .rows()
+            .into_iter()
+            .filter_map(|row| match row {
+                DownloadRow::Task(i) => Some(i),
+                DownloadRow::Playlist(_) => None,
+            })
+            .collect();
+        let pos = tasks.iter().position(|&i| i == idx)?;
+        if down {
+            tasks.get(pos + 1).copied()
+        } else {
+            pos.checked_sub(1).map(|p| tasks[p])
+        }
+    }
+
+    /// The task in the last row of the table.
+    pub fn last_task(&self) -> Option<usize> {
+        self.rows().into_iter().rev().find_map(|row| match row {
+            DownloadRow::Task(i) => Some(i),
+            DownloadRow::Playlist(_) => None,
+        })
+    }
+
+    /// Tasks of the playlist with the given id.
+    pub fn playlist_tasks<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a DownloadTask> {
+        self.tasks
+            .values()
+            .filter(move |t| t.playlist.as_ref().is_some_and(|p| p.id == id))
+    }
+
+    /// Marks a task as part of a playlist downloaded as a whole.
+    pub fn set_playlist(&mut self, id: &str, playlist: Option<PlaylistItem>) {
+        if let Some(task) = self.tasks.get_mut(id) {
+            task.playlist = playlist;
+        }
+    }
+
+    /// Downloads holding a slot. Paused ones are left out so the queue keeps moving,
+    /// `resume_download` only continues them while a slot is free.
+    pub fn active_count(&self) -> usize {
+        self.tasks
+            .values()
+            .filter(|t| t.status == DownloadStatus::Downloading)
+            .count()
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.tasks
+            .values()
+            .filter(|t| t.status == DownloadStatus::Pending)
+            .count()
+    }
+
+    /// The pending task that starts next, in queue order.
+    pub fn next_pending(&self) -> Option<String> {
+        self.task_order
+            .iter()
+            .find(|id| {
+                self.tasks
+                    .get(*id)
+                    .is_some_and(|t| t.status == DownloadStatus::Pending)
+            })
+            .cloned()
+    }
+
+    /// Swaps the task at `idx` with its neighbour and returns its new index.
+    pub fn move_task(&mut self, idx: usize, up: bool) -> Option<usize> {
+        let target = if up { idx.checked_sub(1)? } else { idx + 1 };
+        if target >= self.task_order.len() || idx >= self.task_order.len() {
+            return None;
+        }
+        self.task_order.swap(idx, target);
+        Some(target)
+    }
+
+    /// Moves the task at `idx` in front of all other pending tasks and returns its new index.
+    pub fn move_to_front(&mut self, idx: usize) -> Option<usize> {
+        if idx >= self.task_order.len() {
+            return None;
+        }
+        let id = self.task_order.remove(idx);
+        let target = self
+            .task_order
+            .iter()
+            .position(|id| {
+                self.tasks
+                    .get(id)
+                    .is_some_and(|t| t.status == DownloadStatus::Pending)
+            })
+            .unwrap_or(self.task_order.len())
+            .min(idx);
+        self.task_order.insert(target, id);
+        Some(target)
     }
 }
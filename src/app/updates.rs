@@ -2,7 +2,8 @@ use super::actions;
 use super::{App, AppState, InputMode};
 use crate::model::download::DownloadEvent;
 use crate::sys::media::MediaEvent;
-use crate::sys::yt;
+use crate::sys::mpv_ipc::{Command, Event, Osd, SeekMode};
+use crate::sys::{cache, yt};
 
 pub fn on_tick(app: &mut App) {
     // Update Pet Animation
@@ -10,7 +11,12 @@ pub fn on_tick(app: &mut App) {
 
     // check for images - DO THIS FIRST
     while let Ok((id, img)) = app.image_rx.try_recv() {
+        let is_playing = app.playback_video.as_ref().is_some_and(|v| v.id == id);
         app.image_cache.insert(id, img);
+        if is_playing {
+            // The cover for MPRIS was waiting for this
+            actions::update_media_metadata(app);
+        }
     }
 
     // check for search results
@@ -23,24 +29,23 @@ pub fn on_tick(app: &mut App) {
                 }
                 match item {
                     yt::SearchResult::Video(video) => {
-                        // Trigger image download if thumbnail exists, even if partial
-                        if let Some(url) = &video.thumbnail_url {
-                            if !app.image_cache.contains_key(&video.id) {
-                                let _ = app.image_tx.send((video.id.clone(), url.clone()));
+                        push_search_result(app, video);
+                    }
+                    yt::SearchResult::Refresh(videos) => {
+                        // Update the cached page in place, keeping details resolved meanwhile
+                        for video in videos {
+                            match app.search_results.iter_mut().find(|v| v.id == video.id) {
+                                Some(existing) => {
+                                    if existing.is_partial || !video.is_partial {
+                                        *existing = video;
+                                    }
+                                }
+                                None => push_search_result(app, video),
                             }
                         }
-
-                        if video.is_partial && video.video_type == crate::model::VideoType::Video {
-                            app.pending_resolution_ids.push(video.url.clone());
-                        }
-
-                        app.search_results.push(video);
-                        if app.selected_result_index.is_none() {
-                            app.selected_result_index = Some(0);
-                            request_image_for_selection(app);
-                        }
-                        if app.state == AppState::Search {
-                            app.state = AppState::Results;
+                        if !app.pending_resolution_ids.is_empty() {
+                            let items: Vec<String> = app.pending_resolution_ids.drain(..).collect();
+                            let _ = app.details_tx.send(items);
                         }
                     }
                     yt::SearchResult::Progress(progress) => {
@@ -73,6 +78,51 @@ pub fn on_tick(app: &mut App) {
         }
     }
 
+    // check for the subscription feed
+    while let Ok((res, id)) = app.feed_rx.try_recv() {
+        if id != app.current_search_id {
+            log::debug!("Ignoring stale feed for ID: {}", id);
+            continue;
+        }
+        app.is_searching = false;
+        app.search_progress = None;
+        match res {
+            Ok(crate::model::subscription::Feed { videos, complete }) => {
+                // Partial feeds could drop ids that are only missing for now
+                if complete {
+                    app.subscriptions.prune_seen(&videos);
+                    app.subscriptions.save();
+                }
+                let unseen = videos
+                    .iter()
+                    .filter(|v| !app.subscriptions.is_seen(&v.id))
+                    .count();
+                let total = videos.len();
+                for video in videos {
+                    push_search_result(app, video);
+                }
+                app.status_message = Some(if total == 0 {
+                    "No videos in feed.".to_string()
+                } else if complete {
+                    format!("Feed: {} new of {} videos.", unseen, total)
+                } else {
+                    format!(
+                        "Feed: {} new of {} videos (some channels failed).",
+                        unseen, total
+                    )
+                });
+                if !app.pending_resolution_ids.is_empty() {
+                    let items: Vec<String> = app.pending_resolution_ids.drain(..).collect();
+                    let _ = app.details_tx.send(items);
+                }
+            }
+            Err(e) => {
+                log::error!("Feed error: {}", e);
+                app.status_message = Some(format!("Error: {}", e));
+            }
+        }
+    }
+
     // Flush pending resolutions periodically (e.g. if we have >= 5 items)
     if app.pending_resolution_ids.len() >= 5 {
         let items: Vec<String> = app.pending_resolution_ids.drain(..).collect();
@@ -119,9 +169,16 @@ pub fn on_tick(app: &mut App) {
     if let Ok(res) = app.format_rx.try_recv() {
         match res {
             Ok(formats) => {
-                app.formats = formats;
-                if !app.formats.is_empty() {
-                    app.selected_format_index = Some(0);
+                app.formats = formats.formats;
+                app.selected_subtitles = crate::model::preferred_subtitles(
+                    &formats.subtitles,
+                    &app.settings.subtitles.languages,
+                    app.settings.subtitles.auto_captions,
+                );
+                app.subtitles = formats.subtitles;
+                if app.format_row_count() > 0 {
+                    app.selected_format_index = None;
+                    actions::select_format_row(app, 0);
                 }
                 // If we were waiting (Loading), switch to FormatSelection
                 if app.input_mode == InputMode::Loading {
@@ -139,24 +196,33 @@ pub fn on_tick(app: &mut App) {
     // Check for download events
     while let Ok(event) = app.download_event_rx.try_recv() {
         match event {
-            DownloadEvent::Update(id, progress, speed, eta, total_size) => {
+            DownloadEvent::Update(id, progress) => {
                 if let Some(task) = app.download_manager.tasks.get_mut(&id) {
                     task.status = crate::model::download::DownloadStatus::Downloading;
-                    task.progress = progress;
-                    task.speed = speed;
-                    task.eta = eta;
-                    task.total_size = total_size;
+                    if progress.phase == crate::model::download::DownloadPhase::Downloading {
+                        task.progress = progress.percent().unwrap_or(task.progress);
+                        task.stats = progress;
+                    } else {
+                        // Post-processing keeps the sizes of the finished download
+                        task.progress = 100.0;
+                        task.stats.phase = progress.phase;
+                        task.stats.postprocessor = progress.postprocessor;
+                        task.stats.speed = None;
+                        task.stats.eta = None;
+                    }
                 }
             }
             DownloadEvent::Finished(id) => {
                 if let Some(task) = app.download_manager.tasks.get_mut(&id) {
                     task.status = crate::model::download::DownloadStatus::Finished;
                     task.progress = 100.0;
-                    task.speed = String::new();
-                    task.eta = String::new();
+                    task.attempts = 0;
+                    task.stats.speed = None;
+                    task.stats.eta = None;
                 }
                 app.selected_download_indices.clear();
                 app.selected_download_index = None;
+                actions::write_playlist_m3u(app, &id);
                 actions::refresh_local_files(app);
             }
             DownloadEvent::Error(id, error) => {
@@ -167,6 +233,9 @@ pub fn on_tick(app: &mut App) {
                     }
                 }
             }
+            DownloadEvent::TransientError(id, error) => {
+                actions::schedule_retry(app, &id, error);
+            }
             DownloadEvent::Started(id, pid) => {
                 log::info!("Download started for video {} with PID {}", id, pid);
                 if let Some(task) = app.download_manager.tasks.get_mut(&id) {
@@ -184,49 +253,97 @@ pub fn on_tick(app: &mut App) {
                     task.status = crate::model::download::DownloadStatus::Downloading;
                 }
             }
+            DownloadEvent::Output(id, path) => {
+                if let Some(task) = app.download_manager.tasks.get_mut(&id) {
+                    task.output_file = Some(path);
+                }
+            }
+            DownloadEvent::Requeued(id) => {
+                if let Some(task) = app.download_manager.tasks.get_mut(&id) {
+                    task.status = crate::model::download::DownloadStatus::Pending;
+                    task.pid = None;
+                    task.stats.speed = None;
+                    task.stats.eta = None;
+                }
+            }
             DownloadEvent::Canceled(id) => {
                 if let Some(task) = app.download_manager.tasks.get_mut(&id) {
                     task.status = crate::model::download::DownloadStatus::Canceled;
-                    task.speed = String::new();
-                    task.eta = String::new();
+                    task.stats.speed = None;
+                    task.stats.eta = None;
                 }
                 actions::refresh_local_files(app);
             }
         }
     }
 
+    actions::apply_rate_schedule(app);
+    actions::retry_due_downloads(app);
+    actions::start_pending_downloads(app);
+    app.download_manager.persist();
+
     // Check for media events
     while let Ok(event) = app.media_rx.try_recv() {
         match event {
+            // Pause state and MPRIS status follow from mpv's pause event
             MediaEvent::Play => {
-                actions::send_command(app, "{\"command\": [\"set_property\", \"pause\", false]}\n");
-                app.is_paused = false;
-                if let Some(mc) = &mut app.media_controller {
-                    let _ = mc.set_playback_status(true);
-                }
+                actions::send_command(app, Command::SetProperty("pause", false.into()));
             }
             MediaEvent::Pause => {
-                actions::send_command(app, "{\"command\": [\"set_property\", \"pause\", true]}\n");
-                app.is_paused = true;
-                if let Some(mc) = &mut app.media_controller {
-                    let _ = mc.set_playback_status(false);
-                }
+                actions::send_command(app, Command::SetProperty("pause", true.into()));
             }
             MediaEvent::Toggle => {
-                actions::send_command(app, "{\"command\": [\"cycle\", \"pause\"]}\n");
-                app.is_paused = !app.is_paused;
-                if let Some(mc) = &mut app.media_controller {
-                    let _ = mc.set_playback_status(!app.is_paused);
-                }
+                actions::toggle_pause(app);
             }
             MediaEvent::Stop => {
                 actions::stop_playback(app);
             }
+            MediaEvent::Seek(offset) => {
+                actions::send_command(app, Command::Seek(offset, SeekMode::Relative, Osd::MsgBar));
+            }
+            MediaEvent::SetPosition(position) => {
+                actions::send_command(
+                    app,
+                    Command::Seek(position, SeekMode::Absolute, Osd::MsgBar),
+                );
+            }
+            MediaEvent::SetVolume(volume) => {
+                // mpv reports the new volume back, which updates MPRIS
+                let percent = (volume * 100.0).clamp(0.0, 130.0);
+                actions::send_command(app, Command::SetProperty("volume", percent.into()));
+            }
+            MediaEvent::OpenUri(uri) => {
+                app.status_message = Some(format!("Opening {}", uri));
+                app.pending_action = Some(super::PendingPlayback::new(
+                    super::AppAction::WatchExternal,
+                    uri.clone(),
+                    uri,
+                    None,
+                ));
+            }
+            MediaEvent::Quit => {
+                actions::stop_playback(app);
+                app.running = false;
+            }
             MediaEvent::Next => {
-                actions::send_command(app, "{\"command\": [\"seek\", 10, \"relative\"]}\n");
+                if app.play_queue.is_empty() && app.player_playlist.len() < 2 {
+                    actions::send_command(
+                        app,
+                        Command::Seek(10.0, SeekMode::Relative, Osd::Default),
+                    );
+                } else {
+                    actions::play_next(app);
+                }
             }
             MediaEvent::Previous => {
-                actions::send_command(app, "{\"command\": [\"seek\", -10, \"relative\"]}\n");
+                if app.play_queue.is_empty() && app.player_playlist.len() < 2 {
+                    actions::send_command(
+                        app,
+                        Command::Seek(-10.0, SeekMode::Relative, Osd::Default),
+                    );
+                } else {
+                    actions::play_previous(app);
+                }
             }
         }
     }
@@ -234,58 +351,185 @@ pub fn on_tick(app: &mut App) {
     // Check if playback process finished
     if let Some(ref mut child) = app.playback_process {
         if let Ok(Some(_)) = child.try_wait() {
+            actions::save_history_position(app);
             app.playback_process = None;
-            app.playback_cmd_tx = None;
+            app.player = None;
             app.playback_title = None;
+            app.playback_video = None;
             app.playback_time = 0.0;
             app.playback_total = 0.0;
             app.playback_duration_str = None;
             app.is_paused = false;
             app.is_finishing = false;
+            app.player_subtitles.clear();
+            app.sub_visible = None;
+            app.player_audio_tracks.clear();
+            app.muted = false;
+            app.playback_speed = 1.0;
+            app.loop_file = false;
+            app.skip_segments.clear();
+            app.last_skip = None;
+            if matches!(app.state, AppState::SubtitlePicker | AppState::ChapterList) {
+                app.state = app.previous_app_state;
+            }
             app.terminal_loading = false;
             app.terminal_loading_error = None;
             app.terminal_ready_url = None;
+            app.video_feed = None;
+            app.audio_player = false;
+            app.player_playlist.clear();
+            app.player_playlist_pos = 0;
+            app.playlist_source = None;
             app.status_message = Some("Stopped.".to_string());
             if let Some(mc) = &mut app.media_controller {
-                let _ = mc.set_playback_status(false);
+                let _ = mc.set_stopped();
+            }
+
+            // Continue with the next queued item
+            if app.queue_playing {
+                app.queue_playing = false;
+                if !actions::play_next(app) {
+                    app.status_message = Some("Queue finished.".to_string());
+                }
             }
         }
     }
 
-    // Process IPC responses for progress tracking
-    while let Ok(msg) = app.playback_res_rx.try_recv() {
-        if let Ok(val) = serde_json::from_str::<serde_json::Value>(&msg) {
-            if let Some(t) = val["data"].as_f64() {
-                if val["request_id"].as_u64() == Some(1) {
-                    app.playback_time = t;
-                } else if val["request_id"].as_u64() == Some(2) {
-                    app.playback_total = t;
+    // SponsorBlock segments for the current stream
+    while let Ok((id, segments)) = app.segments_rx.try_recv() {
+        if app.playback_video.as_ref().is_some_and(|v| v.id == id) {
+            log::info!("{} SponsorBlock segments for {}", segments.len(), id);
+            app.skip_segments = segments;
+            // Indices into the old list mean nothing in the new one
+            app.last_skip = None;
+            app.unskipped_segments.clear();
+        }
+    }
+
+    // Playlists to download as a whole
+    while let Ok((download, listing)) = app.playlist_download_rx.try_recv() {
+        actions::add_playlist_downloads(app, download, listing);
+    }
+
+    // More entries of the playlist being played
+    while let Ok((url, page)) = app.playlist_page_rx.try_recv() {
+        actions::add_playlist_page(app, &url, page);
+    }
+
+    // Property changes from mpv
+    while let Ok(event) = app.player_events.try_recv() {
+        match event {
+            Event::TimePos(t) => {
+                app.playback_time = t;
+                app.is_finishing = app.playback_total > 0.0 && app.playback_total - t < 2.0;
+                actions::skip_segment(app);
+                if !app.is_paused && app.media_synced_at.elapsed().as_secs() >= 1 {
+                    actions::sync_media_status(app);
+                }
+            }
+            Event::Duration(t) => {
+                let changed = (app.playback_total - t).abs() > 1.0;
+                app.playback_total = t;
+                if changed {
+                    actions::update_media_metadata(app);
+                }
+            }
+            Event::TrackList(tracks) => {
+                let (subtitles, rest): (Vec<_>, Vec<_>) =
+                    tracks.into_iter().partition(|t| t.kind == "sub");
+                app.player_subtitles = subtitles;
+                app.player_audio_tracks = rest.into_iter().filter(|t| t.kind == "audio").collect();
+                if app.announce_subtitle {
+                    app.announce_subtitle = false;
+                    let label = app
+                        .player_subtitles
+                        .iter()
+                        .find(|t| t.selected)
+                        .map(|t| t.label())
+                        .unwrap_or_else(|| "Off".to_string());
+                    app.status_message = Some(format!("Subtitles: {}", label));
+                }
+            }
+            Event::SubVisibility(visible) => {
+                // The first value is the initial state, not a toggle
+                if app.sub_visible.is_some_and(|v| v != visible) {
+                    app.status_message = Some(
+                        if visible {
+                            "Subtitles shown"
+                        } else {
+                            "Subtitles hidden"
+                        }
+                        .to_string(),
+                    );
+                }
+                app.sub_visible = Some(visible);
+            }
+            Event::Volume(volume) => {
+                app.volume = volume;
+                if let Some(mc) = &mut app.media_controller {
+                    let _ = mc.set_volume(volume / 100.0);
+                }
+            }
+            Event::Mute(muted) => {
+                app.muted = muted;
+            }
+            Event::Speed(speed) => {
+                app.playback_speed = speed;
+            }
+            Event::LoopFile(on) => {
+                app.loop_file = on;
+            }
+            Event::Pause(paused) => {
+                actions::set_paused(app, paused);
+            }
+            Event::EofReached(eof) => {
+                if eof {
+                    app.is_finishing = true;
+                }
+            }
+            Event::Idle(idle) if app.audio_player => {
+                // The audio player is meant to idle and waits for the next track. Going
+                // idle after one was loaded ends the playlist, also when the last track
+                // failed to load before reporting a duration.
+                let was_playing = !app.audio_player_idle;
+                app.audio_player_idle = idle;
+                if idle && was_playing {
+                    actions::finish_audio_playlist(app);
+                }
+            }
+            Event::Idle(idle) => {
+                // With idle=yes in mpv.conf, mpv stays open after the last file instead
+                // of quitting. Ending it runs the usual cleanup and queue advance above.
+                // It is also idle at startup, before a duration is known.
+                if idle
+                    && app.playback_total > 0.0
+                    && let Some(child) = &mut app.playback_process
+                {
+                    log::info!("mpv went idle, ending playback");
+                    let _ = child.start_kill();
+                }
+            }
+            Event::PlaylistPos(pos) => {
+                if !app.player_playlist.is_empty()
+                    && pos >= 0
+                    && pos as usize != app.player_playlist_pos
+                {
+                    actions::begin_playlist_entry(app, pos as usize);
                 }
             }
         }
     }
 
+    // Persist the watch position every now and then in case we never see a clean stop
+    if app.playback_video.is_some() && app.history_saved_at.elapsed().as_secs() >= 15 {
+        actions::save_history_position(app);
+    }
+
     // Update progress string and finishing state
     if app.playback_total > 0.0 {
         let current = yt::format_duration(app.playback_time);
         let total = yt::format_duration(app.playback_total);
         app.playback_duration_str = Some(format!("{}/{}", current, total));
-
-        app.is_finishing = app.playback_total - app.playback_time < 2.0;
-    }
-
-    // Trigger property requests if we are playing
-    if app.playback_cmd_tx.is_some() && !app.is_paused {
-        actions::send_command(
-            app,
-            "{\"command\": [\"get_property\", \"time-pos\"], \"request_id\": 1}
-",
-        );
-        actions::send_command(
-            app,
-            "{\"command\": [\"get_property\", \"duration\"], \"request_id\": 2}
-",
-        );
     }
 
     // Update terminal loading progress
@@ -337,6 +581,37 @@ pub fn move_selection(app: &mut App, delta: i32) {
     }
 }
 
+fn push_search_result(app: &mut App, mut video: crate::model::Video) {
+    if video.is_partial && video.video_type == crate::model::VideoType::Video {
+        // Use cached details if we have them, refreshing only once they are stale
+        match cache::get_video(&app.settings, &video.id) {
+            Some(cached) => {
+                if !cached.fresh {
+                    app.pending_resolution_ids.push(video.url.clone());
+                }
+                video = cached.value;
+            }
+            None => app.pending_resolution_ids.push(video.url.clone()),
+        }
+    }
+
+    // Trigger image download if thumbnail exists, even if partial
+    if let Some(url) = &video.thumbnail_url {
+        if !app.image_cache.contains_key(&video.id) {
+            let _ = app.image_tx.send((video.id.clone(), url.clone()));
+        }
+    }
+
+    app.search_results.push(video);
+    if app.selected_result_index.is_none() {
+        app.selected_result_index = Some(0);
+        request_image_for_selection(app);
+    }
+    if app.state == AppState::Search {
+        app.state = AppState::Results;
+    }
+}
+
 pub fn request_image_for_selection(app: &mut App) {
     if let Some(idx) = app.selected_result_index {
         if let Some(video) = app.search_results.get(idx) {
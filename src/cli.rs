@@ -1,15 +1,17 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(name = "Rataplay")]
 #[command(author = "Mojahid")]
 #[command(version = env!("CARGO_PKG_VERSION"))]
 #[command(disable_version_flag = true)]
+#[command(args_conflicts_with_subcommands = true)]
 #[command(help_template = "NAME:
    {name} - Terminal YouTube Search & Play
 
 USAGE:
    rataplay [query] [global options]
+   rataplay <command> [options]
 
 VERSION:
    {version}
@@ -31,6 +33,12 @@ DESCRIPTION:
 
 
 
+COMMANDS (headless, no TUI):
+{subcommands}
+
+   Exit codes: 0 success, 1 failure, 2 usage error, 3 no results,
+               4 missing dependency, 130 interrupted
+
 AUTHOR:
    {author}
 
@@ -44,4 +52,56 @@ pub struct Cli {
     /// print the version
     #[arg(short = 'v', long = "version", action = clap::ArgAction::Version)]
     pub show_version: Option<bool>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Search and print results (one per line, or JSON)
+    Search {
+        /// Search query or URL
+        query: String,
+
+        /// Print results as a JSON array
+        #[arg(long)]
+        json: bool,
+
+        /// Number of results (defaults to search_limit from config)
+        #[arg(short = 'n', long)]
+        limit: Option<u32>,
+    },
+    /// List the available formats of a video
+    Formats {
+        url: String,
+
+        /// Print formats as a JSON array
+        #[arg(long)]
+        json: bool,
+    },
+    /// Download a video, printing progress line by line
+    Download {
+        url: String,
+
+        /// Format ID as listed by `formats`
+        #[arg(short = 'f', long, default_value = "best")]
+        format: String,
+
+        /// Output directory (defaults to download_directory from config)
+        #[arg(short = 'o', long)]
+        output: Option<String>,
+
+        /// Speed limit such as 500K or 2M (defaults to the limit from config)
+        #[arg(short = 'r', long)]
+        limit_rate: Option<String>,
+    },
+    /// Play a video with mpv and wait until it ends
+    Play {
+        url: String,
+
+        /// Play audio only
+        #[arg(long)]
+        audio: bool,
+    },
 }
@@ -0,0 +1,278 @@
+use crate::cli::Command;
+use crate::model::Video;
+use crate::model::download::DownloadRequest;
+use crate::model::settings::Settings;
+use crate::sys::config::Config;
+use crate::sys::{download, local, process, yt};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::mpsc;
+
+// Exit codes for scripts. 2 is left to clap for usage errors.
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_NO_RESULTS: i32 = 3;
+pub const EXIT_MISSING_DEPENDENCY: i32 = 4;
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Runs a CLI subcommand without starting the TUI and returns the process exit code.
+pub async fn run(command: Command, config: &Config, settings: Settings) -> i32 {
+    let needs_mpv = matches!(command, Command::Play { .. });
+    match crate::sys::deps::check_dependencies(&settings) {
+        Ok(status) if needs_mpv && !status.mpv_installed => {
+            eprintln!("mpv is not installed or not in PATH.");
+            return EXIT_MISSING_DEPENDENCY;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Dependency check failed: {}", e);
+            return EXIT_MISSING_DEPENDENCY;
+        }
+    }
+
+    match command {
+        Command::Search { query, json, limit } => {
+            search(
+                query,
+                json,
+                limit.unwrap_or(config.search_limit),
+                config,
+                settings,
+            )
+            .await
+        }
+        Command::Formats { url, json } => formats(&url, json, &settings).await,
+        Command::Download {
+            url,
+            format,
+            output,
+            limit_rate,
+        } => {
+            if let Some(rate) = &limit_rate
+                && !download::is_valid_rate(rate)
+            {
+                eprintln!("Invalid rate limit: {}", rate);
+                return EXIT_FAILURE;
+            }
+            let dir = output.unwrap_or_else(|| settings.download_directory.clone());
+            download(&url, &format, limit_rate.as_deref(), &dir, &settings).await
+        }
+        Command::Play { url, audio } => play(&url, audio, &settings).await,
+    }
+}
+
+async fn search(query: String, json: bool, limit: u32, config: &Config, settings: Settings) -> i32 {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let show_live = config.show_live;
+    let show_playlists = config.show_playlists;
+
+    let handle = tokio::spawn(async move {
+        yt::search_videos_flat(
+            &query,
+            1,
+            limit.max(1),
+            show_live,
+            show_playlists,
+            settings,
+            tx,
+        )
+        .await
+    });
+
+    let mut videos = Vec::new();
+    let mut failed = false;
+    while let Some(res) = rx.recv().await {
+        match res {
+            Ok(yt::SearchResult::Video(video)) => videos.push(video),
+            Ok(yt::SearchResult::Progress(_) | yt::SearchResult::Refresh(_)) => {}
+            // The same error is returned by the search and printed below
+            Err(_) => failed = true,
+        }
+    }
+
+    match handle.await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            eprintln!("Search failed: {}", e);
+            failed = true;
+        }
+        Err(e) => {
+            eprintln!("Search task failed: {}", e);
+            failed = true;
+        }
+    }
+
+    if json {
+        match serde_json::to_string_pretty(&videos) {
+            Ok(out) => println!("{}", out),
+            Err(e) => {
+                eprintln!("Failed to serialize results: {}", e);
+                return EXIT_FAILURE;
+            }
+        }
+    } else {
+        for v in &videos {
+            println!(
+                "{}\t{}\t{}\t{}\t{}",
+                v.id, v.title, v.channel, v.duration_string, v.url
+            );
+        }
+    }
+
+    if videos.is_empty() {
+        if failed {
+            EXIT_FAILURE
+        } else {
+            EXIT_NO_RESULTS
+        }
+    } else {
+        EXIT_OK
+    }
+}
+
+async fn formats(url: &str, json: bool, settings: &Settings) -> i32 {
+    let formats = match yt::get_video_formats(url, settings).await {
+        Ok(f) => f.formats,
+        Err(e) => {
+            eprintln!("Failed to fetch formats: {}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    if json {
+        match serde_json::to_string_pretty(&formats) {
+            Ok(out) => println!("{}", out),
+            Err(e) => {
+                eprintln!("Failed to serialize formats: {}", e);
+                return EXIT_FAILURE;
+            }
+        }
+    } else {
+        for f in &formats {
+            let size = f
+                .filesize
+                .map(|s| format!("{:.1}MiB", s as f64 / 1024.0 / 1024.0))
+                .unwrap_or_else(|| "-".to_string());
+            println!(
+                "{}\t{}\t{}\t{}\t{}",
+                f.format_id, f.ext, f.resolution, size, f.note
+            );
+        }
+    }
+
+    if formats.is_empty() {
+        EXIT_NO_RESULTS
+    } else {
+        EXIT_OK
+    }
+}
+
+async fn download(
+    url: &str,
+    format: &str,
+    limit_rate: Option<&str>,
+    dir: &str,
+    settings: &Settings,
+) -> i32 {
+    let request = DownloadRequest {
+        video: Video {
+            id: url.to_string(),
+            title: url.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        },
+        format_id: format.to_string(),
+        rate_limit: limit_rate.map(str::to_string),
+        postprocess: settings.postprocess.clone(),
+        profile: None,
+        audio_only: false,
+        subtitles: Vec::new(),
+        playlist: None,
+    };
+    let dir = local::resolve_path(dir).to_string_lossy().to_string();
+
+    let mut child = match download::start_download(&request, &dir, settings).await {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Failed to start download: {}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped")).lines();
+    let mut stderr = BufReader::new(child.stderr.take().expect("stderr is piped")).lines();
+
+    // Progress lines are "percent<TAB>total bytes<TAB>bytes per second<TAB>eta seconds",
+    // with unknown values left empty; everything else goes to stderr
+    loop {
+        tokio::select! {
+            Ok(Some(line)) = stdout.next_line() => {
+                if let Some(progress) = download::parse_progress(&line)
+                    && progress.phase == crate::model::download::DownloadPhase::Downloading
+                {
+                    let field = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_default();
+                    println!(
+                        "{}\t{}\t{}\t{}",
+                        progress.percent().map(|p| format!("{:.1}", p)).unwrap_or_default(),
+                        field(progress.total_bytes),
+                        field(progress.speed.map(|s| s as u64)),
+                        field(progress.eta),
+                    );
+                }
+            }
+            Ok(Some(line)) = stderr.next_line() => {
+                eprintln!("{}", line);
+            }
+            status = child.wait() => {
+                return match status {
+                    Ok(s) if s.success() => EXIT_OK,
+                    Ok(s) => {
+                        eprintln!("Download failed with exit code: {:?}", s.code());
+                        EXIT_FAILURE
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to wait for download process: {}", e);
+                        EXIT_FAILURE
+                    }
+                };
+            }
+            _ = tokio::signal::ctrl_c() => {
+                let _ = child.start_kill();
+                return EXIT_INTERRUPTED;
+            }
+        }
+    }
+}
+
+async fn play(url: &str, audio: bool, settings: &Settings) -> i32 {
+    let res = if audio {
+        process::play_audio(url, None, settings)
+    } else {
+        process::play_video(url, None, None, None, None, settings)
+    };
+
+    let mut child = match res {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("Failed to start mpv: {}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    tokio::select! {
+        status = child.wait() => match status {
+            Ok(s) if s.success() => EXIT_OK,
+            Ok(s) => {
+                eprintln!("mpv exited with code: {:?}", s.code());
+                EXIT_FAILURE
+            }
+            Err(e) => {
+                eprintln!("Failed to wait for mpv: {}", e);
+                EXIT_FAILURE
+            }
+        },
+        _ = tokio::signal::ctrl_c() => {
+            let _ = child.start_kill();
+            EXIT_INTERRUPTED
+        }
+    }
+}
@@ -1,5 +1,6 @@
 mod app;
 mod cli;
+mod headless;
 mod model;
 mod sys;
 mod tui;
@@ -7,8 +8,8 @@ mod tui;
 use crate::model::settings::Settings;
 use anyhow::Result;
 use app::{
-    App, AppAction, handle_key_event, handle_mouse_event, handle_paste, on_tick, perform_search,
-    stop_playback,
+    App, AppAction, AppState, handle_key_event, handle_mouse_event, handle_paste, on_tick,
+    perform_search, stop_playback,
 };
 use clap::Parser;
 use cli::Cli;
@@ -45,8 +46,6 @@ async fn main() -> Result<()> {
         original_hook(panic_info);
     }));
 
-    println!("Checking dependencies...");
-
     // Load configuration early
     let config = match crate::sys::config::Config::load() {
         Ok(c) => c,
@@ -72,6 +71,13 @@ async fn main() -> Result<()> {
         eprintln!("Could not determine log path, logging disabled.");
     }
 
+    // Headless subcommands never touch the terminal
+    if let Some(command) = args.command {
+        let code = headless::run(command, &config, settings).await;
+        exit(code);
+    }
+
+    println!("Checking dependencies...");
     log::info!("Checking dependencies...");
     match sys::deps::check_dependencies(&settings) {
         Ok(status) => {
@@ -227,12 +233,46 @@ async fn main() -> Result<()> {
 
     // Main Loop
     let tick_rate = Duration::from_millis(250);
+    let frame_rate = Duration::from_millis(33);
     let mut last_tick = Instant::now();
+    let mut graphics_shown = false;
 
     let run_result = async {
         loop {
             terminal.draw(|f| tui::ui(f, &mut app, &mut picker))?;
 
+            // Kitty and sixel video goes over the video pane, unless a popup covers it
+            let graphics = match &app.video_feed {
+                Some(sys::video::VideoFeed::Graphics(frame))
+                    if matches!(
+                        app.state,
+                        AppState::Search | AppState::Results | AppState::Downloads
+                    ) =>
+                {
+                    Some(frame.clone())
+                }
+                _ => None,
+            };
+            match (graphics, app.video_area) {
+                (Some(frame), Some(area)) => {
+                    let redraw = !graphics_shown;
+                    sys::video::write_graphics(
+                        terminal.backend_mut(),
+                        &frame,
+                        area.x,
+                        area.y,
+                        redraw,
+                    )?;
+                    graphics_shown = true;
+                }
+                _ if graphics_shown => {
+                    sys::video::clear_graphics(terminal.backend_mut())?;
+                    terminal.clear()?;
+                    graphics_shown = false;
+                }
+                _ => {}
+            }
+
             // Check for shutdown signal
             if shutdown_rx.try_recv().is_ok() {
                 app.running = false;
@@ -247,9 +287,13 @@ async fn main() -> Result<()> {
                 app.reload_config();
             }
 
-            let timeout = tick_rate
+            let mut timeout = tick_rate
                 .checked_sub(last_tick.elapsed())
                 .unwrap_or_else(|| Duration::from_secs(0));
+            if app.video_feed.is_some() {
+                // Redraw at video frame rate while the video pane is shown
+                timeout = timeout.min(frame_rate);
+            }
 
             if crossterm::event::poll(timeout)? {
                 match event::read()? {
@@ -261,13 +305,35 @@ async fn main() -> Result<()> {
             }
 
             // Handle pending actions (Playback)
-            if let Some((action, url, title)) = app.pending_action.take() {
-                // Kill previous playback if any
-                stop_playback(&mut app);
+            if let Some(pending) = app
+                .pending_action
+                .take()
+                .and_then(|p| app::actions::check_resume(&mut app, p))
+            {
+                let app::PendingPlayback {
+                    action,
+                    url,
+                    title,
+                    video,
+                    start,
+                    playlist,
+                    playlist_source,
+                } = pending;
+                // Playback started meanwhile replaces a shuffle still being listed
+                app.shuffle_pending = None;
+
+                // Kill previous playback if any, the audio player takes the next track itself
+                if !(action == AppAction::ListenAudio && app::actions::audio_player_active(&app)) {
+                    stop_playback(&mut app);
+                }
 
                 // Suspend TUI only if needed (not needed for terminal anymore as it's separate)
 
                 let full_url = url.clone();
+                app.queue_playing = app
+                    .play_queue
+                    .current_item()
+                    .is_some_and(|item| item.playback_url() == full_url);
 
                 match action {
                     AppAction::WatchExternal => {
@@ -278,27 +344,33 @@ async fn main() -> Result<()> {
                             (full_url.to_string(), None)
                         };
 
-                        match sys::process::play_video(&final_url, format_id.as_deref(), false, None, &settings) {
+                        match sys::process::play_video(
+                            &final_url,
+                            format_id.as_deref(),
+                            None,
+                            None,
+                            start,
+                            &settings,
+                        ) {
                             Ok(child) => {
-                                let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<String>();
-                                let (res_tx, res_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
-                                app.playback_res_rx = res_rx;
-
                                 let socket_path = sys::mpv_ipc::get_ipc_path();
-                                if !cfg!(windows) {
-                                    let _ = std::fs::remove_file(&socket_path);
-                                }
 
-                                tokio::spawn(sys::mpv_ipc::spawn_ipc_handler(socket_path, rx, res_tx));
-
-                                app.playback_cmd_tx = Some(tx);
+                                let (player, events) = sys::mpv_ipc::MpvClient::spawn(socket_path);
+                                app.player = Some(player);
+                                app.player_events = events;
                                 app.playback_process = Some(child);
                                 app.playback_title = Some(title);
                                 app.status_message = Some("Playing externally...".to_string());
+                                if let Some(video) = video {
+                                    app::actions::record_history(&mut app, &video, false);
+                                    app::actions::request_segments(&mut app, &video);
+                                    app.playback_video = Some(video);
+                                }
+                                app::actions::update_media_metadata(&mut app);
                                 if let Some(mc) = &mut app.media_controller {
-                                    let _ = mc.set_metadata(app.playback_title.as_deref().unwrap_or("Unknown"), None, None);
-                                    let _ = mc.set_playback_status(true);
+                                    let _ = mc.set_playback_status(true, None);
                                 }
+                                app::actions::start_playlist(&mut app, playlist, playlist_source);
                             }
                             Err(e) => {
                                 app.status_message = Some(format!("Error playing video: {}", e));
@@ -306,31 +378,28 @@ async fn main() -> Result<()> {
                         }
                     }
                     AppAction::ListenAudio => {
-                        match sys::process::play_audio(&full_url.to_string(), &settings) {
-                            Ok(child) => {
-                                let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<String>();
-                                let (res_tx, res_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
-                                app.playback_res_rx = res_rx;
-
-                                let socket_path = sys::mpv_ipc::get_ipc_path();
-                                if !cfg!(windows) {
-                                    let _ = std::fs::remove_file(&socket_path);
+                        if !app::actions::audio_player_active(&app) {
+                            match sys::process::start_audio_player(&settings) {
+                                Ok(child) => {
+                                    let socket_path = sys::mpv_ipc::get_ipc_path();
+
+                                    let (player, events) =
+                                        sys::mpv_ipc::MpvClient::spawn(socket_path);
+                                    app.player = Some(player);
+                                    app.player_events = events;
+                                    app.playback_process = Some(child);
+                                    app.audio_player = true;
+                                    app.audio_player_idle = true;
                                 }
-
-                                tokio::spawn(sys::mpv_ipc::spawn_ipc_handler(socket_path, rx, res_tx));
-
-                                app.playback_cmd_tx = Some(tx);
-                                app.playback_process = Some(child);
-                                app.playback_title = Some(title);
-                                app.status_message = Some("Playing audio...".to_string());
-                                if let Some(mc) = &mut app.media_controller {
-                                    let _ = mc.set_metadata(app.playback_title.as_deref().unwrap_or("Unknown"), None, None);
-                                    let _ = mc.set_playback_status(true);
+                                Err(e) => {
+                                    app.status_message =
+                                        Some(format!("Error playing audio: {}", e));
                                 }
                             }
-                            Err(e) => {
-                                app.status_message = Some(format!("Error playing audio: {}", e));
-                            }
+                        }
+                        if app::actions::audio_player_active(&app) {
+                            app::actions::load_audio(&mut app, &full_url, title, video, start);
+                            app.status_message = Some("Playing audio...".to_string());
                         }
                     }
                     _ => {}
@@ -342,11 +411,6 @@ async fn main() -> Result<()> {
                 app.terminal_loading = false;
                 app.terminal_loading_progress = 0.0;
 
-                // Suspend TUI logic but stay in Alternate Screen
-                execute!(terminal.backend_mut(), DisableMouseCapture)?;
-                disable_raw_mode()?;
-                terminal.show_cursor()?;
-
                 // Play video (direct URL is faster)
                 let (final_url, ua) = if url.starts_with("http") && url.contains('|') {
                     let parts: Vec<&str> = url.splitn(2, '|').collect();
@@ -355,83 +419,61 @@ async fn main() -> Result<()> {
                     (url.as_str(), None)
                 };
 
-                if let Ok(mut child) = sys::process::play_video(final_url, None, true, ua, &settings) {
-                    // Update media controller
-                    if let Some(mc) = &mut app.media_controller {
-                        let _ = mc.set_metadata("Terminal Playback", None, None);
-                        let _ = mc.set_playback_status(true);
-                    }
+                // mpv renders at the size of the video pane, the TUI keeps running around it
+                let video = app.terminal_video.take();
+                if app.playback_process.is_some() {
+                    stop_playback(&mut app);
+                }
+                let size = terminal.size()?;
+                let (columns, rows) =
+                    tui::components::video_pane::output_size(size.width, size.height);
+                let output = sys::video::TerminalOutput {
+                    output: sys::video::VideoOutput::select(
+                        settings.terminal_video.output,
+                        picker.protocol_type(),
+                    ),
+                    columns,
+                    rows,
+                    cell_size: picker.font_size(),
+                };
+                log::info!("Terminal playback with the {} output", output.output.name());
+                match sys::process::play_video(final_url, None, Some(&output), ua, None, &settings)
+                {
+                    Ok(mut child) => {
+                        let socket_path = sys::mpv_ipc::get_ipc_path();
+
+                        let feed = sys::video::VideoFeed::new(&output);
+                        if let Some(stdout) = child.stdout.take() {
+                            sys::video::spawn_reader(stdout, feed.clone());
+                        }
 
-                    // Set up IPC for terminal playback
-                    let socket_path = sys::mpv_ipc::get_ipc_path();
-                    if !cfg!(windows) {
-                        let _ = std::fs::remove_file(&socket_path);
-                    }
-                    
-                    // We need a separate task to write to the socket because the main loop is blocked here
-                    // waiting for the child process.
-                    let (cmd_tx, cmd_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
-                    
-                    // Spawn IPC writer task
-                    let writer_handle = tokio::spawn(sys::mpv_ipc::spawn_ipc_writer(socket_path.clone(), cmd_rx));
-
-                    // Event loop for terminal playback
-                    loop {
-                        tokio::select! {
-                            // Check if child exited
-                            status = child.wait() => {
-                                let _ = status; 
-                                break;
-                            }
-                            // Handle media events
-                            Some(event) = app.media_rx.recv() => {
-                                use sys::media::MediaEvent;
-                                match event {
-                                    MediaEvent::Play => {
-                                        let _ = cmd_tx.send("{\"command\": [\"set_property\", \"pause\", false]}\n".to_string());
-                                        if let Some(mc) = &mut app.media_controller {
-                                            let _ = mc.set_playback_status(true);
-                                        }
-                                    }
-                                    MediaEvent::Pause => {
-                                        let _ = cmd_tx.send("{\"command\": [\"set_property\", \"pause\", true]}\n".to_string());
-                                        if let Some(mc) = &mut app.media_controller {
-                                            let _ = mc.set_playback_status(false);
-                                        }
-                                    }
-                                    MediaEvent::Toggle => {
-                                        let _ = cmd_tx.send("{\"command\": [\"cycle\", \"pause\"]}\n".to_string());
-                                    }
-                                    MediaEvent::Next => {
-                                        let _ = cmd_tx.send("{\"command\": [\"seek\", 10, \"relative\"]}\n".to_string());
-                                    }
-                                    MediaEvent::Previous => {
-                                        let _ = cmd_tx.send("{\"command\": [\"seek\", -10, \"relative\"]}\n".to_string());
-                                    }
-                                    MediaEvent::Stop => {
-                                        let _ = child.start_kill();
-                                    }
-                                }
+                        let (player, events) = sys::mpv_ipc::MpvClient::spawn(socket_path);
+                        app.player = Some(player);
+                        app.player_events = events;
+                        app.playback_process = Some(child);
+                        app.video_feed = Some(feed);
+                        app.playback_title = Some(
+                            video
+                                .as_ref()
+                                .map_or("Terminal Playback", |v| v.title.as_str())
+                                .to_string(),
+                        );
+                        app.status_message = Some("Playing in terminal...".to_string());
+                        if let Some(video) = video {
+                            if final_url.starts_with("http") {
+                                app::actions::request_segments(&mut app, &video);
                             }
+                            app.playback_video = Some(video);
+                        }
+                        app::actions::update_media_metadata(&mut app);
+                        if let Some(mc) = &mut app.media_controller {
+                            let _ = mc.set_playback_status(true, None);
                         }
                     }
-                    
-                    // Cleanup
-                    writer_handle.abort();
-                    if !cfg!(windows) {
-                        let _ = std::fs::remove_file(&socket_path);
-                    }
-
-                    if let Some(mc) = &mut app.media_controller {
-                        let _ = mc.set_playback_status(false);
+                    Err(e) => {
+                        app.status_message = Some(format!("Error playing in terminal: {}", e));
                     }
                 }
-
-                // Resume TUI
-                enable_raw_mode()?;
-                execute!(terminal.backend_mut(), EnableMouseCapture)?;
-                terminal.hide_cursor()?;
-                terminal.clear()?;
             }
 
             if last_tick.elapsed() >= tick_rate {
@@ -444,11 +486,13 @@ async fn main() -> Result<()> {
             }
         }
         Ok::<(), anyhow::Error>(())
-    }.await;
+    }
+    .await;
 
     // Restore Terminal
     app.cleanup();
     stop_playback(&mut app);
+    sys::process::remove_ipc_socket();
     cleanup_terminal(&mut terminal)?;
 
     if let Err(err) = run_result {
@@ -1,6 +1,10 @@
-use crate::model::Video;
+use crate::model::{SubtitleTrack, Video};
+use crate::sys::config::{DownloadProfile, PostProcessing};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq)]
+const QUEUE_FILE: &str = "downloads.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DownloadStatus {
     Pending,
     Downloading,
@@ -11,6 +15,68 @@ pub enum DownloadStatus {
     Error(String),
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DownloadPhase {
+    #[default]
+    Downloading,
+    Merging,
+    PostProcessing,
+}
+
+/// Progress as reported by yt-dlp's JSON progress template. Formatting is left to the UI.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DownloadProgress {
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>, // exact or estimated
+    pub speed: Option<f64>,       // bytes per second
+    pub eta: Option<u64>,         // seconds
+    pub fragment_index: Option<u64>,
+    pub fragment_count: Option<u64>,
+    pub phase: DownloadPhase,
+    pub postprocessor: Option<String>, // yt-dlp name, e.g. "EmbedThumbnail"
+}
+
+impl DownloadProgress {
+    /// Percentage of the current file, if the total size is known.
+    pub fn percent(&self) -> Option<f64> {
+        match self.total_bytes {
+            Some(total) if total > 0 => {
+                Some((self.downloaded_bytes as f64 / total as f64 * 100.0).min(100.0))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Place of a download in a playlist that is downloaded as a whole. Such downloads
+/// are numbered and go to a folder named after the playlist.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistItem {
+    pub id: String, // playlist id, groups the downloads panel
+    pub title: String,
+    pub index: u64, // 1-based
+    pub count: u64, // entries in the playlist
+}
+
+/// A playlist to download as a whole, listed in the background first.
+#[derive(Debug, Clone)]
+pub struct PlaylistDownload {
+    pub url: String,
+    pub profile: Option<DownloadProfile>, // best quality without one
+    pub postprocess: PostProcessing,
+}
+
+/// The videos of a playlist with their place in it, or why they couldn't be listed.
+pub type PlaylistListing = Result<Vec<(Video, PlaylistItem)>, String>;
+
+impl PlaylistItem {
+    /// The index with leading zeros, so files sort in playlist order.
+    pub fn number(&self) -> String {
+        let width = self.count.to_string().len().max(2);
+        format!("{:0width$}", self.index, width = width)
+    }
+}
+
 #[derive(Debug)]
 pub struct DownloadTask {
     #[allow(dead_code)]
@@ -20,15 +86,35 @@ pub struct DownloadTask {
     pub format_id: String, // Store the format ID used for download
     pub status: DownloadStatus,
     pub progress: f64, // 0.0 to 100.0
-    pub speed: String,
-    pub eta: String,
-    pub total_size: String,
+    pub stats: DownloadProgress,
     pub pid: Option<u32>,
     pub info_json_path: Option<std::path::PathBuf>,
+    pub rate_limit: Option<String>, // Overrides the global limit, empty means unlimited
+    pub attempts: u32,              // Automatic retries so far
+    pub retry_at: Option<i64>,      // unix timestamp of the next automatic retry
+    pub postprocess: PostProcessing,
+    pub profile: Option<DownloadProfile>,
+    pub audio_only: bool,              // routed to the audio directory
+    pub subtitles: Vec<SubtitleTrack>, // written next to the media
+    pub playlist: Option<PlaylistItem>,
+    pub output_file: Option<std::path::PathBuf>, // final file, once yt-dlp moved it there
+}
+
+/// Everything the download manager needs to start yt-dlp for a task.
+#[derive(Debug, Clone)]
+pub struct DownloadRequest {
+    pub video: Video,
+    pub format_id: String,
+    pub rate_limit: Option<String>, // overrides the scheduled global limit
+    pub postprocess: PostProcessing,
+    pub profile: Option<DownloadProfile>, // format_id is the profile's selector then
+    pub audio_only: bool,
+    pub subtitles: Vec<SubtitleTrack>,
+    pub playlist: Option<PlaylistItem>,
 }
 
 impl DownloadTask {
-    pub fn new(video: Video, format_id: String) -> Self {
+    pub fn new(video: Video, format_id: String, postprocess: PostProcessing) -> Self {
         Self {
             id: video.id.clone(),
             title: video.title.clone(),
@@ -36,23 +122,105 @@ impl DownloadTask {
             format_id,
             status: DownloadStatus::Pending,
             progress: 0.0,
-            speed: String::new(),
-            eta: String::new(),
-            total_size: String::new(),
+            stats: DownloadProgress::default(),
             pid: None,
             info_json_path: None,
+            rate_limit: None,
+            attempts: 0,
+            retry_at: None,
+            postprocess,
+            profile: None,
+            audio_only: false,
+            subtitles: Vec::new(),
+            playlist: None,
+            output_file: None,
+        }
+    }
+
+    pub fn request(&self) -> DownloadRequest {
+        DownloadRequest {
+            video: self.video.clone(),
+            format_id: self.format_id.clone(),
+            rate_limit: self.rate_limit.clone(),
+            postprocess: self.postprocess.clone(),
+            profile: self.profile.clone(),
+            audio_only: self.audio_only,
+            subtitles: self.subtitles.clone(),
+            playlist: self.playlist.clone(),
+        }
+    }
+
+    /// Queues a failed or canceled task again on user request, with a fresh retry budget.
+    pub fn restart(&mut self) {
+        self.status = DownloadStatus::Pending;
+        self.attempts = 0;
+        self.retry_at = None;
+    }
+
+    pub fn rate_limit_label(&self) -> String {
+        match self.rate_limit.as_deref() {
+            None => "Global".to_string(),
+            Some("") => "Unlimited".to_string(),
+            Some(rate) => format!("{}/s", rate),
+        }
+    }
+}
+
+/// A download as written to the queue file. Progress is left out, yt-dlp reports it again on resume.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedDownload {
+    pub video: Video,
+    pub format_id: String,
+    pub status: DownloadStatus,
+    #[serde(default)]
+    pub info_json_path: Option<std::path::PathBuf>,
+    #[serde(default)]
+    pub rate_limit: Option<String>,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub retry_at: Option<i64>,
+    #[serde(default)]
+    pub postprocess: PostProcessing,
+    #[serde(default)]
+    pub profile: Option<DownloadProfile>,
+    #[serde(default)]
+    pub audio_only: bool,
+    #[serde(default)]
+    pub subtitles: Vec<SubtitleTrack>,
+    #[serde(default)]
+    pub playlist: Option<PlaylistItem>,
+    #[serde(default)]
+    pub output_file: Option<std::path::PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DownloadQueue {
+    pub tasks: Vec<QueuedDownload>,
+}
+
+impl DownloadQueue {
+    pub fn load() -> Self {
+        crate::sys::state::load(QUEUE_FILE)
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = crate::sys::state::save(QUEUE_FILE, self) {
+            log::error!("Failed to save download queue: {}", e);
         }
     }
 }
 
 #[derive(Debug)]
 pub enum DownloadEvent {
-    // This matches what your app is expecting: id, progress, speed, eta, total_size
-    Update(String, f64, String, String, String),
+    Update(String, DownloadProgress),
     Finished(String),
     Error(String, String),
-    Started(String, u32), // New variant for download start and PID
-    Pause(String),        // New variant for pausing a download
-    Resume(String),       // New variant for resuming a download
-    Canceled(String),     // New variant for user-initiated cancellation
+    TransientError(String, String), // Network trouble worth retrying
+    Started(String, u32),           // New variant for download start and PID
+    Pause(String),                  // New variant for pausing a download
+    Resume(String),                 // New variant for resuming a download
+    Canceled(String),               // New variant for user-initiated cancellation
+    Requeued(String),               // Stopped to be started again, e.g. with a new rate limit
+    Output(String, std::path::PathBuf), // Final file after post-processing
 }
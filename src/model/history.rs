@@ -0,0 +1,104 @@
+use crate::model::Video;
+use serde::{Deserialize, Serialize};
+
+const HISTORY_FILE: &str = "history.json";
+const MAX_ENTRIES: usize = 500;
+// Positions closer than this to either end are not worth resuming
+const RESUME_MARGIN_SECS: f64 = 10.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub video: Video,
+    #[serde(default)]
+    pub position: f64,
+    #[serde(default)]
+    pub duration: f64,
+    pub last_played: i64, // unix timestamp
+    #[serde(default)]
+    pub is_audio: bool,
+}
+
+impl HistoryEntry {
+    pub fn is_local(&self) -> bool {
+        !self.video.url.starts_with("http")
+    }
+
+    pub fn resume_position(&self) -> Option<f64> {
+        let near_end = self.duration > 0.0 && self.position > self.duration - RESUME_MARGIN_SECS;
+        (self.position > RESUME_MARGIN_SECS && !near_end).then_some(self.position)
+    }
+}
+
+/// Watch history, most recently played first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    pub fn load() -> Self {
+        crate::sys::state::load(HISTORY_FILE)
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = crate::sys::state::save(HISTORY_FILE, self) {
+            log::error!("Failed to save history: {}", e);
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|e| e.video.id == id)
+    }
+
+    /// Moves the video to the top of the history, keeping any known position.
+    pub fn record(&mut self, video: &Video, is_audio: bool) {
+        let previous = self
+            .entries
+            .iter()
+            .position(|e| e.video.id == video.id)
+            .map(|idx| self.entries.remove(idx));
+
+        let (position, duration) = previous
+            .map(|e| (e.position, e.duration))
+            .unwrap_or((0.0, 0.0));
+
+        self.entries.insert(
+            0,
+            HistoryEntry {
+                video: video.clone(),
+                position,
+                duration,
+                last_played: chrono::Utc::now().timestamp(),
+                is_audio,
+            },
+        );
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    pub fn update_position(&mut self, id: &str, position: f64, duration: f64) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.video.id == id) {
+            entry.position = position;
+            if duration > 0.0 {
+                entry.duration = duration;
+            }
+        }
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if idx < self.entries.len() {
+            self.entries.remove(idx);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::model::Video;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -15,4 +16,18 @@ impl LocalFile {
         let audio_exts = ["mp3", "m4a", "flac", "wav", "ogg", "opus", "aac", "wma"];
         audio_exts.contains(&self.extension.to_lowercase().as_str())
     }
+
+    /// Minimal `Video` for playback bookkeeping (queue, history); the path doubles as id and url.
+    /// Chapters come from the `.info.json` of the download, if it is still there.
+    pub fn to_video(&self) -> Video {
+        let path = self.path.to_string_lossy().to_string();
+        Video {
+            id: path.clone(),
+            title: self.name.clone(),
+            url: path,
+            is_partial: false,
+            chapters: crate::sys::local::info_json_chapters(&self.path),
+            ..Default::default()
+        }
+    }
 }
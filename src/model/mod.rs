@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 pub mod download;
+pub mod history;
 pub mod local;
+pub mod queue;
 pub mod settings;
+pub mod subscription;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Video {
@@ -32,6 +35,21 @@ pub struct Video {
     pub parent_playlist_url: Option<String>,
     #[serde(default)]
     pub parent_playlist_title: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapters: Vec<Chapter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Chapter {
+    pub start_time: f64, // seconds
+    pub end_time: f64,
+    pub title: String,
+}
+
+/// Index of the chapter playing at `time`, chapters are sorted by start time.
+pub fn chapter_at(chapters: &[Chapter], time: f64) -> Option<usize> {
+    chapters.iter().rposition(|c| c.start_time <= time)
 }
 
 impl Default for Video {
@@ -54,6 +72,7 @@ impl Default for Video {
             parent_playlist_id: None,
             parent_playlist_url: None,
             parent_playlist_title: None,
+            chapters: Vec::new(),
         }
     }
 }
@@ -78,3 +97,56 @@ pub struct VideoFormat {
     #[serde(default)]
     pub has_video: bool,
 }
+
+/// A subtitle language offered for a video.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SubtitleTrack {
+    pub lang: String, // yt-dlp language key, e.g. "en" or "en-orig"
+    pub name: String,
+    pub auto: bool, // auto-generated captions
+}
+
+impl SubtitleTrack {
+    /// Whether this track is in `lang`, regional variants included ("en" matches "en-US").
+    pub fn matches(&self, lang: &str) -> bool {
+        self.lang == lang
+            || self
+                .lang
+                .strip_prefix(lang)
+                .is_some_and(|rest| rest.starts_with('-'))
+    }
+}
+
+/// The preferred tracks out of `tracks`, one per language in order of preference.
+/// Manual subtitles win over auto captions, which are only used if `auto_captions` is set.
+pub fn preferred_subtitles(
+    tracks: &[SubtitleTrack],
+    languages: &[String],
+    auto_captions: bool,
+) -> Vec<SubtitleTrack> {
+    let mut selected: Vec<SubtitleTrack> = Vec::new();
+    for lang in languages {
+        let track = tracks
+            .iter()
+            .find(|t| !t.auto && t.matches(lang))
+            .or_else(|| {
+                tracks
+                    .iter()
+                    .find(|t| auto_captions && t.auto && t.matches(lang))
+            });
+        if let Some(track) = track
+            && !selected.contains(track)
+        {
+            selected.push(track.clone());
+        }
+    }
+    selected
+}
+
+/// Formats and subtitles of a video, as offered on the format selection screen.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MediaFormats {
+    pub formats: Vec<VideoFormat>,
+    #[serde(default)]
+    pub subtitles: Vec<SubtitleTrack>,
+}
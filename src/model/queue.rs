@@ -0,0 +1,219 @@
+use crate::model::Video;
+use crate::model::local::LocalFile;
+use serde::{Deserialize, Serialize};
+
+const QUEUE_FILE: &str = "play_queue.json";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum QueueMode {
+    #[default]
+    Video,
+    Audio,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueItem {
+    pub video: Video, // For local files the url holds the file path
+    #[serde(default)]
+    pub mode: QueueMode,
+    #[serde(default)]
+    pub is_local: bool,
+}
+
+impl QueueItem {
+    pub fn new(video: Video, mode: QueueMode) -> Self {
+        Self {
+            video,
+            mode,
+            is_local: false,
+        }
+    }
+
+    pub fn local(file: &LocalFile, mode: QueueMode) -> Self {
+        Self {
+            video: file.to_video(),
+            mode,
+            is_local: true,
+        }
+    }
+
+    /// URL handed to the player, in the same shape `pending_action` expects.
+    pub fn playback_url(&self) -> String {
+        if self.is_local || self.mode == QueueMode::Audio {
+            self.video.url.clone()
+        } else {
+            format!("{}::best", self.video.url)
+        }
+    }
+}
+
+/// An entry of mpv's playlist, in mpv's order.
+#[derive(Debug, Clone)]
+pub struct PlaylistEntry {
+    pub title: String,
+    pub video: Option<Video>,
+    pub queue_index: Option<usize>, // the queue item it was loaded for
+}
+
+/// Where further entries of a playlist being played come from. They are loaded a
+/// page at a time as playback gets close to the last loaded one.
+#[derive(Debug, Clone)]
+pub struct PlaylistSource {
+    pub url: String,
+    pub next_start: u32,    // 1-based index of the first entry not loaded yet
+    pub total: Option<u64>, // entries in the whole playlist, if known
+    pub loading: bool,
+    pub exhausted: bool,
+}
+
+/// A page of a playlist being played, fetched for a `PlaylistSource`.
+#[derive(Debug, Clone, Default)]
+pub struct PlaylistPage {
+    pub videos: Vec<Video>,
+    pub listed: u32, // entries yt-dlp listed, unavailable ones included
+}
+
+/// Shuffles in place, without pulling in a random number crate.
+pub fn shuffle<T>(items: &mut [T]) {
+    use std::hash::{BuildHasher, Hasher};
+    let state = std::collections::hash_map::RandomState::new();
+    for i in (1..items.len()).rev() {
+        let mut hasher = state.build_hasher();
+        hasher.write_usize(i);
+        let j = (hasher.finish() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayQueue {
+    pub items: Vec<QueueItem>,
+    pub current: Option<usize>,
+}
+
+impl PlayQueue {
+    pub fn load() -> Self {
+        let mut queue: Self = crate::sys::state::load(QUEUE_FILE);
+        if queue.current.is_some_and(|i| i >= queue.items.len()) {
+            queue.current = None;
+        }
+        queue
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = crate::sys::state::save(QUEUE_FILE, self) {
+            log::error!("Failed to save play queue: {}", e);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn push(&mut self, item: QueueItem) {
+        self.items.push(item);
+    }
+
+    pub fn remove(&mut self, idx: usize) -> Option<QueueItem> {
+        if idx >= self.items.len() {
+            return None;
+        }
+        let item = self.items.remove(idx);
+        self.current = match self.current {
+            Some(c) if c == idx => None,
+            Some(c) if c > idx => Some(c - 1),
+            other => other,
+        };
+        Some(item)
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.current = None;
+    }
+
+    /// Swaps the item at `idx` with its neighbour and returns its new index.
+    pub fn move_item(&mut self, idx: usize, up: bool) -> Option<usize> {
+        let target = if up { idx.checked_sub(1)? } else { idx + 1 };
+        if target >= self.items.len() || idx >= self.items.len() {
+            return None;
+        }
+        self.items.swap(idx, target);
+        self.current = match self.current {
+            Some(c) if c == idx => Some(target),
+            Some(c) if c == target => Some(idx),
+            other => other,
+        };
+        Some(target)
+    }
+
+    pub fn current_item(&self) -> Option<&QueueItem> {
+        self.current.and_then(|i| self.items.get(i))
+    }
+
+    pub fn next_index(&self) -> Option<usize> {
+        let next = self.current.map(|i| i + 1).unwrap_or(0);
+        (next < self.items.len()).then_some(next)
+    }
+
+    pub fn previous_index(&self) -> Option<usize> {
+        self.current.and_then(|i| i.checked_sub(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue_of(n: usize) -> PlayQueue {
+        let mut queue = PlayQueue::default();
+        for i in 0..n {
+            queue.push(QueueItem::new(
+                Video {
+                    id: i.to_string(),
+                    ..Default::default()
+                },
+                QueueMode::Video,
+            ));
+        }
+        queue
+    }
+
+    #[test]
+    fn test_move_and_remove_keep_current() {
+        let mut queue = queue_of(3);
+        queue.current = Some(1);
+
+        assert_eq!(queue.move_item(1, true), Some(0));
+        assert_eq!(queue.current, Some(0));
+        assert_eq!(queue.move_item(0, true), None);
+
+        queue.remove(2);
+        assert_eq!(queue.current, Some(0));
+        queue.remove(0);
+        assert_eq!(queue.current, None);
+    }
+
+    #[test]
+    fn test_next_previous() {
+        let mut queue = queue_of(2);
+        assert_eq!(queue.next_index(), Some(0));
+        assert_eq!(queue.previous_index(), None);
+
+        queue.current = Some(1);
+        assert_eq!(queue.next_index(), None);
+        assert_eq!(queue.previous_index(), Some(0));
+    }
+
+    #[test]
+    fn test_shuffle_keeps_items() {
+        let mut items: Vec<usize> = (0..50).collect();
+        shuffle(&mut items);
+        items.sort_unstable();
+        assert_eq!(items, (0..50).collect::<Vec<_>>());
+    }
+}
@@ -1,4 +1,7 @@
-use crate::sys::config::{Config, CookieSource};
+use crate::sys::config::{
+    Config, CookieSource, DownloadProfile, Output, PostProcessing, RateWindow, SponsorBlock,
+    Subtitles, TerminalVideo,
+};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
@@ -19,6 +22,23 @@ pub struct Settings {
     pub cookie_file: Option<PathBuf>,
     pub browser_name: Option<String>,
     pub log_path: Option<PathBuf>,
+
+    pub cache_enabled: bool,
+    pub cache_ttl_seconds: u64,
+    pub cache_max_entries: usize,
+
+    pub download_rate_limit: String,
+    pub rate_schedule: Vec<RateWindow>,
+
+    pub retry_max_attempts: u32,
+    pub retry_backoff_seconds: u64,
+
+    pub postprocess: PostProcessing,
+    pub output: Output,
+    pub subtitles: Subtitles,
+    pub sponsorblock: SponsorBlock,
+    pub terminal_video: TerminalVideo,
+    pub profiles: Vec<DownloadProfile>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -44,6 +64,19 @@ impl Default for Settings {
             cookie_file: None,
             browser_name: None,
             log_path: None,
+            cache_enabled: false,
+            cache_ttl_seconds: 0,
+            cache_max_entries: 0,
+            download_rate_limit: String::new(),
+            rate_schedule: Vec::new(),
+            retry_max_attempts: 0,
+            retry_backoff_seconds: 0,
+            postprocess: PostProcessing::default(),
+            output: Output::default(),
+            subtitles: Subtitles::default(),
+            sponsorblock: SponsorBlock::default(),
+            terminal_video: TerminalVideo::default(),
+            profiles: Vec::new(),
         }
     }
 }
@@ -111,6 +144,23 @@ impl Settings {
 
         settings.log_path = config.logging.path;
 
+        settings.cache_enabled = config.cache.enabled;
+        settings.cache_ttl_seconds = config.cache.ttl_seconds;
+        settings.cache_max_entries = config.cache.max_entries;
+
+        settings.download_rate_limit = config.download_rate_limit;
+        settings.rate_schedule = config.rate_schedule;
+
+        settings.retry_max_attempts = config.retry.max_attempts;
+        settings.retry_backoff_seconds = config.retry.backoff_seconds;
+
+        settings.postprocess = config.postprocess;
+        settings.output = config.output;
+        settings.subtitles = config.subtitles;
+        settings.sponsorblock = config.sponsorblock;
+        settings.terminal_video = config.terminal_video;
+        settings.profiles = config.profiles;
+
         settings
     }
 
@@ -0,0 +1,90 @@
+use crate::model::Video;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const SUBSCRIPTIONS_FILE: &str = "subscriptions.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subscription {
+    pub channel_id: String,
+    pub channel: String,
+    pub subscribed_at: i64, // unix timestamp
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Subscriptions {
+    pub channels: Vec<Subscription>,
+    // Feed items the user has already watched or dismissed
+    #[serde(default)]
+    pub seen: HashSet<String>,
+}
+
+impl Subscriptions {
+    pub fn load() -> Self {
+        crate::sys::state::load(SUBSCRIPTIONS_FILE)
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = crate::sys::state::save(SUBSCRIPTIONS_FILE, self) {
+            log::error!("Failed to save subscriptions: {}", e);
+        }
+    }
+
+    pub fn is_subscribed(&self, channel_id: &str) -> bool {
+        self.channels.iter().any(|c| c.channel_id == channel_id)
+    }
+
+    /// Subscribes to the channel of `video`, or unsubscribes if already subscribed.
+    /// Returns whether the channel is now subscribed.
+    pub fn toggle(&mut self, video: &Video) -> bool {
+        if self.is_subscribed(&video.channel_id) {
+            self.channels.retain(|c| c.channel_id != video.channel_id);
+            false
+        } else {
+            self.channels.push(Subscription {
+                channel_id: video.channel_id.clone(),
+                channel: video.channel.clone(),
+                subscribed_at: chrono::Utc::now().timestamp(),
+            });
+            true
+        }
+    }
+
+    pub fn is_seen(&self, id: &str) -> bool {
+        self.seen.contains(id)
+    }
+
+    pub fn mark_seen(&mut self, id: &str) -> bool {
+        self.seen.insert(id.to_string())
+    }
+
+    /// Forgets seen ids that are no longer part of the feed, they will not show up again.
+    pub fn prune_seen(&mut self, feed: &[Video]) {
+        let ids: HashSet<&str> = feed.iter().map(|v| v.id.as_str()).collect();
+        self.seen.retain(|id| ids.contains(id.as_str()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+}
+
+/// Result of fetching the uploads of all subscribed channels.
+#[derive(Debug, Clone)]
+pub struct Feed {
+    pub videos: Vec<Video>,
+    pub complete: bool, // false if some channels failed to load
+}
+
+/// Merges per-channel uploads into one feed, newest first.
+/// Items without an upload date sort after dated ones.
+pub fn merge_feed(uploads: Vec<Vec<Video>>) -> Vec<Video> {
+    let mut feed: Vec<Video> = uploads.into_iter().flatten().collect();
+    // upload_date is YYYYMMDD, so string order is date order
+    feed.sort_by(|a, b| b.upload_date.cmp(&a.upload_date));
+    feed
+}
@@ -0,0 +1,174 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::model::settings::Settings;
+use crate::model::{MediaFormats, Video};
+use crate::sys::config::Config;
+use crate::sys::sponsorblock::Segment;
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    stored_at: i64, // unix timestamp
+    value: T,
+}
+
+/// A cached value and whether it is still within the configured TTL.
+/// Stale values are still usable while a fresh copy is fetched.
+pub struct Cached<T> {
+    pub value: T,
+    pub fresh: bool,
+}
+
+pub fn search_key(
+    query: &str,
+    start: u32,
+    end: u32,
+    show_live: bool,
+    show_playlists: bool,
+) -> String {
+    format!(
+        "search:{}:{}-{}:{}:{}",
+        query, start, end, show_live, show_playlists
+    )
+}
+
+/// 64-bit FNV-1a. File names must stay the same across builds, which std's hashers
+/// don't promise.
+fn fnv1a(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn entry_path(key: &str) -> Result<PathBuf> {
+    // Keys contain URLs and free text, so hash them into a safe file name
+    Ok(Config::get_cache_dir()?.join(format!("{:016x}.json", fnv1a(key))))
+}
+
+fn get<T: DeserializeOwned>(settings: &Settings, key: &str) -> Option<Cached<T>> {
+    if !settings.cache_enabled {
+        return None;
+    }
+
+    let path = entry_path(key).ok()?;
+    let content = fs::read_to_string(&path).ok()?;
+    let entry: Entry<T> = match serde_json::from_str(&content) {
+        Ok(entry) => entry,
+        Err(e) => {
+            log::warn!("Discarding unreadable cache entry {:?}: {}", path, e);
+            let _ = fs::remove_file(&path);
+            return None;
+        }
+    };
+
+    let age = chrono::Utc::now().timestamp() - entry.stored_at;
+    Some(Cached {
+        value: entry.value,
+        fresh: age >= 0 && (age as u64) < settings.cache_ttl_seconds,
+    })
+}
+
+fn put<T: Serialize + ?Sized>(settings: &Settings, key: &str, value: &T) {
+    if !settings.cache_enabled {
+        return;
+    }
+
+    let entry = Entry {
+        stored_at: chrono::Utc::now().timestamp(),
+        value,
+    };
+    if let Err(e) = write_entry(key, &entry) {
+        log::error!("Failed to write cache entry for {}: {}", key, e);
+        return;
+    }
+    prune(settings.cache_max_entries);
+}
+
+fn write_entry<T: Serialize>(key: &str, entry: &Entry<T>) -> Result<()> {
+    let path = entry_path(key)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_string(entry)?)?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+/// Removes the oldest entries until at most `max_entries` remain.
+fn prune(max_entries: usize) {
+    let dir = match Config::get_cache_dir() {
+        Ok(dir) => dir,
+        Err(_) => return,
+    };
+    let read_dir = match fs::read_dir(&dir) {
+        Ok(read_dir) => read_dir,
+        Err(_) => return,
+    };
+
+    let mut entries: Vec<_> = read_dir
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| {
+            let modified = e.metadata().and_then(|m| m.modified()).ok()?;
+            Some((modified, e.path()))
+        })
+        .collect();
+
+    if entries.len() <= max_entries {
+        return;
+    }
+
+    entries.sort_by_key(|(modified, _)| *modified);
+    let excess = entries.len() - max_entries;
+    for (_, path) in entries.into_iter().take(excess) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+pub fn get_search(settings: &Settings, key: &str) -> Option<Cached<Vec<Video>>> {
+    get(settings, key)
+}
+
+pub fn put_search(settings: &Settings, key: &str, videos: &[Video]) {
+    put(settings, key, videos)
+}
+
+pub fn get_video(settings: &Settings, id: &str) -> Option<Cached<Video>> {
+    get(settings, &format!("video:{}", id))
+}
+
+pub fn put_video(settings: &Settings, video: &Video) {
+    put(settings, &format!("video:{}", video.id), video)
+}
+
+pub fn get_formats(settings: &Settings, url: &str) -> Option<Cached<MediaFormats>> {
+    get(settings, &format!("formats:{}", url))
+}
+
+pub fn put_formats(settings: &Settings, url: &str, formats: &MediaFormats) {
+    put(settings, &format!("formats:{}", url), formats)
+}
+
+pub fn get_segments(settings: &Settings, video_id: &str) -> Option<Cached<Vec<Segment>>> {
+    get(settings, &format!("sponsorblock:{}", video_id))
+}
+
+pub fn put_segments(settings: &Settings, video_id: &str, segments: &[Segment]) {
+    put(settings, &format!("sponsorblock:{}", video_id), segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fnv1a_is_stable() {
+        assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a("search:lofi:1:20"), 0xd99c8a59d8be19ec);
+    }
+}
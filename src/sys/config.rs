@@ -25,6 +25,12 @@ pub struct Config {
     pub show_playlists: bool,
     #[serde(default = "default_progress_style")]
     pub progress_style: String,
+    #[serde(default = "default_max_concurrent_downloads")]
+    pub max_concurrent_downloads: u32,
+    #[serde(default = "default_true")]
+    pub auto_resume_downloads: bool,
+    #[serde(default)]
+    pub download_rate_limit: String, // yt-dlp rate, e.g. "2M". Empty means unlimited
 
     // New Fields
     #[serde(default)]
@@ -33,6 +39,51 @@ pub struct Config {
     pub cookies: Cookies,
     #[serde(default)]
     pub logging: Logging,
+    #[serde(default)]
+    pub cache: Cache,
+    #[serde(default)]
+    pub retry: Retry,
+    #[serde(default)]
+    pub postprocess: PostProcessing,
+    #[serde(default)]
+    pub output: Output,
+    #[serde(default)]
+    pub subtitles: Subtitles,
+    #[serde(default)]
+    pub sponsorblock: SponsorBlock,
+    #[serde(default)]
+    pub terminal_video: TerminalVideo,
+    #[serde(default)]
+    pub rate_schedule: Vec<RateWindow>,
+    #[serde(default)]
+    pub profiles: Vec<DownloadProfile>,
+}
+
+/// A named download setup, listed on top of the format selection screen.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct DownloadProfile {
+    pub name: String,
+    #[serde(default = "default_profile_format")]
+    pub format: String, // yt-dlp format selector, used as is
+    #[serde(default)]
+    pub merge_format: String, // container for merged video, e.g. "mkv"
+    #[serde(default)]
+    pub audio_format: String, // extract audio and convert, e.g. "opus"
+    #[serde(default)]
+    pub output_template: String,
+    #[serde(default)]
+    pub directory: String, // relative paths are inside download_directory
+    #[serde(default)]
+    pub postprocess: Option<PostProcessing>, // [postprocess] defaults when unset
+}
+
+/// Overrides `download_rate_limit` between two times of day. The window may wrap past midnight.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct RateWindow {
+    pub start: String, // "HH:MM"
+    pub end: String,   // "HH:MM", exclusive
+    #[serde(default)]
+    pub rate_limit: String,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -78,6 +129,211 @@ pub struct Logging {
     pub path: Option<PathBuf>,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Cache {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_cache_ttl")]
+    pub ttl_seconds: u64,
+    #[serde(default = "default_cache_max_entries")]
+    pub max_entries: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Retry {
+    #[serde(default = "default_retry_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_retry_backoff")]
+    pub backoff_seconds: u64,
+}
+
+/// What yt-dlp does with a file after downloading it. Set globally here,
+/// and per download from the format selection screen.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PostProcessing {
+    #[serde(default = "default_false")]
+    pub embed_thumbnail: bool,
+    #[serde(default = "default_false")]
+    pub embed_metadata: bool, // includes chapters
+    #[serde(default = "default_false")]
+    pub embed_subtitles: bool,
+    #[serde(default)]
+    pub sponsorblock: SponsorBlockMode,
+    #[serde(default = "default_sponsorblock_categories")]
+    pub sponsorblock_categories: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SponsorBlockMode {
+    #[default]
+    Off,
+    Mark,   // as chapters
+    Remove, // cut from the file
+}
+
+impl SponsorBlockMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Mark => "mark",
+            Self::Remove => "remove",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Off => Self::Mark,
+            Self::Mark => Self::Remove,
+            Self::Remove => Self::Off,
+        }
+    }
+}
+
+/// File names and folders for downloads, inside `download_directory`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Output {
+    #[serde(default = "default_output_template")]
+    pub template: String,
+    #[serde(default = "default_false")]
+    pub channel_folders: bool,
+    #[serde(default = "default_false")]
+    pub playlist_folders: bool,
+    #[serde(default)]
+    pub date_folders: DateFolders,
+    #[serde(default)]
+    pub audio_directory: String, // audio-only downloads, relative paths are inside download_directory
+    #[serde(default)]
+    pub video_directory: String,
+}
+
+/// Subtitle languages in order of preference, used for playback and preselected for downloads.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Subtitles {
+    #[serde(default)]
+    pub languages: Vec<String>,
+    #[serde(default = "default_true")]
+    pub auto_captions: bool, // fall back to auto-generated captions
+}
+
+impl Default for Subtitles {
+    fn default() -> Self {
+        Self {
+            languages: Vec::new(),
+            auto_captions: true,
+        }
+    }
+}
+
+/// Skipping sponsor reads, intros and the like while streaming, using the SponsorBlock API.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SponsorBlock {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_sponsorblock_api_url")]
+    pub api_url: String,
+    #[serde(default = "default_sponsorblock_skip_categories")]
+    pub categories: Vec<String>,
+}
+
+impl Default for SponsorBlock {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            api_url: default_sponsorblock_api_url(),
+            categories: default_sponsorblock_skip_categories(),
+        }
+    }
+}
+
+/// mpv video output for "Watch in Terminal".
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TerminalVideoOutput {
+    #[default]
+    Auto, // kitty or sixel graphics when the terminal supports them, tct otherwise
+    Kitty,
+    Sixel,
+    Tct, // colored half blocks, works everywhere
+}
+
+impl TerminalVideoOutput {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Kitty => "kitty",
+            Self::Sixel => "sixel",
+            Self::Tct => "tct",
+        }
+    }
+}
+
+/// Rendering of video inside the terminal.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct TerminalVideo {
+    #[serde(default)]
+    pub output: TerminalVideoOutput,
+    #[serde(default = "default_terminal_max_height")]
+    pub max_height: u32, // pixels, 0 means no cap
+    #[serde(default = "default_terminal_max_fps")]
+    pub max_fps: u32, // 0 means no cap
+}
+
+impl Default for TerminalVideo {
+    fn default() -> Self {
+        Self {
+            output: TerminalVideoOutput::Auto,
+            max_height: default_terminal_max_height(),
+            max_fps: default_terminal_max_fps(),
+        }
+    }
+}
+
+/// Groups downloads by upload date.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DateFolders {
+    #[default]
+    Off,
+    Year,
+    Month,
+}
+
+impl DateFolders {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Year => "year",
+            Self::Month => "month",
+        }
+    }
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self {
+            template: default_output_template(),
+            channel_folders: false,
+            playlist_folders: false,
+            date_folders: DateFolders::Off,
+            audio_directory: String::new(),
+            video_directory: String::new(),
+        }
+    }
+}
+
+impl Default for PostProcessing {
+    fn default() -> Self {
+        Self {
+            embed_thumbnail: false,
+            embed_metadata: false,
+            embed_subtitles: false,
+            sponsorblock: SponsorBlockMode::Off,
+            sponsorblock_categories: default_sponsorblock_categories(),
+        }
+    }
+}
+
 impl Default for Executables {
     fn default() -> Self {
         Self {
@@ -108,6 +364,25 @@ impl Default for Logging {
     }
 }
 
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_retry_max_attempts(),
+            backoff_seconds: default_retry_backoff(),
+        }
+    }
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_seconds: default_cache_ttl(),
+            max_entries: default_cache_max_entries(),
+        }
+    }
+}
+
 fn default_theme() -> String {
     "Default".to_string()
 }
@@ -148,6 +423,46 @@ fn default_download_directory() -> String {
 fn default_progress_style() -> String {
     "━".to_string()
 }
+fn default_max_concurrent_downloads() -> u32 {
+    3
+}
+fn default_cache_ttl() -> u64 {
+    3600
+}
+fn default_cache_max_entries() -> usize {
+    500
+}
+fn default_retry_max_attempts() -> u32 {
+    3
+}
+fn default_retry_backoff() -> u64 {
+    10
+}
+fn default_profile_format() -> String {
+    "bestvideo+bestaudio/best".to_string()
+}
+fn default_sponsorblock_categories() -> String {
+    "sponsor,selfpromo,interaction".to_string()
+}
+fn default_sponsorblock_api_url() -> String {
+    "https://sponsor.ajay.app".to_string()
+}
+fn default_sponsorblock_skip_categories() -> Vec<String> {
+    vec![
+        "sponsor".to_string(),
+        "intro".to_string(),
+        "selfpromo".to_string(),
+    ]
+}
+fn default_terminal_max_height() -> u32 {
+    720
+}
+fn default_terminal_max_fps() -> u32 {
+    30
+}
+pub fn default_output_template() -> String {
+    "%(title).150s - %(id)s.%(ext)s".to_string()
+}
 
 impl Default for Config {
     fn default() -> Self {
@@ -160,9 +475,21 @@ impl Default for Config {
             show_live: default_true(),
             show_playlists: default_true(),
             progress_style: default_progress_style(),
+            max_concurrent_downloads: default_max_concurrent_downloads(),
+            auto_resume_downloads: default_true(),
+            download_rate_limit: String::new(),
             executables: Executables::default(),
             cookies: Cookies::default(),
             logging: Logging::default(),
+            cache: Cache::default(),
+            retry: Retry::default(),
+            postprocess: PostProcessing::default(),
+            output: Output::default(),
+            subtitles: Subtitles::default(),
+            sponsorblock: SponsorBlock::default(),
+            terminal_video: TerminalVideo::default(),
+            rate_schedule: Vec::new(),
+            profiles: Vec::new(),
         }
     }
 }
@@ -256,6 +583,29 @@ impl Config {
         anyhow::bail!("Could not determine project directories")
     }
 
+    pub fn get_state_dir() -> Result<PathBuf> {
+        if let Some(dirs) = ProjectDirs::from("com", "rataplay", "rataplay") {
+            // Linux: ~/.local/state/rataplay (XDG State Home)
+            if let Some(state) = dirs.state_dir() {
+                return Ok(state.to_path_buf());
+            }
+
+            // macOS/Windows have no state dir, keep it next to other local data
+            return Ok(dirs.data_local_dir().to_path_buf());
+        }
+
+        anyhow::bail!("Could not determine project directories")
+    }
+
+    pub fn get_cache_dir() -> Result<PathBuf> {
+        if let Some(dirs) = ProjectDirs::from("com", "rataplay", "rataplay") {
+            // Linux: ~/.cache/rataplay, macOS: ~/Library/Caches/..., Windows: %LOCALAPPDATA%/.../cache
+            return Ok(dirs.cache_dir().to_path_buf());
+        }
+
+        anyhow::bail!("Could not determine project directories")
+    }
+
     pub fn save(&self) -> anyhow::Result<()> {
         let path = Self::get_config_path();
         info!("Saving config to {:?}", path);
@@ -305,6 +655,9 @@ impl Config {
             "show_live",
             "show_playlists",
             "progress_style",
+            "max_concurrent_downloads",
+            "auto_resume_downloads",
+            "download_rate_limit",
         ];
         let mut first_section_index = None;
 
@@ -384,6 +737,24 @@ impl Config {
                                 root_keys_updated.insert("progress_style");
                             }
                         }
+                        "max_concurrent_downloads" => {
+                            new_line = format!(
+                                "max_concurrent_downloads = {}",
+                                self.max_concurrent_downloads
+                            );
+                            root_keys_updated.insert("max_concurrent_downloads");
+                        }
+                        "auto_resume_downloads" => {
+                            new_line =
+                                format!("auto_resume_downloads = {}", self.auto_resume_downloads);
+                            root_keys_updated.insert("auto_resume_downloads");
+                        }
+                        "download_rate_limit" => {
+                            if let Ok(val) = serde_json::to_string(&self.download_rate_limit) {
+                                new_line = format!("download_rate_limit = {}", val);
+                                root_keys_updated.insert("download_rate_limit");
+                            }
+                        }
                         k if root_keys.contains(&k) => {
                             root_keys_updated.insert(k);
                         }
@@ -397,6 +768,10 @@ impl Config {
                     if key == "enabled" {
                         new_line = format!("enabled = {}", self.cookies.enabled);
                     }
+                } else if current_section == "cache" {
+                    if key == "enabled" {
+                        new_line = format!("enabled = {}", self.cache.enabled);
+                    }
                 } else if current_section == "logging" {
                     if key == "enabled" {
                         new_line = format!("enabled = {}", self.logging.enabled);
@@ -448,6 +823,19 @@ impl Config {
                             missing_lines.push(format!("progress_style = {}", val));
                         }
                     }
+                    "max_concurrent_downloads" => missing_lines.push(format!(
+                        "max_concurrent_downloads = {}",
+                        self.max_concurrent_downloads
+                    )),
+                    "auto_resume_downloads" => missing_lines.push(format!(
+                        "auto_resume_downloads = {}",
+                        self.auto_resume_downloads
+                    )),
+                    "download_rate_limit" => {
+                        if let Ok(val) = serde_json::to_string(&self.download_rate_limit) {
+                            missing_lines.push(format!("download_rate_limit = {}", val));
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -521,6 +909,27 @@ impl Config {
             serde_json::to_string(&self.progress_style)?
         ));
 
+        content
+            .push_str("# How many downloads run at the same time. The rest wait in the queue.\n");
+        content.push_str(&format!(
+            "max_concurrent_downloads = {}\n\n",
+            self.max_concurrent_downloads
+        ));
+
+        content.push_str(
+            "# Restart downloads that were running when the app was closed. Otherwise they come back paused.\n",
+        );
+        content.push_str(&format!(
+            "auto_resume_downloads = {}\n\n",
+            self.auto_resume_downloads
+        ));
+
+        content.push_str("# Download speed limit passed to yt-dlp, e.g. \"500K\" or \"2M\". Empty means unlimited.\n");
+        content.push_str(&format!(
+            "download_rate_limit = {}\n\n",
+            serde_json::to_string(&self.download_rate_limit)?
+        ));
+
         content.push_str("# --- Advanced Configuration ---\n\n");
 
         content.push_str("[executables]\n");
@@ -629,6 +1038,210 @@ impl Config {
             content.push_str("# You can set a custom path for the log file.\n");
             content.push_str("# path = \"/absolute/path/to/rataplay.log\"\n");
         }
+        content.push('\n');
+
+        content.push_str("[cache]\n");
+        content.push_str("# Search results, video details and formats are cached on disk.\n");
+        content.push_str(
+            "# Results older than ttl_seconds are still shown, then refreshed in the background.\n",
+        );
+        content.push_str(&format!("enabled = {}\n", self.cache.enabled));
+        content.push_str(&format!("ttl_seconds = {}\n", self.cache.ttl_seconds));
+        content.push_str("# Oldest entries are removed once this many are stored.\n");
+        content.push_str(&format!("max_entries = {}\n", self.cache.max_entries));
+        content.push('\n');
+
+        content.push_str("[retry]\n");
+        content.push_str(
+            "# Downloads failing with network errors (HTTP 403/429/5xx, resets, fragments) are retried.\n",
+        );
+        content.push_str("# The wait doubles after each attempt, starting at backoff_seconds. 0 disables retries.\n");
+        content.push_str(&format!("max_attempts = {}\n", self.retry.max_attempts));
+        content.push_str(&format!(
+            "backoff_seconds = {}\n",
+            self.retry.backoff_seconds
+        ));
+        content.push('\n');
+
+        content.push_str("[postprocess]\n");
+        content.push_str(
+            "# Defaults for new downloads, each can be changed on the format selection screen.\n",
+        );
+        content.push_str(&format!(
+            "embed_thumbnail = {}\n",
+            self.postprocess.embed_thumbnail
+        ));
+        content.push_str("# Title, uploader, description and chapters.\n");
+        content.push_str(&format!(
+            "embed_metadata = {}\n",
+            self.postprocess.embed_metadata
+        ));
+        content.push_str(&format!(
+            "embed_subtitles = {}\n",
+            self.postprocess.embed_subtitles
+        ));
+        content
+            .push_str("# SponsorBlock segments: \"off\", \"mark\" (as chapters) or \"remove\".\n");
+        content.push_str(&format!(
+            "sponsorblock = \"{}\"\n",
+            self.postprocess.sponsorblock.name()
+        ));
+        content.push_str("# Options: sponsor, intro, outro, selfpromo, preview, filler, interaction, music_offtopic\n");
+        content.push_str(&format!(
+            "sponsorblock_categories = {}\n",
+            serde_json::to_string(&self.postprocess.sponsorblock_categories)?
+        ));
+        content.push('\n');
+
+        content.push_str("[output]\n");
+        content.push_str(
+            "# yt-dlp output template for file names, see \"OUTPUT TEMPLATE\" in its manual.\n",
+        );
+        content.push_str(&format!(
+            "template = {}\n",
+            serde_json::to_string(&self.output.template)?
+        ));
+        content.push_str("# Sort downloads into subfolders, nested in this order.\n");
+        content.push_str(&format!(
+            "channel_folders = {}\n",
+            self.output.channel_folders
+        ));
+        content.push_str(&format!(
+            "playlist_folders = {}\n",
+            self.output.playlist_folders
+        ));
+        content.push_str("# Upload date folders: \"off\", \"year\" or \"month\".\n");
+        content.push_str(&format!(
+            "date_folders = \"{}\"\n",
+            self.output.date_folders.name()
+        ));
+        content.push_str(
+            "# Separate destinations for audio-only and video downloads. Relative paths are\n",
+        );
+        content.push_str("# inside download_directory, empty keeps everything together.\n");
+        content.push_str(&format!(
+            "audio_directory = {}\n",
+            serde_json::to_string(&self.output.audio_directory)?
+        ));
+        content.push_str(&format!(
+            "video_directory = {}\n",
+            serde_json::to_string(&self.output.video_directory)?
+        ));
+        content.push('\n');
+
+        content.push_str("[subtitles]\n");
+        content.push_str(
+            "# Preferred languages, e.g. [\"en\", \"de\"]. Selected during playback and preselected\n",
+        );
+        content.push_str("# on the format selection screen for downloading next to the media.\n");
+        content.push_str(&format!(
+            "languages = {}\n",
+            serde_json::to_string(&self.subtitles.languages)?
+        ));
+        content
+            .push_str("# Use auto-generated captions when there are no subtitles in a language.\n");
+        content.push_str(&format!(
+            "auto_captions = {}\n",
+            self.subtitles.auto_captions
+        ));
+        content.push('\n');
+
+        content.push_str("[sponsorblock]\n");
+        content.push_str(
+            "# Skip segments automatically while streaming, \"z\" jumps back into the last one.\n",
+        );
+        content.push_str(&format!("enabled = {}\n", self.sponsorblock.enabled));
+        content.push_str("# Point this at a mirror or a local instance if you like.\n");
+        content.push_str(&format!(
+            "api_url = {}\n",
+            serde_json::to_string(&self.sponsorblock.api_url)?
+        ));
+        content.push_str(
+            "# sponsor, intro, outro, selfpromo, interaction, preview, music_offtopic, filler\n",
+        );
+        content.push_str(&format!(
+            "categories = {}\n",
+            serde_json::to_string(&self.sponsorblock.categories)?
+        ));
+        content.push('\n');
+
+        content.push_str("[terminal_video]\n");
+        content.push_str(
+            "# mpv output for \"Watch in Terminal\": \"auto\", \"kitty\", \"sixel\" or \"tct\".\n",
+        );
+        content.push_str(
+            "# auto uses kitty or sixel graphics when the terminal supports them, tct otherwise.\n",
+        );
+        content.push_str(&format!(
+            "output = \"{}\"\n",
+            self.terminal_video.output.name()
+        ));
+        content.push_str("# Caps for the stream and the rendering, 0 means no cap.\n");
+        content.push_str(&format!(
+            "max_height = {}\n",
+            self.terminal_video.max_height
+        ));
+        content.push_str(&format!("max_fps = {}\n", self.terminal_video.max_fps));
+        content.push('\n');
+
+        content
+            .push_str("# Different speed limits by time of day. The first matching window wins,\n");
+        content
+            .push_str("# outside all windows download_rate_limit applies. Running downloads are\n");
+        content.push_str("# restarted with the new limit when a window starts or ends.\n");
+        if self.rate_schedule.is_empty() {
+            content.push_str("# [[rate_schedule]]\n");
+            content.push_str("# start = \"01:00\"\n");
+            content.push_str("# end = \"07:00\"\n");
+            content.push_str("# rate_limit = \"\"  # Unlimited at night\n");
+        }
+        for window in &self.rate_schedule {
+            content.push_str("[[rate_schedule]]\n");
+            content.push_str(&format!(
+                "start = {}\n",
+                serde_json::to_string(&window.start)?
+            ));
+            content.push_str(&format!("end = {}\n", serde_json::to_string(&window.end)?));
+            content.push_str(&format!(
+                "rate_limit = {}\n",
+                serde_json::to_string(&window.rate_limit)?
+            ));
+        }
+        content.push('\n');
+
+        content.push_str(
+            "# Download profiles, listed on top of the format selection screen and offered\n",
+        );
+        content.push_str("# for batch and playlist downloads.\n");
+        if self.profiles.is_empty() {
+            content.push_str("# [[profiles]]\n");
+            content.push_str("# name = \"archive-1080p\"\n");
+            content
+                .push_str("# format = \"bestvideo[height<=1080]+bestaudio/best[height<=1080]\"\n");
+            content.push_str("# merge_format = \"mkv\"\n");
+            content.push_str(
+                "# directory = \"Archive\"  # Inside download_directory unless absolute\n",
+            );
+            content.push_str("# output_template = \"%(uploader)s/%(title)s [%(id)s].%(ext)s\"\n");
+            content.push_str(
+                "# [profiles.postprocess]  # Optional, [postprocess] applies otherwise\n",
+            );
+            content.push_str("# embed_metadata = true\n");
+            content.push_str("# embed_thumbnail = true\n");
+            content.push_str("#\n");
+            content.push_str("# [[profiles]]\n");
+            content.push_str("# name = \"podcast-opus\"\n");
+            content.push_str("# format = \"bestaudio/best\"\n");
+            content.push_str("# audio_format = \"opus\"\n");
+        } else {
+            #[derive(Serialize)]
+            struct Profiles<'a> {
+                profiles: &'a [DownloadProfile],
+            }
+            content.push_str(&toml::to_string(&Profiles {
+                profiles: &self.profiles,
+            })?);
+        }
 
         fs::write(path, content)?;
         info!("Configuration saved successfully (new/force)");
@@ -1,50 +1,256 @@
-use crate::model::Video;
+use crate::model::download::{DownloadPhase, DownloadProgress, DownloadRequest};
 use crate::model::settings::Settings;
+use crate::sys::config::{DateFolders, Output, PostProcessing, RateWindow, SponsorBlockMode};
+use crate::sys::local;
 use crate::sys::yt::build_base_command;
 use anyhow::Result;
+use chrono::{Local, NaiveTime};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tokio::process::Child;
 
-pub fn parse_progress(line: &str) -> Option<(f64, String, String, String)> {
-    if !line.starts_with("[download]") {
-        return None;
+// Marks our progress template lines apart from anything else yt-dlp prints
+const PROGRESS_PREFIX: &str = "[rataplay-progress]";
+// Marks the line with the final file, printed once post-processing moved it in place
+const FILE_PREFIX: &str = "[rataplay-file]";
+
+/// The final file of a download, from the line printed by the template in `start_download`.
+pub fn parse_output_file(line: &str) -> Option<PathBuf> {
+    let path = line.strip_prefix(FILE_PREFIX)?.trim();
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
+
+/// Parses a line printed by the progress templates set up in `start_download`.
+/// Post-processing lines only carry the phase, byte counts are left at zero.
+pub fn parse_progress(line: &str) -> Option<DownloadProgress> {
+    let json = line.strip_prefix(PROGRESS_PREFIX)?.trim();
+    let val: Value = serde_json::from_str(json).ok()?;
+
+    if let Some(postprocessor) = val["postprocessor"].as_str() {
+        let phase = if postprocessor == "Merger" {
+            DownloadPhase::Merging
+        } else {
+            DownloadPhase::PostProcessing
+        };
+        return Some(DownloadProgress {
+            phase,
+            postprocessor: Some(postprocessor.to_string()),
+            ..Default::default()
+        });
     }
 
-    let parts: Vec<&str> = line.split_whitespace().collect();
-    // Example: [download] 1.5% of ~4.30MiB at 2.50MiB/s ETA 00:01
-    // idx: 0      1    2  3        4  5         6   7
+    // yt-dlp reports some of these as floats, others as ints, depending on the downloader
+    let number = |key: &str| val[key].as_f64();
+    Some(DownloadProgress {
+        downloaded_bytes: number("downloaded_bytes").unwrap_or(0.0) as u64,
+        total_bytes: number("total_bytes")
+            .or_else(|| number("total_bytes_estimate"))
+            .map(|n| n as u64),
+        speed: number("speed"),
+        eta: number("eta").map(|n| n as u64),
+        fragment_index: number("fragment_index").map(|n| n as u64),
+        fragment_count: number("fragment_count").map(|n| n as u64),
+        phase: DownloadPhase::Downloading,
+        postprocessor: None,
+    })
+}
+
+/// Collects what yt-dlp printed to stderr that matters once it exits with an error.
+#[derive(Debug, Default)]
+pub struct ErrorLog {
+    pub last_error: Option<String>,
+    saw_transient: bool,
+}
 
-    if parts.len() < 4 {
-        return None;
+impl ErrorLog {
+    pub fn push(&mut self, line: &str) {
+        if let Some(message) = line.strip_prefix("ERROR:") {
+            self.last_error = Some(message.trim().to_string());
+        }
+        if is_transient_error(line) {
+            self.saw_transient = true;
+        }
     }
 
-    let progress_str = parts[1].trim_end_matches('%');
-    let progress = progress_str.parse::<f64>().ok()?;
+    /// Whether the failure looks like network trouble that may go away on retry.
+    /// The final ERROR line decides; warnings only count when yt-dlp printed no error.
+    pub fn is_transient(&self) -> bool {
+        match &self.last_error {
+            Some(message) => is_transient_error(message),
+            None => self.saw_transient,
+        }
+    }
+}
 
-    let size = parts[3].trim_start_matches('~').to_string();
+pub fn is_transient_error(line: &str) -> bool {
+    const PATTERNS: [&str; 10] = [
+        "http error 403",
+        "http error 429",
+        "http error 5",
+        "connection reset",
+        "connection aborted",
+        "remote end closed connection",
+        "timed out",
+        "incompleteread",
+        "temporary failure in name resolution",
+        "fragment",
+    ];
+    let line = line.to_lowercase();
+    PATTERNS.iter().any(|p| line.contains(p))
+}
 
-    let mut speed = String::new();
-    let mut eta = String::new();
+/// Checks a yt-dlp rate such as "500K", "2M" or "1.5G". Empty means unlimited.
+pub fn is_valid_rate(rate: &str) -> bool {
+    if rate.is_empty() {
+        return true;
+    }
+    let number = rate.trim_end_matches(['K', 'M', 'G', 'k', 'm', 'g']);
+    rate.len() - number.len() <= 1 && number.parse::<f64>().is_ok_and(|n| n > 0.0)
+}
 
-    // Search for "at" and "ETA" as they might be missing or in different positions
-    for i in 4..parts.len() {
-        if parts[i] == "at" && i + 1 < parts.len() {
-            speed = parts[i + 1].to_string();
-        } else if parts[i] == "ETA" && i + 1 < parts.len() {
-            eta = parts[i + 1].to_string();
+fn window_contains(window: &RateWindow, now: NaiveTime) -> bool {
+    let start = NaiveTime::parse_from_str(&window.start, "%H:%M");
+    let end = NaiveTime::parse_from_str(&window.end, "%H:%M");
+    match (start, end) {
+        (Ok(start), Ok(end)) if start <= end => start <= now && now < end,
+        // Wraps past midnight, e.g. 22:00 - 06:00
+        (Ok(start), Ok(end)) => now >= start || now < end,
+        _ => {
+            log::warn!(
+                "Ignoring rate schedule window with invalid times: {} - {}",
+                window.start,
+                window.end
+            );
+            false
         }
     }
+}
+
+/// The global rate limit in effect at `now`, taking the schedule into account.
+pub fn scheduled_rate_limit(settings: &Settings, now: NaiveTime) -> String {
+    settings
+        .rate_schedule
+        .iter()
+        .find(|w| window_contains(w, now))
+        .map(|w| w.rate_limit.clone())
+        .unwrap_or_else(|| settings.download_rate_limit.clone())
+}
+
+fn postprocess_args(postprocess: &PostProcessing) -> Vec<String> {
+    let mut args = Vec::new();
+    if postprocess.embed_thumbnail {
+        args.push("--embed-thumbnail".to_string());
+    }
+    if postprocess.embed_metadata {
+        args.push("--embed-metadata".to_string());
+        args.push("--embed-chapters".to_string());
+    }
+    if postprocess.embed_subtitles {
+        args.push("--embed-subs".to_string());
+    }
+    let categories = if postprocess.sponsorblock_categories.trim().is_empty() {
+        "default".to_string()
+    } else {
+        postprocess.sponsorblock_categories.replace(' ', "")
+    };
+    match postprocess.sponsorblock {
+        SponsorBlockMode::Off => {}
+        SponsorBlockMode::Mark => {
+            args.push("--sponsorblock-mark".to_string());
+            args.push(categories);
+        }
+        SponsorBlockMode::Remove => {
+            args.push("--sponsorblock-remove".to_string());
+            args.push(categories);
+        }
+    }
+    args
+}
+
+/// Where a download goes: the directory passed with `-P` and the `-o` template,
+/// which includes the routing subfolders. Channel and date folders are filled in
+/// by yt-dlp, the playlist is only known to us.
+pub fn output_location(
+    request: &DownloadRequest,
+    download_dir: &Path,
+    output: &Output,
+) -> (PathBuf, String) {
+    let profile = request.profile.as_ref();
+    let audio_only = request.audio_only || profile.is_some_and(|p| !p.audio_format.is_empty());
+
+    let directory = match profile.map(|p| p.directory.as_str()) {
+        Some(dir) if !dir.is_empty() => dir,
+        _ if audio_only => output.audio_directory.as_str(),
+        _ => output.video_directory.as_str(),
+    };
+    let dir = local::resolve_in(download_dir, directory);
+
+    let template = match profile.map(|p| p.output_template.as_str()) {
+        Some(template) if !template.is_empty() => template.to_string(),
+        _ if !output.template.is_empty() => output.template.clone(),
+        _ => crate::sys::config::default_output_template(),
+    };
+
+    // A whole playlist keeps together in its own folder, numbered in playlist order
+    if let Some(item) = &request.playlist {
+        let name = format!("{} - ", item.number());
+        let template = match template.rsplit_once('/') {
+            Some((folders, file)) => format!("{}/{}{}", folders, name, file),
+            None => format!("{}{}", name, template),
+        };
+        return (
+            dir,
+            format!(
+                "{}/{}",
+                local::folder_name(&item.title).replace('%', "%%"),
+                template
+            ),
+        );
+    }
+
+    let mut folders = Vec::new();
+    if output.channel_folders {
+        folders.push("%(channel,uploader|Unknown Channel)s".to_string());
+    }
+    if output.playlist_folders
+        && let Some(playlist) = &request.video.parent_playlist_title
+    {
+        // Literal text in a template, so path separators and % must not get through
+        folders.push(local::folder_name(playlist).replace('%', "%%"));
+    }
+    match output.date_folders {
+        DateFolders::Off => {}
+        DateFolders::Year => folders.push("%(upload_date>%Y|Undated)s".to_string()),
+        DateFolders::Month => folders.push("%(upload_date>%Y-%m|Undated)s".to_string()),
+    }
+    folders.push(template);
 
-    Some((progress, size, speed, eta))
+    (dir, folders.join("/"))
 }
 
+/// The folder a playlist downloaded as a whole goes to, where its `.m3u` is written.
+pub fn playlist_folder(
+    request: &DownloadRequest,
+    download_dir: &Path,
+    output: &Output,
+) -> Option<PathBuf> {
+    let item = request.playlist.as_ref()?;
+    let (dir, _) = output_location(request, download_dir, output);
+    Some(dir.join(local::folder_name(&item.title)))
+}
+
+/// Spawns yt-dlp for a queued download.
 pub async fn start_download(
-    video: &Video,
-    format_id: &str,
+    request: &DownloadRequest,
     download_dir: &str,
     settings: &Settings,
 ) -> Result<Child> {
-    let download_dir = std::path::PathBuf::from(download_dir);
+    let video = &request.video;
+    let format_id = request.format_id.as_str();
+    let profile = request.profile.as_ref();
+    let (download_dir, output_template) =
+        output_location(request, Path::new(download_dir), &settings.output);
 
     if let Err(e) = tokio::fs::create_dir_all(&download_dir).await {
         anyhow::bail!("Failed to create download dir: {}", e);
@@ -52,7 +258,10 @@ pub async fn start_download(
 
     let mut cmd = build_base_command(settings);
     cmd.kill_on_drop(true);
-    let format_arg = if format_id == "best" {
+    let format_arg = if profile.is_some() {
+        // Profiles carry a complete format expression
+        format_id.to_string()
+    } else if format_id == "best" {
         "bestvideo+bestaudio/best".to_string()
     } else {
         format!("{}+bestaudio/best", format_id)
@@ -60,10 +269,47 @@ pub async fn start_download(
 
     cmd.arg("-f").arg(format_arg);
     cmd.arg("-P").arg(&download_dir);
-    cmd.arg("-o").arg("%(title).150s - %(id)s.%(ext)s");
+    cmd.arg("-o").arg(&output_template);
+
+    if let Some(profile) = profile {
+        if !profile.merge_format.is_empty() {
+            cmd.arg("--merge-output-format").arg(&profile.merge_format);
+        }
+        if !profile.audio_format.is_empty() {
+            cmd.arg("-x")
+                .arg("--audio-format")
+                .arg(&profile.audio_format);
+        }
+    }
     cmd.arg("--newline");
     cmd.arg("--progress");
+    cmd.arg("--progress-template")
+        .arg(format!("download:{} %(progress)j", PROGRESS_PREFIX));
+    cmd.arg("--progress-template")
+        .arg(format!("postprocess:{} %(progress)j", PROGRESS_PREFIX));
+    // Printing makes yt-dlp quiet, the progress templates still show with --progress
+    cmd.arg("--print")
+        .arg(format!("after_move:{} %(filepath)s", FILE_PREFIX));
     cmd.arg("--write-info-json");
+
+    cmd.args(postprocess_args(&request.postprocess));
+
+    if !request.subtitles.is_empty() {
+        let langs: Vec<&str> = request.subtitles.iter().map(|s| s.lang.as_str()).collect();
+        cmd.arg("--write-subs");
+        if request.subtitles.iter().any(|s| s.auto) {
+            cmd.arg("--write-auto-subs");
+        }
+        cmd.arg("--sub-langs").arg(langs.join(","));
+    }
+
+    let rate_limit = match &request.rate_limit {
+        Some(rate) => rate.clone(),
+        None => scheduled_rate_limit(settings, Local::now().time()),
+    };
+    if !rate_limit.is_empty() {
+        cmd.arg("--limit-rate").arg(&rate_limit);
+    }
     cmd.arg(&video.url);
 
     cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
@@ -82,3 +328,143 @@ pub async fn start_download(
     })?;
     Ok(child)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::download::PlaylistItem;
+
+    fn window(start: &str, end: &str, rate_limit: &str) -> RateWindow {
+        RateWindow {
+            start: start.to_string(),
+            end: end.to_string(),
+            rate_limit: rate_limit.to_string(),
+        }
+    }
+
+    fn at(time: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_scheduled_rate_limit() {
+        let settings = Settings {
+            download_rate_limit: "2M".to_string(),
+            rate_schedule: vec![window("01:00", "07:00", ""), window("22:00", "00:30", "5M")],
+            ..Default::default()
+        };
+
+        assert_eq!(scheduled_rate_limit(&settings, at("03:00")), "");
+        assert_eq!(scheduled_rate_limit(&settings, at("07:00")), "2M");
+        assert_eq!(scheduled_rate_limit(&settings, at("12:00")), "2M");
+        assert_eq!(scheduled_rate_limit(&settings, at("23:15")), "5M");
+        assert_eq!(scheduled_rate_limit(&settings, at("00:10")), "5M");
+    }
+
+    #[test]
+    fn test_parse_progress() {
+        let line = r#"[rataplay-progress] {"status": "downloading", "downloaded_bytes": 1048576, "total_bytes_estimate": 4194304.5, "speed": 524288.0, "eta": 6, "fragment_index": 3, "fragment_count": 12}"#;
+        let progress = parse_progress(line).unwrap();
+        assert_eq!(progress.downloaded_bytes, 1048576);
+        assert_eq!(progress.total_bytes, Some(4194304));
+        assert_eq!(progress.speed, Some(524288.0));
+        assert_eq!(progress.eta, Some(6));
+        assert_eq!(progress.fragment_index, Some(3));
+        assert_eq!(progress.phase, DownloadPhase::Downloading);
+        assert_eq!(progress.percent().map(|p| p.round()), Some(25.0));
+
+        let line = r#"[rataplay-progress] {"status": "started", "postprocessor": "Merger"}"#;
+        assert_eq!(parse_progress(line).unwrap().phase, DownloadPhase::Merging);
+
+        assert!(parse_progress("[download]   1.5% of ~4.30MiB at 2.50MiB/s ETA 00:01").is_none());
+
+        assert_eq!(
+            parse_output_file("[rataplay-file] /downloads/Mix/01 - Song.opus"),
+            Some(PathBuf::from("/downloads/Mix/01 - Song.opus"))
+        );
+        assert!(parse_output_file("[download] Destination: Song.webm").is_none());
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        let mut log = ErrorLog::default();
+        log.push("WARNING: [youtube] Got error: HTTP Error 503: Service Unavailable. Retrying");
+        assert!(log.is_transient());
+        log.push("ERROR: [youtube] abc: Video unavailable. This video is private");
+        assert!(!log.is_transient());
+        assert_eq!(
+            log.last_error.as_deref(),
+            Some("[youtube] abc: Video unavailable. This video is private")
+        );
+
+        let mut log = ErrorLog::default();
+        log.push("ERROR: fragment 3 not found, unable to continue");
+        assert!(log.is_transient());
+    }
+
+    #[test]
+    fn test_output_location() {
+        let mut request = DownloadRequest {
+            video: crate::model::Video {
+                parent_playlist_title: Some("Mix / 100%".to_string()),
+                ..Default::default()
+            },
+            format_id: "best".to_string(),
+            rate_limit: None,
+            postprocess: PostProcessing::default(),
+            profile: None,
+            audio_only: false,
+            subtitles: Vec::new(),
+            playlist: None,
+        };
+        let output = Output {
+            channel_folders: true,
+            playlist_folders: true,
+            date_folders: DateFolders::Year,
+            audio_directory: "Music".to_string(),
+            ..Default::default()
+        };
+        let base = Path::new("/downloads");
+
+        let (dir, template) = output_location(&request, base, &output);
+        assert_eq!(dir, PathBuf::from("/downloads"));
+        assert_eq!(
+            template,
+            "%(channel,uploader|Unknown Channel)s/Mix _ 100%%/%(upload_date>%Y|Undated)s/%(title).150s - %(id)s.%(ext)s"
+        );
+
+        request.audio_only = true;
+        request.video.parent_playlist_title = None;
+        let (dir, template) = output_location(&request, base, &Output::default());
+        assert_eq!(dir, PathBuf::from("/downloads"));
+        assert_eq!(template, "%(title).150s - %(id)s.%(ext)s");
+        let (dir, _) = output_location(&request, base, &output);
+        assert_eq!(dir, PathBuf::from("/downloads/Music"));
+
+        request.audio_only = false;
+        request.playlist = Some(PlaylistItem {
+            id: "PL1".to_string(),
+            title: "Mix / 100%".to_string(),
+            index: 7,
+            count: 120,
+        });
+        let (dir, template) = output_location(&request, base, &output);
+        assert_eq!(dir, PathBuf::from("/downloads"));
+        assert_eq!(template, "Mix _ 100%%/007 - %(title).150s - %(id)s.%(ext)s");
+        assert_eq!(
+            playlist_folder(&request, base, &output),
+            Some(PathBuf::from("/downloads/Mix _ 100%"))
+        );
+    }
+
+    #[test]
+    fn test_is_valid_rate() {
+        assert!(is_valid_rate(""));
+        assert!(is_valid_rate("500K"));
+        assert!(is_valid_rate("1.5M"));
+        assert!(is_valid_rate("1048576"));
+        assert!(!is_valid_rate("fast"));
+        assert!(!is_valid_rate("2MM"));
+        assert!(!is_valid_rate("0"));
+    }
+}
@@ -1,7 +1,14 @@
 use anyhow::Result;
-use image::DynamicImage;
+use image::{DynamicImage, ImageFormat};
+use std::fs;
+use std::path::PathBuf;
 use std::sync::OnceLock;
 
+use crate::sys::config::Config;
+
+// Covers are only needed while something plays, keep a few for replays
+const MAX_COVERS: usize = 50;
+
 static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
 
 fn get_client() -> &'static reqwest::Client {
@@ -49,3 +56,52 @@ pub async fn download_image(url: &str, video_id: &str) -> Result<DynamicImage> {
 
     anyhow::bail!("Failed to download image for {}", video_id)
 }
+
+fn cover_path(video_id: &str) -> Result<PathBuf> {
+    let name: String = video_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    Ok(Config::get_cache_dir()?
+        .join("covers")
+        .join(format!("{}.jpg", name)))
+}
+
+/// `file://` URL of a cover written before, MPRIS clients want a local file.
+pub fn cover_url(video_id: &str) -> Option<String> {
+    let path = cover_path(video_id).ok()?;
+    path.exists().then(|| format!("file://{}", path.display()))
+}
+
+/// Writes a thumbnail to the cover cache and returns its URL, see `cover_url`.
+pub fn write_cover(video_id: &str, img: &DynamicImage) -> Result<String> {
+    let path = cover_path(video_id)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+        prune_covers(dir);
+    }
+    // JPEG has no alpha channel
+    DynamicImage::ImageRgb8(img.to_rgb8()).save_with_format(&path, ImageFormat::Jpeg)?;
+    Ok(format!("file://{}", path.display()))
+}
+
+fn prune_covers(dir: &std::path::Path) {
+    let mut covers: Vec<_> = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir
+            .flatten()
+            .filter_map(|e| {
+                let modified = e.metadata().and_then(|m| m.modified()).ok()?;
+                Some((modified, e.path()))
+            })
+            .collect(),
+        Err(_) => return,
+    };
+    if covers.len() < MAX_COVERS {
+        return;
+    }
+    covers.sort_by_key(|(modified, _)| *modified);
+    let excess = covers.len() + 1 - MAX_COVERS;
+    for (_, path) in covers.into_iter().take(excess) {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::model::local::LocalFile;
+use crate::model::settings::Settings;
 use anyhow::Result;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -30,53 +31,116 @@ pub fn resolve_path(path_str: &str) -> PathBuf {
     path
 }
 
-pub fn scan_local_files(dir: &Path) -> Vec<LocalFile> {
-    let mut files = Vec::new();
+/// Resolves `dir` inside `base`. Absolute and `~` paths are used as they are.
+pub fn resolve_in(base: &Path, dir: &str) -> PathBuf {
+    if dir.is_empty() {
+        return base.to_path_buf();
+    }
+    let resolved = resolve_path(dir);
+    if resolved.is_absolute() {
+        resolved
+    } else {
+        base.join(resolved)
+    }
+}
+
+/// Folders downloads can end up in: the download directory, plus routing
+/// and profile directories that lie outside of it.
+pub fn download_roots(settings: &Settings) -> Vec<PathBuf> {
+    let base = resolve_path(&settings.download_directory);
+    let mut roots = vec![base.clone()];
 
+    let dirs = [
+        &settings.output.audio_directory,
+        &settings.output.video_directory,
+    ]
+    .into_iter()
+    .chain(settings.profiles.iter().map(|p| &p.directory));
+    for dir in dirs {
+        let dir = resolve_in(&base, dir);
+        if !roots.iter().any(|root| dir.starts_with(root)) {
+            roots.push(dir);
+        }
+    }
+    roots
+}
+
+// Routing nests channel, playlist and date folders, profile templates may add more
+const MAX_SCAN_DEPTH: usize = 6;
+
+fn walk_files(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
     if let Ok(entries) = fs::read_dir(dir) {
         for entry in entries.flatten() {
             let path = entry.path();
-            if path.is_file() {
-                let name = path
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string();
-                let extension = path
-                    .extension()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string();
-
-                let metadata = path.metadata().ok();
-                let size_bytes = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-                let modified = metadata
-                    .as_ref()
-                    .and_then(|m| m.modified().ok())
-                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                    .map(|d| d.as_secs())
-                    .unwrap_or(0);
-
-                let size = format_size(size_bytes);
-                let is_garbage = name.ends_with(".part")
-                    || name.ends_with(".ytdl")
-                    || name.ends_with(".tmp")
-                    || name.ends_with(".info.json")
-                    || name.ends_with(".json");
-
-                if !is_garbage {
-                    files.push(LocalFile {
-                        name,
-                        path,
-                        size,
-                        extension,
-                        is_garbage,
-                        modified,
-                    });
+            if path.is_dir() {
+                let hidden = entry.file_name().to_string_lossy().starts_with('.');
+                if !hidden && depth < MAX_SCAN_DEPTH {
+                    walk_files(&path, depth + 1, files);
                 }
+            } else if path.is_file() {
+                files.push(path);
             }
         }
     }
+}
+
+/// Files in `dirs` and their subfolders, hidden folders are skipped.
+fn files_in(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for dir in dirs {
+        walk_files(dir, 0, &mut files);
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+pub fn scan_local_files(dirs: &[PathBuf]) -> Vec<LocalFile> {
+    let mut files = Vec::new();
+
+    for path in files_in(dirs) {
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let extension = path
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        let metadata = path.metadata().ok();
+        let size_bytes = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+        let modified = metadata
+            .as_ref()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let size = format_size(size_bytes);
+        let is_garbage = name.ends_with(".part")
+            || name.ends_with(".ytdl")
+            || name.ends_with(".tmp")
+            || name.ends_with(".info.json")
+            || name.ends_with(".json");
+        // Subtitles downloaded next to the media, mpv picks them up from there
+        let is_sidecar = [".vtt", ".srt", ".ass", ".lrc"]
+            .iter()
+            .any(|ext| name.ends_with(ext));
+
+        if !is_garbage && !is_sidecar {
+            files.push(LocalFile {
+                name,
+                path,
+                size,
+                extension,
+                is_garbage,
+                modified,
+            });
+        }
+    }
     // Sort by modified time descending (newest first)
     files.sort_by(|a, b| b.modified.cmp(&a.modified));
     files
@@ -92,7 +156,7 @@ fn format_size(bytes: u64) -> String {
 }
 
 pub fn scan_download_tasks(
-    dir: &Path,
+    dirs: &[PathBuf],
 ) -> Vec<(
     crate::model::Video,
     String,
@@ -101,64 +165,61 @@ pub fn scan_download_tasks(
 )> {
     let mut tasks = Vec::new();
 
-    if let Ok(entries) = fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            let name = path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-
-            if name.ends_with(".info.json") {
-                if let Ok(content) = fs::read_to_string(&path) {
-                    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                        let id = json["id"].as_str().unwrap_or("").to_string();
-                        let title = json["title"].as_str().unwrap_or("").to_string();
-                        let url = json["webpage_url"]
-                            .as_str()
-                            .or_else(|| json["url"].as_str())
-                            .unwrap_or("")
-                            .to_string();
-                        let format_id = json["format_id"].as_str().unwrap_or("best").to_string();
-
-                        if !id.is_empty() && !url.is_empty() {
-                            let mut video = crate::model::Video::default();
-                            video.id = id.clone();
-                            video.title = title.clone();
-                            video.url = url.clone();
-
-                            let base_path_str = path.to_string_lossy();
-                            let base_path = base_path_str
-                                .strip_suffix(".info.json")
-                                .unwrap_or(&base_path_str);
-
-                            let mut status = crate::model::download::DownloadStatus::Finished;
-
-                            if let Some(filename) = json["_filename"].as_str() {
-                                let part_path = PathBuf::from(format!("{}.part", filename));
+    for path in files_in(dirs) {
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        if name.ends_with(".info.json") {
+            if let Ok(content) = fs::read_to_string(&path) {
+                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
+                    let id = json["id"].as_str().unwrap_or("").to_string();
+                    let title = json["title"].as_str().unwrap_or("").to_string();
+                    let url = json["webpage_url"]
+                        .as_str()
+                        .or_else(|| json["url"].as_str())
+                        .unwrap_or("")
+                        .to_string();
+                    let format_id = json["format_id"].as_str().unwrap_or("best").to_string();
+
+                    if !id.is_empty() && !url.is_empty() {
+                        let mut video = crate::model::Video::default();
+                        video.id = id.clone();
+                        video.title = title.clone();
+                        video.url = url.clone();
+
+                        let base_path_str = path.to_string_lossy();
+                        let base_path = base_path_str
+                            .strip_suffix(".info.json")
+                            .unwrap_or(&base_path_str);
+
+                        let mut status = crate::model::download::DownloadStatus::Finished;
+
+                        if let Some(filename) = json["_filename"].as_str() {
+                            let part_path = PathBuf::from(format!("{}.part", filename));
+                            if part_path.exists() {
+                                status = crate::model::download::DownloadStatus::Canceled;
+                            }
+                        } else {
+                            let extensions = ["mp4", "mkv", "webm", "mp3", "m4a", "opus"];
+                            for ext in extensions {
+                                let part_path =
+                                    PathBuf::from(format!("{}.{}.part", base_path, ext));
                                 if part_path.exists() {
                                     status = crate::model::download::DownloadStatus::Canceled;
+                                    break;
                                 }
-                            } else {
-                                let extensions = ["mp4", "mkv", "webm", "mp3", "m4a", "opus"];
-                                for ext in extensions {
-                                    let part_path =
-                                        PathBuf::from(format!("{}.{}.part", base_path, ext));
-                                    if part_path.exists() {
-                                        status = crate::model::download::DownloadStatus::Canceled;
-                                        break;
-                                    }
-                                    let part_path_2 = PathBuf::from(format!("{}.part", base_path));
-                                    if part_path_2.exists() {
-                                        status = crate::model::download::DownloadStatus::Canceled;
-                                        break;
-                                    }
+                                let part_path_2 = PathBuf::from(format!("{}.part", base_path));
+                                if part_path_2.exists() {
+                                    status = crate::model::download::DownloadStatus::Canceled;
+                                    break;
                                 }
                             }
-
-                            tasks.push((video, format_id, status, path.clone()));
                         }
+
+                        tasks.push((video, format_id, status, path.clone()));
                     }
                 }
             }
@@ -167,6 +228,15 @@ pub fn scan_download_tasks(
     tasks
 }
 
+/// Chapters from the `.info.json` yt-dlp wrote next to a downloaded file.
+pub fn info_json_chapters(media: &Path) -> Vec<crate::model::Chapter> {
+    fs::read_to_string(media.with_extension("info.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .map(|json| crate::sys::yt::parse_chapters(&json))
+        .unwrap_or_default()
+}
+
 pub fn delete_task_files(info_json: &Path) -> Result<()> {
     if !info_json.exists() {
         return Ok(());
@@ -200,25 +270,39 @@ pub fn delete_task_files(info_json: &Path) -> Result<()> {
     Ok(())
 }
 
+/// A title usable as a file or folder name.
+pub fn folder_name(title: &str) -> String {
+    title
+        .chars()
+        .map(|c| if c == '/' || c == '\\' { '_' } else { c })
+        .collect()
+}
+
+/// An `.m3u` in `folder` listing `files` in the given order, relative to it where possible.
+pub fn m3u_content(folder: &Path, files: &[PathBuf]) -> String {
+    let mut content = String::from("#EXTM3U\n");
+    for file in files {
+        let path = file.strip_prefix(folder).unwrap_or(file);
+        content.push_str(&path.to_string_lossy().replace('\\', "/"));
+        content.push('\n');
+    }
+    content
+}
+
 pub fn delete_file(path: &Path) -> Result<()> {
     fs::remove_file(path)?;
     Ok(())
 }
 
-pub fn cleanup_garbage(dir: &Path) -> Result<usize> {
+pub fn cleanup_garbage(dirs: &[PathBuf]) -> Result<usize> {
     let mut count = 0;
 
-    if let Ok(entries) = fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() {
-                let name = path.file_name().unwrap_or_default().to_string_lossy();
-                // We keep .info.json now as it represents persistent metadata
-                if name.ends_with(".part") || name.ends_with(".ytdl") || name.ends_with(".tmp") {
-                    if fs::remove_file(path).is_ok() {
-                        count += 1;
-                    }
-                }
+    for path in files_in(dirs) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        // We keep .info.json now as it represents persistent metadata
+        if name.ends_with(".part") || name.ends_with(".ytdl") || name.ends_with(".tmp") {
+            if fs::remove_file(path).is_ok() {
+                count += 1;
             }
         }
     }
@@ -1,4 +1,7 @@
-use souvlaki::{MediaControlEvent, MediaControls, MediaMetadata, PlatformConfig};
+use souvlaki::{
+    MediaControlEvent, MediaControls, MediaMetadata, MediaPlayback, MediaPosition, PlatformConfig,
+    SeekDirection,
+};
 use std::time::Duration;
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -10,8 +13,26 @@ pub enum MediaEvent {
     Next,
     Previous,
     Stop,
+    Seek(f64),        // seconds, negative is backwards
+    SetPosition(f64), // seconds from the start
+    SetVolume(f64),   // 1.0 is 100%
+    OpenUri(String),
+    Quit,
 }
 
+/// What desktop widgets show about the playing item.
+#[derive(Debug, Clone, Default)]
+pub struct NowPlaying {
+    pub title: String,
+    pub artist: Option<String>, // channel
+    pub album: Option<String>,  // parent playlist
+    pub duration: Option<Duration>,
+    pub cover_url: Option<String>, // file:// URL of the cached thumbnail
+}
+
+/// Playback status, metadata and volume for desktop widgets. The playback speed is
+/// not exposed: souvlaki 0.7 answers MPRIS `Rate` with a fixed 1.0 and has no way to
+/// set it, so other speeds are only reflected through the position updates.
 pub struct MediaController {
     controls: MediaControls,
 }
@@ -40,7 +61,18 @@ impl MediaController {
                 MediaControlEvent::Next => MediaEvent::Next,
                 MediaControlEvent::Previous => MediaEvent::Previous,
                 MediaControlEvent::Stop => MediaEvent::Stop,
-                _ => return, // Ignore others for now
+                // No amount given, use the same step as Next/Previous without a queue
+                MediaControlEvent::Seek(direction) => MediaEvent::Seek(signed(direction, 10.0)),
+                MediaControlEvent::SeekBy(direction, amount) => {
+                    MediaEvent::Seek(signed(direction, amount.as_secs_f64()))
+                }
+                MediaControlEvent::SetPosition(MediaPosition(position)) => {
+                    MediaEvent::SetPosition(position.as_secs_f64())
+                }
+                MediaControlEvent::SetVolume(volume) => MediaEvent::SetVolume(volume),
+                MediaControlEvent::OpenUri(uri) => MediaEvent::OpenUri(uri),
+                MediaControlEvent::Quit => MediaEvent::Quit,
+                MediaControlEvent::Raise => return, // There is no window to raise
             };
             let _ = tx.send(app_event);
         })?;
@@ -48,27 +80,50 @@ impl MediaController {
         Ok(Self { controls })
     }
 
-    pub fn set_playback_status(&mut self, playing: bool) -> Result<(), souvlaki::Error> {
+    pub fn set_playback_status(
+        &mut self,
+        playing: bool,
+        position: Option<Duration>,
+    ) -> Result<(), souvlaki::Error> {
+        let progress = position.map(MediaPosition);
         self.controls.set_playback(if playing {
-            souvlaki::MediaPlayback::Playing { progress: None }
+            MediaPlayback::Playing { progress }
         } else {
-            souvlaki::MediaPlayback::Paused { progress: None }
+            MediaPlayback::Paused { progress }
         })
     }
 
-    pub fn set_metadata(
-        &mut self,
-        title: &str,
-        artist: Option<&str>,
-        duration: Option<Duration>,
-    ) -> Result<(), souvlaki::Error> {
+    pub fn set_stopped(&mut self) -> Result<(), souvlaki::Error> {
+        self.controls.set_playback(MediaPlayback::Stopped)
+    }
+
+    /// Volume shown to MPRIS clients, 1.0 being 100%.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn set_volume(&mut self, volume: f64) -> Result<(), souvlaki::Error> {
+        self.controls.set_volume(volume)
+    }
+
+    /// Only MPRIS has a volume.
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    pub fn set_volume(&mut self, _volume: f64) -> Result<(), souvlaki::Error> {
+        Ok(())
+    }
+
+    pub fn set_metadata(&mut self, now_playing: &NowPlaying) -> Result<(), souvlaki::Error> {
         let metadata = MediaMetadata {
-            title: Some(title),
-            artist,
-            album: None,
-            duration,
-            cover_url: None,
+            title: Some(&now_playing.title),
+            artist: now_playing.artist.as_deref(),
+            album: now_playing.album.as_deref(),
+            duration: now_playing.duration,
+            cover_url: now_playing.cover_url.as_deref(),
         };
         self.controls.set_metadata(metadata)
     }
 }
+
+fn signed(direction: SeekDirection, seconds: f64) -> f64 {
+    match direction {
+        SeekDirection::Forward => seconds,
+        SeekDirection::Backward => -seconds,
+    }
+}
@@ -1,3 +1,4 @@
+pub mod cache;
 pub mod config;
 pub mod cookies;
 pub mod deps;
@@ -8,4 +9,7 @@ pub mod logging;
 pub mod media;
 pub mod mpv_ipc;
 pub mod process;
+pub mod sponsorblock;
+pub mod state;
+pub mod video;
 pub mod yt;
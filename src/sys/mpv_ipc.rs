@@ -1,118 +1,421 @@
 use anyhow::Result;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::sync::mpsc::UnboundedSender;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 
-pub fn get_ipc_path() -> String {
-    if cfg!(windows) {
-        format!(r"\\\\.\\pipe\\rataplay-mpv-{}", std::process::id())
-    } else {
-        format!("/tmp/rataplay-mpv-{}.sock", std::process::id())
+// mpv creates the socket shortly after starting, slow machines may need a few seconds
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const MAX_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Properties observed on every connection, their changes arrive as `Event`s.
+const OBSERVED_PROPERTIES: &[&str] = &[
+    "time-pos",
+    "duration",
+    "pause",
+    "eof-reached",
+    "idle-active",
+    "track-list",
+    "sub-visibility",
+    "volume",
+    "mute",
+    "speed",
+    "loop-file",
+    "playlist-pos",
+];
+
+/// A track from mpv's `track-list` property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub id: i64,
+    pub kind: String, // "sub", "audio" or "video"
+    pub lang: Option<String>,
+    pub title: Option<String>,
+    pub selected: bool,
+    pub external: bool, // loaded from a separate file or URL
+}
+
+impl Track {
+    pub fn label(&self) -> String {
+        let mut label = match (&self.title, &self.lang) {
+            (Some(title), Some(lang)) => format!("{} ({})", title, lang),
+            (Some(title), None) => title.clone(),
+            (None, Some(lang)) => lang.clone(),
+            (None, None) => format!("Track {}", self.id),
+        };
+        if self.external {
+            label.push_str(" [external]");
+        }
+        label
     }
 }
 
-pub async fn spawn_ipc_handler(
-    socket_path: String,
-    mut cmd_rx: tokio::sync::mpsc::UnboundedReceiver<String>,
-    res_tx: UnboundedSender<String>,
-) -> Result<()> {
-    // Wait a bit for mpv to create the socket
-    tokio::time::sleep(std::time::Duration::from_millis(600)).await;
+/// All tracks out of a `track-list` value.
+pub fn parse_tracks(data: &Value) -> Vec<Track> {
+    data.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|t| {
+            Some(Track {
+                id: t["id"].as_i64()?,
+                kind: t["type"].as_str()?.to_string(),
+                lang: t["lang"].as_str().map(str::to_string),
+                title: t["title"].as_str().map(str::to_string),
+                selected: t["selected"].as_bool().unwrap_or(false),
+                external: t["external"].as_bool().unwrap_or(false),
+            })
+        })
+        .collect()
+}
 
-    #[cfg(unix)]
-    {
-        match tokio::net::UnixStream::connect(&socket_path).await {
-            Ok(stream) => {
-                log::info!("Connected to MPV IPC socket: {}", socket_path);
-                let (reader, mut writer) = stream.into_split();
-                let mut reader = BufReader::new(reader);
-
-                // Spawning reader task
-                let reader_handle = tokio::spawn(async move {
-                    let mut line = String::new();
-                    while let Ok(n) = reader.read_line(&mut line).await {
-                        if n == 0 {
-                            break;
-                        }
-                        let _ = res_tx.send(line.clone());
-                        line.clear();
-                    }
-                });
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekMode {
+    Relative,
+    Absolute,
+}
+
+/// Where mpv shows feedback for a command on its own OSD.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Osd {
+    Default,
+    Msg,    // text message
+    MsgBar, // text message and progress bar
+}
+
+/// How `loadfile` treats the playlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadMode {
+    Replace, // play now, dropping the playlist
+    Append,  // play after the last entry
+}
+
+/// A command for mpv, see "List of Input Commands" in its manual.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    SetProperty(&'static str, Value),
+    Cycle(&'static str, Osd),
+    Add(&'static str, f64, Osd),
+    Seek(f64, SeekMode, Osd),
+    ObserveProperty(u64, &'static str),
+    LoadFile(String, LoadMode, Option<f64>), // url and start position
+    PlaylistClear,                           // all entries except the current one
+    PlaylistNext,
+    PlaylistPrev,
+}
 
-                // Writer loop
-                while let Some(cmd) = cmd_rx.recv().await {
-                    let _ = writer.write_all(cmd.as_bytes()).await;
-                    let _ = writer.flush().await;
+impl Command {
+    fn args(&self) -> Value {
+        let osd_prefix = |osd: &Osd| match osd {
+            Osd::Default => None,
+            Osd::Msg => Some(json!("osd-msg")),
+            Osd::MsgBar => Some(json!("osd-msg-bar")),
+        };
+        match self {
+            Self::SetProperty(name, value) => json!(["set_property", name, value]),
+            Self::Cycle(name, osd) => osd_prefix(osd)
+                .into_iter()
+                .chain([json!("cycle"), json!(name)])
+                .collect(),
+            Self::Add(name, value, osd) => osd_prefix(osd)
+                .into_iter()
+                .chain([json!("add"), json!(name), json!(value)])
+                .collect(),
+            Self::Seek(seconds, mode, osd) => osd_prefix(osd)
+                .into_iter()
+                .chain([
+                    json!("seek"),
+                    json!(seconds),
+                    json!(match mode {
+                        SeekMode::Relative => "relative",
+                        SeekMode::Absolute => "absolute",
+                    }),
+                ])
+                .collect(),
+            Self::ObserveProperty(id, name) => json!(["observe_property", id, name]),
+            // Named arguments, the position of the options changed between mpv versions
+            Self::LoadFile(url, mode, start) => {
+                let mut command = json!({
+                    "name": "loadfile",
+                    "url": url,
+                    "flags": match mode {
+                        LoadMode::Replace => "replace",
+                        LoadMode::Append => "append",
+                    },
+                });
+                if let Some(start) = start.filter(|s| *s > 0.0) {
+                    command["options"] = json!({ "start": format!("{:.0}", start) });
                 }
-                let _ = reader_handle.abort();
-            }
-            Err(e) => {
-                log::error!("Failed to connect to MPV IPC socket {}: {}", socket_path, e);
+                command
             }
+            Self::PlaylistClear => json!(["playlist-clear"]),
+            Self::PlaylistNext => json!(["playlist-next"]),
+            Self::PlaylistPrev => json!(["playlist-prev"]),
         }
     }
 
-    #[cfg(windows)]
-    {
-        use tokio::net::windows::named_pipe::ClientOptions;
-        if let Ok(client) = ClientOptions::new().open(&socket_path) {
-            let (reader, mut writer) = split(client);
-            let mut reader = BufReader::new(reader);
-
-            // Spawning reader task
-            let reader_handle = tokio::spawn(async move {
-                let mut line = String::new();
-                while let Ok(n) = reader.read_line(&mut line).await {
-                    if n == 0 {
-                        break;
-                    }
-                    let _ = res_tx.send(line.clone());
-                    line.clear();
-                }
-            });
+    /// One line of the JSON IPC protocol.
+    pub fn to_json(&self, request_id: u64) -> String {
+        let mut line = json!({ "command": self.args(), "request_id": request_id }).to_string();
+        line.push('\n');
+        line
+    }
+}
 
-            // Writer loop
-            while let Some(cmd) = cmd_rx.recv().await {
-                let _ = writer.write_all(cmd.as_bytes()).await;
-                let _ = writer.flush().await;
-            }
-            let _ = reader_handle.abort();
+/// A change of an observed property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    TimePos(f64),
+    Duration(f64),
+    Pause(bool),
+    EofReached(bool),
+    Idle(bool),
+    TrackList(Vec<Track>),
+    SubVisibility(bool),
+    Volume(f64), // percent
+    Mute(bool),
+    Speed(f64),
+    LoopFile(bool),
+    PlaylistPos(i64), // -1 when nothing is playing
+}
+
+impl Event {
+    /// The event behind a `property-change` message. Properties without a value
+    /// (e.g. `time-pos` before playback starts) are skipped.
+    pub fn parse(val: &Value) -> Option<Self> {
+        if val["event"].as_str() != Some("property-change") {
+            return None;
         }
+        let data = &val["data"];
+        match val["name"].as_str()? {
+            "time-pos" => data.as_f64().map(Self::TimePos),
+            "duration" => data.as_f64().map(Self::Duration),
+            "pause" => data.as_bool().map(Self::Pause),
+            "eof-reached" => data.as_bool().map(Self::EofReached),
+            "idle-active" => data.as_bool().map(Self::Idle),
+            "track-list" => Some(Self::TrackList(parse_tracks(data))),
+            "sub-visibility" => data.as_bool().map(Self::SubVisibility),
+            "volume" => data.as_f64().map(Self::Volume),
+            "mute" => data.as_bool().map(Self::Mute),
+            "speed" => data.as_f64().map(Self::Speed),
+            // "inf" or a number of repeats when on, false or "no" when off
+            "loop-file" => Some(Self::LoopFile(match data {
+                Value::Bool(on) => *on,
+                Value::String(s) => s != "no",
+                Value::Number(n) => n.as_i64().is_some_and(|n| n > 0),
+                _ => false,
+            })),
+            "playlist-pos" => data.as_i64().map(Self::PlaylistPos),
+            _ => None,
+        }
+    }
+}
+
+/// Connection to the IPC socket of a running mpv.
+/// Commands sent before mpv is up are queued until the connection is made.
+#[derive(Clone)]
+pub struct MpvClient {
+    tx: UnboundedSender<Command>,
+}
+
+impl MpvClient {
+    /// Connects in the background, retrying until mpv has created the socket.
+    /// Changes of the observed properties arrive on the returned receiver.
+    pub fn spawn(socket_path: String) -> (Self, UnboundedReceiver<Event>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        tokio::spawn(run(socket_path, rx, event_tx));
+        (Self { tx }, event_rx)
     }
 
-    if !cfg!(windows) {
-        let _ = tokio::fs::remove_file(&socket_path).await;
+    /// Sends the command, mpv replies to it by request id and failures are logged.
+    pub fn send(&self, command: Command) {
+        let _ = self.tx.send(command);
     }
+}
+
+pub fn get_ipc_path() -> String {
+    if cfg!(windows) {
+        format!(r"\\.\pipe\rataplay-mpv-{}", std::process::id())
+    } else {
+        format!("/tmp/rataplay-mpv-{}.sock", std::process::id())
+    }
+}
+
+#[cfg(unix)]
+async fn connect(socket_path: &str) -> std::io::Result<tokio::net::UnixStream> {
+    tokio::net::UnixStream::connect(socket_path).await
+}
 
-    Ok(())
+#[cfg(windows)]
+async fn connect(
+    socket_path: &str,
+) -> std::io::Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    tokio::net::windows::named_pipe::ClientOptions::new().open(socket_path)
 }
 
-pub async fn spawn_ipc_writer(
+async fn run(
     socket_path: String,
-    mut cmd_rx: tokio::sync::mpsc::UnboundedReceiver<String>,
+    mut rx: UnboundedReceiver<Command>,
+    event_tx: UnboundedSender<Event>,
+) {
+    let started = Instant::now();
+    let mut delay = Duration::from_millis(50);
+    let stream = loop {
+        match connect(&socket_path).await {
+            Ok(stream) => break stream,
+            // Nobody is left to talk to mpv, e.g. playback was stopped while starting
+            Err(_) if rx.is_closed() => return,
+            Err(e) if started.elapsed() >= CONNECT_TIMEOUT => {
+                log::error!("Failed to connect to MPV IPC socket {}: {}", socket_path, e);
+                return;
+            }
+            Err(_) => {
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+        }
+    };
+    log::info!(
+        "Connected to MPV IPC socket {} after {:?}",
+        socket_path,
+        started.elapsed()
+    );
+
+    // The socket file is left alone, the next mpv may already be listening on the
+    // same path. `sys::process` removes it before starting mpv and on exit.
+    if let Err(e) = serve(stream, &mut rx, &event_tx).await {
+        log::warn!("MPV IPC connection ended: {}", e);
+    }
+}
+
+async fn serve<S: AsyncRead + AsyncWrite>(
+    stream: S,
+    rx: &mut UnboundedReceiver<Command>,
+    event_tx: &UnboundedSender<Event>,
 ) -> Result<()> {
-    // Wait for socket
-    tokio::time::sleep(std::time::Duration::from_millis(600)).await;
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+    // Commands waiting for their reply, by request id, to tell which one failed
+    let mut pending: HashMap<u64, Command> = HashMap::new();
+    let mut next_id: u64 = 1;
 
-    #[cfg(unix)]
-    {
-        if let Ok(mut stream) = tokio::net::UnixStream::connect(&socket_path).await {
-            while let Some(cmd) = cmd_rx.recv().await {
-                let _ = stream.write_all(cmd.as_bytes()).await;
-                let _ = stream.flush().await;
+    for (i, name) in OBSERVED_PROPERTIES.iter().enumerate() {
+        let command = Command::ObserveProperty(i as u64 + 1, name);
+        writer
+            .write_all(command.to_json(next_id).as_bytes())
+            .await?;
+        pending.insert(next_id, command);
+        next_id += 1;
+    }
+    writer.flush().await?;
+
+    loop {
+        tokio::select! {
+            command = rx.recv() => {
+                let command = match command {
+                    Some(command) => command,
+                    None => return Ok(()), // all clients are gone
+                };
+                writer.write_all(command.to_json(next_id).as_bytes()).await?;
+                writer.flush().await?;
+                pending.insert(next_id, command);
+                next_id += 1;
+            }
+            line = lines.next_line() => {
+                let val: Value = match line? {
+                    Some(line) => match serde_json::from_str(&line) {
+                        Ok(val) => val,
+                        Err(_) => continue,
+                    },
+                    None => return Ok(()), // mpv quit
+                };
+
+                if let Some(id) = val["request_id"].as_u64() {
+                    let command = pending.remove(&id);
+                    match val["error"].as_str() {
+                        Some("success") => {}
+                        error => log::debug!(
+                            "mpv command {:?} failed: {}",
+                            command,
+                            error.unwrap_or("unknown error")
+                        ),
+                    }
+                } else if let Some(event) = Event::parse(&val) {
+                    let _ = event_tx.send(event);
+                }
             }
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_json() {
+        let seek = Command::Seek(-5.0, SeekMode::Relative, Osd::MsgBar).to_json(7);
+        let val: Value = serde_json::from_str(&seek).unwrap();
+        assert_eq!(
+            val["command"],
+            json!(["osd-msg-bar", "seek", -5.0, "relative"])
+        );
+        assert_eq!(val["request_id"], 7);
+        assert!(seek.ends_with('\n'));
+
+        let load = Command::LoadFile("song.opus".to_string(), LoadMode::Replace, Some(42.0));
+        let val: Value = serde_json::from_str(&load.to_json(8)).unwrap();
+        assert_eq!(
+            val["command"],
+            json!({"name": "loadfile", "url": "song.opus", "flags": "replace", "options": {"start": "42"}})
+        );
 
-    #[cfg(windows)]
-    {
-        use tokio::net::windows::named_pipe::ClientOptions;
-        if let Ok(mut client) = ClientOptions::new().open(&socket_path) {
-            while let Some(cmd) = cmd_rx.recv().await {
-                let _ = client.write_all(cmd.as_bytes()).await;
-                let _ = client.flush().await;
+        let event = Event::parse(&json!({
+            "event": "property-change", "id": 1, "name": "time-pos", "data": 12.5
+        }));
+        assert_eq!(event, Some(Event::TimePos(12.5)));
+        let event = Event::parse(&json!({
+            "event": "property-change", "id": 1, "name": "time-pos", "data": null
+        }));
+        assert_eq!(event, None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_client_retries_and_reports_events() {
+        let path =
+            std::env::temp_dir().join(format!("rataplay-ipc-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        // The client starts before the socket exists, like it does while mpv is starting.
+        // Commands sent meanwhile are queued until it connects.
+        let (client, mut events) = MpvClient::spawn(path.to_string_lossy().to_string());
+        client.send(Command::SetProperty("volume", json!(80.0)));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut lines = BufReader::new(reader).lines();
+
+        let mut volume_request = None;
+        while volume_request.is_none() {
+            let val: Value =
+                serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+            if val["command"] == json!(["set_property", "volume", 80.0]) {
+                volume_request = val["request_id"].as_u64();
             }
         }
-    }
 
-    Ok(())
+        let messages = format!(
+            "{}\n{}\n",
+            json!({"request_id": volume_request, "error": "success", "data": null}),
+            json!({"event": "property-change", "id": 3, "name": "pause", "data": true})
+        );
+        writer.write_all(messages.as_bytes()).await.unwrap();
+
+        assert_eq!(events.recv().await, Some(Event::Pause(true)));
+        let _ = std::fs::remove_file(&path);
+    }
 }
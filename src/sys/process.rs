@@ -1,8 +1,24 @@
 use crate::model::settings::Settings;
+use crate::sys::mpv_ipc::get_ipc_path;
+use crate::sys::video::{TerminalOutput, VideoOutput};
 use anyhow::Result;
 use std::process::Stdio;
 use tokio::process::{Child, Command};
 
+/// Starts mpv, removing the IPC socket of an earlier one first. mpv is killed without
+/// a chance to clean up, and a stale file would stall the client until mpv replaces it.
+fn spawn_mpv(cmd: &mut Command) -> Result<Child> {
+    remove_ipc_socket();
+    Ok(cmd.spawn()?)
+}
+
+/// Removes the IPC socket file, also done on exit after the last mpv was stopped.
+pub fn remove_ipc_socket() {
+    if !cfg!(windows) {
+        let _ = std::fs::remove_file(get_ipc_path());
+    }
+}
+
 fn is_audio_path(path: &str) -> bool {
     let audio_exts = [
         ".mp3", ".m4a", ".flac", ".wav", ".ogg", ".opus", ".aac", ".wma",
@@ -11,16 +27,75 @@ fn is_audio_path(path: &str) -> bool {
     audio_exts.iter().any(|ext| path_lower.ends_with(ext))
 }
 
+/// Subtitle selection for mpv following the preferred languages. Local files pick up
+/// subtitles downloaded next to them.
+fn subtitle_args(settings: &Settings) -> Vec<String> {
+    let mut args = vec!["--sub-auto=fuzzy".to_string()];
+    let languages = &settings.subtitles.languages;
+    if !languages.is_empty() {
+        args.push(format!("--slang={}", languages.join(",")));
+        // The ytdl hook fetches every subtitle otherwise, regional variants are fine
+        let patterns: Vec<String> = languages.iter().map(|l| format!("{}.*", l)).collect();
+        args.push(format!(
+            "--ytdl-raw-options-append=sub-langs={}",
+            patterns.join(",")
+        ));
+        if settings.subtitles.auto_captions {
+            args.push("--ytdl-raw-options-append=write-auto-subs=".to_string());
+        }
+    }
+    args
+}
+
+/// mpv options rendering into `output` for the video pane, within the configured caps.
+fn terminal_args(output: &TerminalOutput, settings: &Settings) -> Vec<String> {
+    let mut args = vec![format!("--vo={}", output.output.name())];
+    let (columns, rows) = (output.columns, output.rows);
+    match output.output {
+        VideoOutput::Tct => {
+            args.push(format!("--vo-tct-width={}", columns));
+            args.push(format!("--vo-tct-height={}", rows));
+        }
+        VideoOutput::Kitty | VideoOutput::Sixel => {
+            let (cell_width, cell_height) = output.cell_size;
+            let mut width = columns as u32 * cell_width as u32;
+            let mut height = rows as u32 * cell_height as u32;
+            let max_height = settings.terminal_video.max_height;
+            if max_height > 0 && height > max_height {
+                width = width * max_height / height;
+                height = max_height;
+            }
+            let vo = output.output.name();
+            args.push(format!("--vo-{}-cols={}", vo, columns));
+            args.push(format!("--vo-{}-rows={}", vo, rows));
+            args.push(format!("--vo-{}-width={}", vo, width));
+            args.push(format!("--vo-{}-height={}", vo, height));
+        }
+    }
+    if settings.terminal_video.max_fps > 0 {
+        args.push(format!("--vf-add=fps={}", settings.terminal_video.max_fps));
+    }
+    args
+}
+
+/// Starts mpv in its own window, or rendering into the terminal with `terminal`. That
+/// output is piped, so the caller shows it with `sys::video` instead of mpv writing
+/// over the TUI.
 pub fn play_video(
     url: &str,
     format: Option<&str>,
-    in_terminal: bool,
+    terminal: Option<&TerminalOutput>,
     user_agent: Option<&str>,
+    start: Option<f64>,
     settings: &Settings,
 ) -> Result<Child> {
     let mut cmd = Command::new(settings.mpv_cmd());
     cmd.kill_on_drop(true);
 
+    if let Some(pos) = start.filter(|p| *p > 0.0) {
+        cmd.arg(format!("--start={:.0}", pos));
+    }
+
     if let Some(fmt) = format {
         cmd.arg(format!("--ytdl-format={}", fmt));
     }
@@ -32,17 +107,12 @@ pub fn play_video(
     }
 
     // Common IPC setup
-    // Common IPC setup
-    let socket_path = if cfg!(windows) {
-        format!(r"\\.\pipe\rataplay-mpv-{}", std::process::id())
-    } else {
-        format!("/tmp/rataplay-mpv-{}.sock", std::process::id())
-    };
-    cmd.arg(format!("--input-ipc-server={}", socket_path));
+    cmd.arg(format!("--input-ipc-server={}", get_ipc_path()));
 
-    if in_terminal {
-        cmd.arg("--vo=tct");
+    if let Some(output) = terminal {
+        cmd.args(terminal_args(output, settings));
         cmd.arg("--really-quiet");
+        cmd.arg("--no-input-terminal");
 
         if is_audio_path(url) {
             // For audio in terminal, show a simple visualizer or just the OSC
@@ -54,9 +124,9 @@ pub fn play_video(
         cmd.arg("--cache-secs=2");
         cmd.arg("--demuxer-max-bytes=10M");
         cmd.arg("--demuxer-readahead-secs=2");
-        cmd.stdin(Stdio::inherit())
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit());
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
     } else {
         // Detached / background
         cmd.arg("--force-window=yes");
@@ -94,25 +164,22 @@ pub fn play_video(
         crate::model::settings::CookieMode::Off | crate::model::settings::CookieMode::Unsetted => {}
     }
 
+    // After the cookie options, which replace the whole raw options list
+    cmd.args(subtitle_args(settings));
+
     cmd.arg(url);
-    let child = cmd.spawn()?;
-    Ok(child)
+    spawn_mpv(&mut cmd)
 }
 
-pub fn play_audio(url: &str, settings: &Settings) -> Result<Child> {
+/// mpv for audio only, without the file to play.
+fn audio_command(settings: &Settings) -> Command {
     let mut cmd = Command::new(settings.mpv_cmd());
     cmd.arg("--no-video");
     cmd.arg("--ytdl-format=bestaudio/best");
     cmd.kill_on_drop(true);
 
     // Common IPC setup
-    let socket_path = if cfg!(windows) {
-        format!(r"\\.\pipe\rataplay-mpv-{}", std::process::id())
-    } else {
-        format!("/tmp/rataplay-mpv-{}.sock", std::process::id())
-    };
-    cmd.arg(format!("--input-ipc-server={}", socket_path));
-    // cmd.arg("--idle=yes");
+    cmd.arg(format!("--input-ipc-server={}", get_ipc_path()));
 
     // Apply cookies to mpv (passed to ytdl-hook)
     match &settings.cookie_mode {
@@ -136,12 +203,29 @@ pub fn play_audio(url: &str, settings: &Settings) -> Result<Child> {
         crate::model::settings::CookieMode::Off | crate::model::settings::CookieMode::Unsetted => {}
     }
 
-    cmd.arg(url);
-
     cmd.stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null());
+    cmd
+}
+
+pub fn play_audio(url: &str, start: Option<f64>, settings: &Settings) -> Result<Child> {
+    let mut cmd = audio_command(settings);
+    if let Some(pos) = start.filter(|p| *p > 0.0) {
+        cmd.arg(format!("--start={:.0}", pos));
+    }
+    cmd.arg(url);
+
+    spawn_mpv(&mut cmd)
+}
+
+/// Starts the long-lived audio player. It waits idle for files loaded over IPC, plays
+/// the playlist without gaps and fetches the next entry ahead of time.
+pub fn start_audio_player(settings: &Settings) -> Result<Child> {
+    let mut cmd = audio_command(settings);
+    cmd.arg("--idle=yes");
+    cmd.arg("--gapless-audio=yes");
+    cmd.arg("--prefetch-playlist=yes");
 
-    let child = cmd.spawn()?;
-    Ok(child)
+    spawn_mpv(&mut cmd)
 }
@@ -0,0 +1,142 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::model::settings::Settings;
+use crate::sys::cache;
+
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+fn get_client() -> &'static reqwest::Client {
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .user_agent(concat!("rataplay/", env!("CARGO_PKG_VERSION")))
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .expect("Failed to build reqwest client")
+    })
+}
+
+/// A part of a video to skip, e.g. a sponsor read.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Segment {
+    pub category: String,
+    pub start: f64, // seconds
+    pub end: f64,
+}
+
+impl Segment {
+    /// Whether `time` falls inside the segment. The last half second is left out,
+    /// so a seek to the end does not land inside it again.
+    pub fn contains(&self, time: f64) -> bool {
+        time >= self.start && time < self.end - 0.5
+    }
+
+    pub fn label(&self) -> &str {
+        match self.category.as_str() {
+            "sponsor" => "sponsor",
+            "intro" => "intro",
+            "outro" => "outro",
+            "selfpromo" => "self-promotion",
+            "interaction" => "interaction reminder",
+            "preview" => "preview",
+            "music_offtopic" => "non-music section",
+            "filler" => "filler",
+            other => other,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiSegment {
+    category: String,
+    segment: [f64; 2],
+    #[serde(rename = "actionType", default)]
+    action_type: Option<String>,
+}
+
+/// Segments from a `/api/skipSegments` reply, sorted by start time.
+/// Only skippable ones are kept, full-video labels and mutes are not.
+pub fn parse_segments(body: &str) -> Result<Vec<Segment>> {
+    let mut segments: Vec<Segment> = serde_json::from_str::<Vec<ApiSegment>>(body)?
+        .into_iter()
+        .filter(|s| s.action_type.as_deref().is_none_or(|a| a == "skip"))
+        .filter(|s| s.segment[1] > s.segment[0])
+        .map(|s| Segment {
+            category: s.category,
+            start: s.segment[0],
+            end: s.segment[1],
+        })
+        .collect();
+    segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+    Ok(segments)
+}
+
+/// Segments of a video in the configured categories, from the cache if fresh.
+pub async fn fetch_segments(video_id: &str, settings: &Settings) -> Result<Vec<Segment>> {
+    let cached = cache::get_segments(settings, video_id);
+    if let Some(cached) = &cached
+        && cached.fresh
+    {
+        return Ok(cached.value.clone());
+    }
+
+    match request_segments(video_id, settings).await {
+        Ok(segments) => {
+            cache::put_segments(settings, video_id, &segments);
+            Ok(segments)
+        }
+        Err(e) => match cached {
+            // Better stale than nothing while the API is unreachable
+            Some(cached) => {
+                log::warn!("Using stale SponsorBlock segments for {}: {}", video_id, e);
+                Ok(cached.value)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+async fn request_segments(video_id: &str, settings: &Settings) -> Result<Vec<Segment>> {
+    let config = &settings.sponsorblock;
+    let url = reqwest::Url::parse_with_params(
+        &format!("{}/api/skipSegments", config.api_url.trim_end_matches('/')),
+        &[
+            ("videoID", video_id.to_string()),
+            ("categories", serde_json::to_string(&config.categories)?),
+        ],
+    )?;
+
+    log::info!("Fetching SponsorBlock segments: {}", url);
+    let resp = get_client().get(url).send().await?;
+    // Videos without any submissions are a 404
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(Vec::new());
+    }
+    let body = resp.error_for_status()?.text().await?;
+    parse_segments(&body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_segments() {
+        let body = r#"[
+            {"category": "intro", "actionType": "skip", "segment": [0.0, 12.5], "UUID": "a"},
+            {"category": "sponsor", "actionType": "mute", "segment": [40.0, 50.0], "UUID": "b"},
+            {"category": "sponsor", "segment": [30.0, 45.0], "UUID": "c"},
+            {"category": "selfpromo", "actionType": "full", "segment": [0.0, 0.0], "UUID": "d"}
+        ]"#;
+
+        let segments = parse_segments(body).unwrap();
+        let found: Vec<(&str, f64)> = segments
+            .iter()
+            .map(|s| (s.category.as_str(), s.start))
+            .collect();
+        assert_eq!(found, [("intro", 0.0), ("sponsor", 30.0)]);
+        assert!(segments[1].contains(30.0));
+        assert!(!segments[1].contains(44.8));
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use serde::{Serialize, de::DeserializeOwned};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::sys::config::Config;
+
+fn state_file(name: &str) -> Result<PathBuf> {
+    Ok(Config::get_state_dir()?.join(name))
+}
+
+/// Loads a JSON state file (e.g. `play_queue.json`) from the state directory.
+/// Missing or unreadable files yield the default value.
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    let path = match state_file(name) {
+        Ok(p) => p,
+        Err(_) => return T::default(),
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(content) => match serde_json::from_str(&content) {
+            Ok(value) => value,
+            Err(e) => {
+                log::error!("Failed to parse state file {:?}: {}", path, e);
+                T::default()
+            }
+        },
+        Err(_) => T::default(),
+    }
+}
+
+pub fn save<T: Serialize>(name: &str, value: &T) -> Result<()> {
+    let path = state_file(name)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Write to a temp file first so a crash never leaves a truncated file behind
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_string_pretty(value)?)?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
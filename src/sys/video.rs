@@ -0,0 +1,522 @@
+use ratatui_image::picker::ProtocolType;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use tokio::io::AsyncReadExt;
+use tokio::process::ChildStdout;
+
+use crate::sys::config::TerminalVideoOutput;
+
+pub type Rgb = [u8; 3];
+
+// Kitty image id of the video, so each frame replaces the last and thumbnails stay
+const KITTY_IMAGE_ID: u32 = 0x5241_5441;
+
+/// mpv video outputs for playback inside the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoOutput {
+    Kitty,
+    Sixel,
+    Tct,
+}
+
+impl VideoOutput {
+    /// The configured output, `auto` follows the graphics protocol detected for thumbnails.
+    pub fn select(configured: TerminalVideoOutput, protocol: ProtocolType) -> Self {
+        match configured {
+            TerminalVideoOutput::Kitty => Self::Kitty,
+            TerminalVideoOutput::Sixel => Self::Sixel,
+            TerminalVideoOutput::Tct => Self::Tct,
+            TerminalVideoOutput::Auto => match protocol {
+                ProtocolType::Kitty => Self::Kitty,
+                ProtocolType::Sixel => Self::Sixel,
+                ProtocolType::Halfblocks | ProtocolType::Iterm2 => Self::Tct,
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Kitty => "kitty",
+            Self::Sixel => "sixel",
+            Self::Tct => "tct",
+        }
+    }
+}
+
+/// Where mpv renders during terminal playback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalOutput {
+    pub output: VideoOutput,
+    pub columns: u16,
+    pub rows: u16,
+    pub cell_size: (u16, u16), // pixels of one cell, for the graphics outputs
+}
+
+/// Output of a running terminal playback, filled by the reader task.
+#[derive(Debug, Clone)]
+pub enum VideoFeed {
+    Pixels(Arc<Mutex<VideoFrame>>),      // tct, drawn by the video pane
+    Graphics(Arc<Mutex<GraphicsFrame>>), // kitty or sixel, written over the video pane
+}
+
+impl VideoFeed {
+    pub fn new(output: &TerminalOutput) -> Self {
+        match output.output {
+            VideoOutput::Tct => Self::Pixels(Arc::new(Mutex::new(VideoFrame::new(
+                output.columns,
+                output.rows,
+            )))),
+            VideoOutput::Kitty | VideoOutput::Sixel => {
+                Self::Graphics(Arc::new(Mutex::new(GraphicsFrame::default())))
+            }
+        }
+    }
+}
+
+/// The latest video picture of terminal playback, two pixels per cell vertically.
+/// Written by the reader task while mpv plays, drawn by the video pane.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoFrame {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Rgb>,
+    pub updated: bool, // anything drawn yet
+}
+
+impl VideoFrame {
+    /// A black frame for mpv's text output of `columns` x `rows` cells.
+    pub fn new(columns: u16, rows: u16) -> Self {
+        let width = columns as usize;
+        let height = rows as usize * 2;
+        Self {
+            width,
+            height,
+            pixels: vec![[0, 0, 0]; width * height],
+            updated: false,
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        self.pixels
+            .get(y * self.width + x)
+            .copied()
+            .unwrap_or([0, 0, 0])
+    }
+
+    fn set_cell(&mut self, column: usize, row: usize, top: Rgb, bottom: Rgb) {
+        let y = row * 2;
+        if column >= self.width || y + 1 >= self.height {
+            return;
+        }
+        self.pixels[y * self.width + column] = top;
+        self.pixels[(y + 1) * self.width + column] = bottom;
+        self.updated = true;
+    }
+}
+
+#[derive(Debug, Default)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    Csi,
+}
+
+/// Reads the ANSI output of mpv's `--vo=tct` back into pixels. tct draws half block
+/// characters with true color (or 256 color) foreground and background, after moving
+/// the cursor to the start of each row. Everything else is skipped.
+#[derive(Debug, Default)]
+pub struct TctParser {
+    state: State,
+    params: String,
+    column: usize,
+    row: usize,
+    fg: Rgb,
+    bg: Rgb,
+    utf8: Vec<u8>,
+}
+
+impl TctParser {
+    pub fn feed(&mut self, bytes: &[u8], frame: &mut VideoFrame) {
+        for &byte in bytes {
+            match self.state {
+                State::Ground => match byte {
+                    0x1b => {
+                        self.utf8.clear();
+                        self.state = State::Escape;
+                    }
+                    b'\n' => {
+                        self.row += 1;
+                        self.column = 0;
+                    }
+                    b'\r' => self.column = 0,
+                    0x20..=0x7e => self.put(byte as char, frame),
+                    0x80.. => {
+                        self.utf8.push(byte);
+                        if let Ok(s) = std::str::from_utf8(&self.utf8) {
+                            let c = s.chars().next().unwrap_or(' ');
+                            self.utf8.clear();
+                            self.put(c, frame);
+                        } else if self.utf8.len() >= 4 {
+                            self.utf8.clear();
+                        }
+                    }
+                    _ => {}
+                },
+                State::Escape => {
+                    self.state = if byte == b'[' {
+                        self.params.clear();
+                        State::Csi
+                    } else {
+                        State::Ground
+                    };
+                }
+                State::Csi => {
+                    if (0x40..=0x7e).contains(&byte) {
+                        self.dispatch(byte);
+                        self.state = State::Ground;
+                    } else {
+                        self.params.push(byte as char);
+                    }
+                }
+            }
+        }
+    }
+
+    fn dispatch(&mut self, command: u8) {
+        // Private sequences like hiding the cursor don't matter here
+        if self.params.starts_with('?') {
+            return;
+        }
+        let params: Vec<u32> = self
+            .params
+            .split(';')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        match command {
+            b'H' | b'f' => {
+                self.row = params.first().copied().unwrap_or(1).max(1) as usize - 1;
+                self.column = params.get(1).copied().unwrap_or(1).max(1) as usize - 1;
+            }
+            b'm' => self.set_colors(&params),
+            _ => {}
+        }
+    }
+
+    fn set_colors(&mut self, params: &[u32]) {
+        let mut i = 0;
+        while i < params.len() {
+            match params[i] {
+                0 => {
+                    self.fg = [0, 0, 0];
+                    self.bg = [0, 0, 0];
+                }
+                code @ (38 | 48) => {
+                    let color = match params.get(i + 1) {
+                        Some(2) if i + 4 < params.len() => {
+                            let rgb = [params[i + 2], params[i + 3], params[i + 4]];
+                            i += 4;
+                            Some(rgb.map(|c| c.min(255) as u8))
+                        }
+                        Some(5) if i + 2 < params.len() => {
+                            i += 2;
+                            Some(xterm_color(params[i]))
+                        }
+                        _ => None,
+                    };
+                    if let Some(color) = color {
+                        if code == 38 {
+                            self.fg = color;
+                        } else {
+                            self.bg = color;
+                        }
+                    }
+                }
+                39 => self.fg = [0, 0, 0],
+                49 => self.bg = [0, 0, 0],
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    fn put(&mut self, c: char, frame: &mut VideoFrame) {
+        let (top, bottom) = match c {
+            '▄' => (self.bg, self.fg),
+            '▀' => (self.fg, self.bg),
+            '█' => (self.fg, self.fg),
+            _ => (self.bg, self.bg), // spaces of the "plain" algorithm
+        };
+        frame.set_cell(self.column, self.row, top, bottom);
+        self.column += 1;
+    }
+}
+
+/// RGB of an xterm 256 color palette index.
+fn xterm_color(index: u32) -> Rgb {
+    const BASIC: [Rgb; 16] = [
+        [0, 0, 0],
+        [128, 0, 0],
+        [0, 128, 0],
+        [128, 128, 0],
+        [0, 0, 128],
+        [128, 0, 128],
+        [0, 128, 128],
+        [192, 192, 192],
+        [128, 128, 128],
+        [255, 0, 0],
+        [0, 255, 0],
+        [255, 255, 0],
+        [0, 0, 255],
+        [255, 0, 255],
+        [0, 255, 255],
+        [255, 255, 255],
+    ];
+    match index {
+        0..=15 => BASIC[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            let level = |v: u32| if v == 0 { 0 } else { (55 + v * 40) as u8 };
+            [level(i / 36), level((i / 6) % 6), level(i % 6)]
+        }
+        _ => {
+            let gray = (8 + (index.min(255) - 232) * 10) as u8;
+            [gray, gray, gray]
+        }
+    }
+}
+
+/// The newest kitty or sixel picture from mpv, as escape sequences for the terminal.
+#[derive(Debug, Default)]
+pub struct GraphicsFrame {
+    pub pending: Option<Vec<u8>>, // not written yet, older ones are dropped
+    pub last: Vec<u8>,            // written most recently, for redrawing while paused
+    pub started: bool,
+}
+
+#[derive(Debug, Default)]
+enum GraphicsState {
+    #[default]
+    Ground,
+    Escape,
+    Sequence,       // inside an APC (kitty) or DCS (sixel)
+    SequenceEscape, // possibly at the closing ESC \
+}
+
+/// Picks the kitty and sixel sequences out of mpv's output. Cursor movement, screen
+/// clearing and the like are dropped, the picture is placed over the video pane instead.
+#[derive(Debug, Default)]
+pub struct GraphicsParser {
+    state: GraphicsState,
+    sequence: Vec<u8>,
+    frame: Vec<u8>,
+}
+
+impl GraphicsParser {
+    pub fn feed(&mut self, bytes: &[u8], frame: &mut GraphicsFrame) {
+        for &byte in bytes {
+            match self.state {
+                GraphicsState::Ground => {
+                    if byte == 0x1b {
+                        self.state = GraphicsState::Escape;
+                    }
+                }
+                GraphicsState::Escape => {
+                    self.state = if byte == b'_' || byte == b'P' {
+                        self.sequence.clear();
+                        self.sequence.extend_from_slice(&[0x1b, byte]);
+                        GraphicsState::Sequence
+                    } else if byte == 0x1b {
+                        GraphicsState::Escape
+                    } else {
+                        GraphicsState::Ground
+                    };
+                }
+                GraphicsState::Sequence => {
+                    if byte == 0x1b {
+                        self.state = GraphicsState::SequenceEscape;
+                    } else {
+                        self.sequence.push(byte);
+                    }
+                }
+                GraphicsState::SequenceEscape => {
+                    if byte == b'\\' {
+                        self.sequence.extend_from_slice(b"\x1b\\");
+                        self.finish_sequence(frame);
+                        self.state = GraphicsState::Ground;
+                    } else {
+                        self.sequence.extend_from_slice(&[0x1b, byte]);
+                        self.state = GraphicsState::Sequence;
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish_sequence(&mut self, frame: &mut GraphicsFrame) {
+        let complete = if self.sequence.starts_with(b"\x1b_G") {
+            match kitty_command(&self.sequence) {
+                Some((command, more)) => {
+                    self.frame.extend_from_slice(&command);
+                    !more
+                }
+                None => false,
+            }
+        } else if self.sequence.starts_with(b"\x1bP") {
+            self.frame.extend_from_slice(&self.sequence);
+            true
+        } else {
+            false
+        };
+        if complete {
+            frame.pending = Some(std::mem::take(&mut self.frame));
+            frame.started = true;
+        }
+    }
+}
+
+/// A kitty graphics command tagged with the video's image id, and whether more chunks
+/// follow. mpv's deletes are dropped, they would remove the thumbnails as well.
+fn kitty_command(sequence: &[u8]) -> Option<(Vec<u8>, bool)> {
+    let body = &sequence[3..];
+    let split = body
+        .iter()
+        .position(|&b| b == b';' || b == 0x1b)
+        .unwrap_or(body.len());
+    let control = String::from_utf8_lossy(&body[..split]);
+    let keys: Vec<&str> = control.split(',').collect();
+    if keys.iter().any(|k| k.starts_with("a=d")) {
+        return None;
+    }
+    let more = keys.contains(&"m=1");
+
+    let mut command = b"\x1b_G".to_vec();
+    // Only the first chunk carries the action, the rest just continue it
+    if keys.iter().any(|k| k.starts_with("a=")) {
+        let extra: Vec<String> = [
+            format!("i={}", KITTY_IMAGE_ID),
+            "p=1".to_string(),
+            "q=2".to_string(),
+        ]
+        .into_iter()
+        .filter(|extra| !keys.iter().any(|k| k.starts_with(&extra[..2])))
+        .collect();
+        for key in extra {
+            command.extend_from_slice(key.as_bytes());
+            command.push(b',');
+        }
+    }
+    command.extend_from_slice(body);
+    Some((command, more))
+}
+
+/// Writes the newest graphics frame, if any, with its top left corner at the cell
+/// `x`, `y` (zero based). `redraw` repeats the last frame when there is no new one,
+/// after it was cleared.
+pub fn write_graphics(
+    out: &mut impl Write,
+    frame: &Mutex<GraphicsFrame>,
+    x: u16,
+    y: u16,
+    redraw: bool,
+) -> io::Result<()> {
+    let pending = match frame.lock() {
+        Ok(mut frame) => match frame.pending.take() {
+            Some(data) => {
+                frame.last = data.clone();
+                Some(data)
+            }
+            None if redraw && !frame.last.is_empty() => Some(frame.last.clone()),
+            None => None,
+        },
+        Err(_) => None,
+    };
+    if let Some(data) = pending {
+        write!(out, "\x1b[{};{}H", y + 1, x + 1)?;
+        out.write_all(&data)?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// Removes the kitty picture of the video, e.g. when playback stops or a popup covers it.
+/// Other terminals ignore the command, sixel pictures are simply drawn over.
+pub fn clear_graphics(out: &mut impl Write) -> io::Result<()> {
+    write!(out, "\x1b_Ga=d,d=I,i={},q=2\x1b\\", KITTY_IMAGE_ID)?;
+    out.flush()
+}
+
+/// Feeds mpv's output into `feed` until mpv closes it.
+pub fn spawn_reader(mut stdout: ChildStdout, feed: VideoFeed) {
+    tokio::spawn(async move {
+        let mut tct = TctParser::default();
+        let mut graphics = GraphicsParser::default();
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let n = match stdout.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            match &feed {
+                VideoFeed::Pixels(frame) => {
+                    if let Ok(mut frame) = frame.lock() {
+                        tct.feed(&buf[..n], &mut frame);
+                    }
+                }
+                VideoFeed::Graphics(frame) => {
+                    if let Ok(mut frame) = frame.lock() {
+                        graphics.feed(&buf[..n], &mut frame);
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tct_parser() {
+        let mut frame = VideoFrame::new(3, 2);
+        let mut parser = TctParser::default();
+        // Lower half blocks: the background is the top pixel
+        let output = "\x1b[?25l\x1b[1;2f\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▄\x1b[0m\
+                      \x1b[2;1H\x1b[38;5;231;48;5;16m▀";
+        // Split inside an escape sequence and a UTF-8 character, like reads from a pipe do
+        let (a, b) = output.as_bytes().split_at(20);
+        parser.feed(a, &mut frame);
+        parser.feed(b, &mut frame);
+
+        assert!(frame.updated);
+        assert_eq!(frame.pixel(1, 0), [0, 0, 255]);
+        assert_eq!(frame.pixel(1, 1), [255, 0, 0]);
+        assert_eq!(frame.pixel(0, 2), [255, 255, 255]);
+        assert_eq!(frame.pixel(0, 3), [0, 0, 0]);
+        assert_eq!(frame.pixel(2, 0), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_graphics_parser() {
+        let mut frame = GraphicsFrame::default();
+        let mut parser = GraphicsParser::default();
+        let output = "\x1b[?1049h\x1b_Ga=d\x1b\\\x1b[2;3H\x1b_Ga=T,f=24,s=2,v=1,m=1;AAAA\x1b\\\x1b_Gm=0;AA==\x1b\\";
+        let (a, b) = output.as_bytes().split_at(40);
+        parser.feed(a, &mut frame);
+        assert!(!frame.started);
+        parser.feed(b, &mut frame);
+
+        let expected = format!(
+            "\x1b_Gi={},p=1,q=2,a=T,f=24,s=2,v=1,m=1;AAAA\x1b\\\x1b_Gm=0;AA==\x1b\\",
+            KITTY_IMAGE_ID
+        );
+        assert_eq!(frame.pending.as_deref(), Some(expected.as_bytes()));
+
+        parser.feed(b"\x1bPq#0;2;0;0;0#0~-\x1b\\", &mut frame);
+        assert_eq!(
+            frame.pending.as_deref(),
+            Some(&b"\x1bPq#0;2;0;0;0#0~-\x1b\\"[..])
+        );
+    }
+}
@@ -1,5 +1,7 @@
+use crate::model::download::PlaylistItem;
+use crate::model::queue::PlaylistPage;
 use crate::model::settings::{CookieMode, Settings};
-use crate::model::{Video, VideoFormat};
+use crate::model::{Chapter, MediaFormats, SubtitleTrack, Video, VideoFormat};
 use crate::sys::cookies;
 use anyhow::{Context, Result};
 use serde_json::Value;
@@ -47,6 +49,8 @@ pub fn build_base_command(settings: &Settings) -> Command {
 pub enum SearchResult {
     Video(Video),
     Progress(f32),
+    // Fresh results for a page that was first served from the cache
+    Refresh(Vec<Video>),
 }
 
 pub async fn search_videos_flat(
@@ -148,238 +152,426 @@ pub async fn search_videos_flat(
             continue;
         }
 
-        if let Ok(val) = serde_json::from_str::<Value>(&line) {
-            // Filter out inaccessible videos
-            let title = val["title"].as_str().unwrap_or_default().to_string();
-            let id = val["id"].as_str().unwrap_or_default().to_string();
+        if let Some(video) = serde_json::from_str::<Value>(&line)
+            .ok()
+            .and_then(|val| parse_flat_entry(&val, is_url, show_live, show_playlists))
+        {
+            count += 1;
+            let progress = (count as f32 / expected as f32).min(1.0);
+            let _ = tx.send(Ok(SearchResult::Video(video)));
+            // We removed progress bar from UI plan, but keeping the event for now as App handles it
+            let _ = tx.send(Ok(SearchResult::Progress(progress)));
+        }
+    }
 
-            // Skip if it's a known placeholder for inaccessible videos
-            if title == "[Private video]"
-                || title == "[Deleted video]"
-                || title.is_empty()
-                || id.is_empty()
-            {
-                continue;
-            }
+    let output = child.wait_with_output().await?; // Await child and capture output
 
-            // Also, skip if there's no usable URL
-            let mut final_url = val["webpage_url"]
-                .as_str()
-                .or_else(|| val["url"].as_str())
-                .unwrap_or("")
-                .to_string();
+    if !output.status.success() {
+        let err_msg = String::from_utf8_lossy(&output.stderr);
+        log::error!("yt-dlp search error: {}", err_msg);
+        let _ = tx.send(Err(format!("yt-dlp error: {}", err_msg)));
+        anyhow::bail!("yt-dlp exited with error: {}", err_msg);
+    }
 
-            if final_url.is_empty() && !id.is_empty() {
-                if let Some(original_url) = val["original_url"].as_str() {
-                    final_url = original_url.to_string();
-                }
-            }
+    let _ = tx.send(Ok(SearchResult::Progress(1.0)));
+    Ok(())
+}
 
-            if final_url.is_empty() {
-                continue;
-            }
+/// Turns one `--flat-playlist` JSON line into a `Video`, or `None` if it should be skipped.
+fn parse_flat_entry(
+    val: &Value,
+    is_url: bool,
+    show_live: bool,
+    show_playlists: bool,
+) -> Option<Video> {
+    // Filter out inaccessible videos
+    let title = val["title"].as_str().unwrap_or_default().to_string();
+    let id = val["id"].as_str().unwrap_or_default().to_string();
 
-            let title = val["title"].as_str().unwrap_or_default().to_string();
-            let channel = val["uploader"]
-                .as_str()
-                .or_else(|| val["uploader_id"].as_str())
-                .or_else(|| val["webpage_url_domain"].as_str())
-                .unwrap_or("Unknown")
-                .to_string();
-            let channel_id = val["channel_id"]
-                .as_str()
-                .or_else(|| val["uploader_id"].as_str())
-                .unwrap_or_default()
-                .to_string();
+    // Skip if it's a known placeholder for inaccessible videos
+    if title == "[Private video]" || title == "[Deleted video]" || title.is_empty() || id.is_empty()
+    {
+        return None;
+    }
 
-            let item_type_str = val["_type"].as_str().unwrap_or("video");
+    // Also, skip if there's no usable URL
+    let mut final_url = val["webpage_url"]
+        .as_str()
+        .or_else(|| val["url"].as_str())
+        .unwrap_or("")
+        .to_string();
 
-            // For entries in a playlist, final_url might contain 'list=' but we want to check if it's primarily a video
-            // Check if this is a real YouTube playlist ID (not just a search query)
-            let playlist_id_str = val["playlist_id"].as_str().unwrap_or("");
-            let is_real_playlist_id = playlist_id_str.starts_with("PL")
-                || playlist_id_str.starts_with("UU")
-                || playlist_id_str.starts_with("FL")
-                || playlist_id_str.starts_with("RD")
-                || playlist_id_str.starts_with("OL");
+    if final_url.is_empty() && !id.is_empty() {
+        if let Some(original_url) = val["original_url"].as_str() {
+            final_url = original_url.to_string();
+        }
+    }
 
-            // Determine video_type before thumbnail extraction
-            let (video_type, playlist_count, duration_string, view_count, concurrent_view_count) =
-                if item_type_str == "playlist" || item_type_str == "multi_video" {
-                    let count = val["playlist_count"]
-                        .as_u64()
-                        .or_else(|| val["n_entries"].as_u64());
-                    let duration_str = format!("{} videos", count.unwrap_or(0));
-                    (
-                        crate::model::VideoType::Playlist,
-                        count,
-                        duration_str,
-                        None,
-                        None,
-                    )
-                } else if item_type_str == "channel" {
-                    (
-                        crate::model::VideoType::Channel,
-                        None,
-                        "N/A".to_string(),
-                        None,
-                        None,
-                    )
-                } else if (item_type_str == "url" || item_type_str == "url_transparent")
-                    && val["ie_key"].as_str() == Some("YoutubeTab")
-                    && (final_url.contains("list=") || final_url.contains("/playlist/"))
-                {
-                    // This handles playlist references in search results
-                    let count = val["playlist_count"]
-                        .as_u64()
-                        .or_else(|| val["n_entries"].as_u64());
-                    let duration_str = format!("{} videos", count.unwrap_or(0));
-                    (
-                        crate::model::VideoType::Playlist,
-                        count,
-                        duration_str,
-                        None,
-                        None,
-                    )
-                } else {
-                    // Default to Video
-                    let duration = val["duration"].as_f64().unwrap_or(0.0);
-                    let view_count = val["view_count"].as_u64();
-                    let concurrent_view_count = val["concurrent_view_count"].as_u64();
-                    (
-                        crate::model::VideoType::Video,
-                        None,
-                        format_duration(duration),
-                        view_count,
-                        concurrent_view_count,
-                    )
-                };
+    if final_url.is_empty() {
+        return None;
+    }
 
-            // Extract live_status before thumbnail extraction for easier access
-            let live_status = val["live_status"].as_str().map(|s| s.to_string());
-            let is_live_bool = val["is_live"].as_bool().unwrap_or(false);
+    let title = val["title"].as_str().unwrap_or_default().to_string();
+    let channel = val["uploader"]
+        .as_str()
+        .or_else(|| val["uploader_id"].as_str())
+        .or_else(|| val["webpage_url_domain"].as_str())
+        .unwrap_or("Unknown")
+        .to_string();
+    let channel_id = val["channel_id"]
+        .as_str()
+        .or_else(|| val["uploader_id"].as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    let item_type_str = val["_type"].as_str().unwrap_or("video");
+
+    // For entries in a playlist, final_url might contain 'list=' but we want to check if it's primarily a video
+    // Check if this is a real YouTube playlist ID (not just a search query)
+    let playlist_id_str = val["playlist_id"].as_str().unwrap_or("");
+    let is_real_playlist_id = playlist_id_str.starts_with("PL")
+        || playlist_id_str.starts_with("UU")
+        || playlist_id_str.starts_with("FL")
+        || playlist_id_str.starts_with("RD")
+        || playlist_id_str.starts_with("OL");
+
+    // Determine video_type before thumbnail extraction
+    let (video_type, playlist_count, duration_string, view_count, concurrent_view_count) =
+        if item_type_str == "playlist" || item_type_str == "multi_video" {
+            let count = val["playlist_count"]
+                .as_u64()
+                .or_else(|| val["n_entries"].as_u64());
+            let duration_str = format!("{} videos", count.unwrap_or(0));
+            (
+                crate::model::VideoType::Playlist,
+                count,
+                duration_str,
+                None,
+                None,
+            )
+        } else if item_type_str == "channel" {
+            (
+                crate::model::VideoType::Channel,
+                None,
+                "N/A".to_string(),
+                None,
+                None,
+            )
+        } else if (item_type_str == "url" || item_type_str == "url_transparent")
+            && val["ie_key"].as_str() == Some("YoutubeTab")
+            && (final_url.contains("list=") || final_url.contains("/playlist/"))
+        {
+            // This handles playlist references in search results
+            let count = val["playlist_count"]
+                .as_u64()
+                .or_else(|| val["n_entries"].as_u64());
+            let duration_str = format!("{} videos", count.unwrap_or(0));
+            (
+                crate::model::VideoType::Playlist,
+                count,
+                duration_str,
+                None,
+                None,
+            )
+        } else {
+            // Default to Video
+            let duration = val["duration"].as_f64().unwrap_or(0.0);
+            let view_count = val["view_count"].as_u64();
+            let concurrent_view_count = val["concurrent_view_count"].as_u64();
+            (
+                crate::model::VideoType::Video,
+                None,
+                format_duration(duration),
+                view_count,
+                concurrent_view_count,
+            )
+        };
 
-            if live_status.as_deref() == Some("is_upcoming") {
-                continue;
-            }
+    // Extract live_status before thumbnail extraction for easier access
+    let live_status = val["live_status"].as_str().map(|s| s.to_string());
+    let is_live_bool = val["is_live"].as_bool().unwrap_or(false);
 
-            // If it's a direct URL (not a search), we bypass filters because the user
-            // specifically requested this item/playlist.
-            if !is_url {
-                if !show_live && (live_status.as_deref() == Some("is_live") || is_live_bool) {
-                    continue;
-                }
+    if live_status.as_deref() == Some("is_upcoming") {
+        return None;
+    }
 
-                if !show_playlists && video_type == crate::model::VideoType::Playlist {
-                    continue;
-                }
-            }
+    // If it's a direct URL (not a search), we bypass filters because the user
+    // specifically requested this item/playlist.
+    if !is_url {
+        if !show_live && (live_status.as_deref() == Some("is_live") || is_live_bool) {
+            return None;
+        }
 
-            // Extract thumbnail based on determined video_type
-            let thumbnail: Option<String> = if video_type == crate::model::VideoType::Playlist {
-                // For actual playlist entries, try playlist_thumbnails first
-                val["playlist_thumbnails"]
-                    .as_array()
-                    .and_then(|arr| arr.last())
-                    .and_then(|t| t["url"].as_str())
-                    .map(|s| s.to_string())
-                    .or_else(|| {
-                        val["thumbnails"]
-                            .as_array()
-                            .and_then(|arr| arr.last())
-                            .and_then(|t| t["url"].as_str())
-                            .map(|s| s.to_string())
-                    })
-                    .or_else(|| val["thumbnail"].as_str().map(|s| s.to_string()))
-            } else {
-                // For videos, try thumbnails array first, then fallback to single thumbnail field
+        if !show_playlists && video_type == crate::model::VideoType::Playlist {
+            return None;
+        }
+    }
+
+    // Extract thumbnail based on determined video_type
+    let thumbnail: Option<String> = if video_type == crate::model::VideoType::Playlist {
+        // For actual playlist entries, try playlist_thumbnails first
+        val["playlist_thumbnails"]
+            .as_array()
+            .and_then(|arr| arr.last())
+            .and_then(|t| t["url"].as_str())
+            .map(|s| s.to_string())
+            .or_else(|| {
                 val["thumbnails"]
                     .as_array()
                     .and_then(|arr| arr.last())
                     .and_then(|t| t["url"].as_str())
                     .map(|s| s.to_string())
-                    .or_else(|| val["thumbnail"].as_str().map(|s| s.to_string()))
-                    .or_else(|| val["url"].as_str().map(|s| s.to_string())) // Last ditch effort of existing logic
-                    .or_else(|| {
-                        // New Fallback: Construct URL from ID
-                        if !id.is_empty() {
-                            Some(format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", id))
-                        } else {
-                            None
-                        }
-                    })
-            };
-            let upload_date = val["upload_date"].as_str().map(|s| s.to_string());
-
-            if video_type == crate::model::VideoType::Playlist {
-                // Always construct canonical URL for real playlists
-                if is_real_playlist_id {
-                    final_url =
-                        format!("https://www.youtube.com/playlist?list={}", playlist_id_str);
-                } else if let Some(p_url) = val["playlist_webpage_url"].as_str() {
-                    // Only use provided URL if it's not a search result page
-                    if !p_url.contains("results?search_query") {
-                        final_url = p_url.to_string();
-                    }
+            })
+            .or_else(|| val["thumbnail"].as_str().map(|s| s.to_string()))
+    } else {
+        // For videos, try thumbnails array first, then fallback to single thumbnail field
+        val["thumbnails"]
+            .as_array()
+            .and_then(|arr| arr.last())
+            .and_then(|t| t["url"].as_str())
+            .map(|s| s.to_string())
+            .or_else(|| val["thumbnail"].as_str().map(|s| s.to_string()))
+            .or_else(|| val["url"].as_str().map(|s| s.to_string())) // Last ditch effort of existing logic
+            .or_else(|| {
+                // New Fallback: Construct URL from ID
+                if !id.is_empty() {
+                    Some(format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", id))
+                } else {
+                    None
                 }
+            })
+    };
+    let upload_date = val["upload_date"].as_str().map(|s| s.to_string());
+
+    if video_type == crate::model::VideoType::Playlist {
+        // Always construct canonical URL for real playlists
+        if is_real_playlist_id {
+            final_url = format!("https://www.youtube.com/playlist?list={}", playlist_id_str);
+        } else if let Some(p_url) = val["playlist_webpage_url"].as_str() {
+            // Only use provided URL if it's not a search result page
+            if !p_url.contains("results?search_query") {
+                final_url = p_url.to_string();
             }
+        }
+    }
 
-            // Check if this video is part of a real playlist (not a search query)
-            // This happens when browsing actual playlists
-            let (parent_playlist_id, parent_playlist_url, parent_playlist_title) =
-                if video_type == crate::model::VideoType::Video && is_real_playlist_id {
-                    // This is a video from a real playlist
-                    let playlist_url =
-                        format!("https://www.youtube.com/playlist?list={}", playlist_id_str);
-                    let playlist_title = val["playlist_title"].as_str().map(|s| s.to_string());
-                    (
-                        Some(playlist_id_str.to_string()),
-                        Some(playlist_url),
-                        playlist_title,
-                    )
-                } else {
-                    (None, None, None)
-                };
+    // Check if this video is part of a real playlist (not a search query)
+    // This happens when browsing actual playlists
+    let (parent_playlist_id, parent_playlist_url, parent_playlist_title) =
+        if video_type == crate::model::VideoType::Video && is_real_playlist_id {
+            // This is a video from a real playlist
+            let playlist_url = format!("https://www.youtube.com/playlist?list={}", playlist_id_str);
+            let playlist_title = val["playlist_title"].as_str().map(|s| s.to_string());
+            (
+                Some(playlist_id_str.to_string()),
+                Some(playlist_url),
+                playlist_title,
+            )
+        } else {
+            (None, None, None)
+        };
+
+    Some(Video {
+        id,
+        title,
+        channel,
+        channel_id,
+        url: final_url,
+        duration_string,
+        thumbnail_url: thumbnail,
+        view_count,
+        concurrent_view_count,
+        upload_date,
+        playlist_count,
+        live_status,
+        is_partial: true,
+        video_type,
+        parent_playlist_id,
+        parent_playlist_url,
+        parent_playlist_title,
+        chapters: parse_chapters(val),
+    })
+}
 
-            let video = Video {
-                id,
-                title,
-                channel,
-                channel_id,
-                url: final_url,
-                duration_string,
-                thumbnail_url: thumbnail,
-                view_count,
-                concurrent_view_count,
-                upload_date,
-                playlist_count,
-                live_status,
-                is_partial: true,
-                video_type,
-                parent_playlist_id,
-                parent_playlist_url,
-                parent_playlist_title,
-            };
+/// Chapters from yt-dlp's JSON, also found in the `.info.json` of downloads.
+pub fn parse_chapters(val: &Value) -> Vec<Chapter> {
+    let mut chapters: Vec<Chapter> = val["chapters"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|c| {
+            Some(Chapter {
+                start_time: c["start_time"].as_f64()?,
+                end_time: c["end_time"].as_f64().unwrap_or(0.0),
+                title: c["title"].as_str().unwrap_or("").to_string(),
+            })
+        })
+        .collect();
+    chapters.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    chapters
+}
 
-            count += 1;
-            let progress = (count as f32 / expected as f32).min(1.0);
-            let _ = tx.send(Ok(SearchResult::Video(video)));
-            // We removed progress bar from UI plan, but keeping the event for now as App handles it
-            let _ = tx.send(Ok(SearchResult::Progress(progress)));
-        }
+/// Fetches the most recent uploads of a channel, newest first.
+pub async fn fetch_channel_uploads(
+    channel_id: &str,
+    limit: u32,
+    settings: &Settings,
+) -> Result<Vec<Video>> {
+    let url = if channel_id.starts_with('@') {
+        format!("https://www.youtube.com/{}/videos", channel_id)
+    } else {
+        format!("https://www.youtube.com/channel/{}/videos", channel_id)
+    };
+    let end = limit.max(1).to_string();
+
+    log::info!("Fetching uploads for channel: {}", channel_id);
+    let output = build_base_command(settings)
+        .args([
+            "--dump-json",
+            "--flat-playlist",
+            "--no-warnings",
+            "--ignore-errors",
+            // Flat entries carry no upload_date unless asked for
+            "--extractor-args",
+            "youtubetab:approximate_date",
+            "--playlist-end",
+            &end,
+            &url,
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to spawn yt-dlp")?
+        .wait_with_output()
+        .await?;
+
+    let videos: Vec<Video> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|val| parse_flat_entry(&val, true, true, false))
+        .filter(|v| v.video_type == crate::model::VideoType::Video)
+        .collect();
+
+    if videos.is_empty() && !output.status.success() {
+        let err_msg = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "yt-dlp error for channel {}: {}",
+            channel_id,
+            err_msg.trim()
+        );
     }
 
-    let output = child.wait_with_output().await?; // Await child and capture output
+    Ok(videos)
+}
 
-    if !output.status.success() {
+/// Videos `start` to `end` (1-based, inclusive) of a playlist, for playing it in parts.
+pub async fn fetch_playlist_page(
+    url: &str,
+    start: u32,
+    end: u32,
+    settings: &Settings,
+) -> Result<PlaylistPage> {
+    let (start, end) = (start.to_string(), end.to_string());
+    log::info!("Fetching playlist entries {}-{} of {}", start, end, url);
+    let output = build_base_command(settings)
+        .args([
+            "--dump-json",
+            "--flat-playlist",
+            "--lazy-playlist",
+            "--no-warnings",
+            "--ignore-errors",
+            "--playlist-start",
+            &start,
+            "--playlist-end",
+            &end,
+            url,
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to spawn yt-dlp")?
+        .wait_with_output()
+        .await?;
+
+    if !output.status.success() && output.stdout.is_empty() {
         let err_msg = String::from_utf8_lossy(&output.stderr);
-        log::error!("yt-dlp search error: {}", err_msg);
-        let _ = tx.send(Err(format!("yt-dlp error: {}", err_msg)));
-        anyhow::bail!("yt-dlp exited with error: {}", err_msg);
+        anyhow::bail!("yt-dlp error for playlist {}: {}", url, err_msg.trim());
     }
 
-    let _ = tx.send(Ok(SearchResult::Progress(1.0)));
-    Ok(())
+    let entries: Vec<Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .collect();
+    Ok(PlaylistPage {
+        listed: entries.len() as u32,
+        videos: entries
+            .iter()
+            .filter_map(|val| parse_flat_entry(val, true, true, false))
+            .filter(|v| v.video_type == crate::model::VideoType::Video)
+            .collect(),
+    })
+}
+
+/// Every video of a playlist with its place in it, for downloading the playlist as a whole.
+pub async fn fetch_playlist_items(
+    url: &str,
+    settings: &Settings,
+) -> Result<Vec<(Video, PlaylistItem)>> {
+    log::info!("Fetching all entries of playlist {}", url);
+    let output = build_base_command(settings)
+        .args([
+            "--dump-single-json",
+            "--flat-playlist",
+            "--no-warnings",
+            "--ignore-errors",
+            url,
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to spawn yt-dlp")?
+        .wait_with_output()
+        .await?;
+
+    match serde_json::from_slice::<Value>(&output.stdout) {
+        Ok(val) => Ok(parse_playlist_items(&val, url)),
+        Err(_) => {
+            let err_msg = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("yt-dlp error for playlist {}: {}", url, err_msg.trim());
+        }
+    }
+}
+
+/// The videos of a `--dump-single-json --flat-playlist` playlist, numbered by their
+/// place in it. A video listed twice is kept at its first place, downloads are per video.
+fn parse_playlist_items(val: &Value, url: &str) -> Vec<(Video, PlaylistItem)> {
+    let entries = val["entries"].as_array().cloned().unwrap_or_default();
+    // The id groups the downloads panel, the URL stands in for playlists without one
+    let id = match val["id"].as_str() {
+        Some(id) if !id.is_empty() => id.to_string(),
+        _ => url.to_string(),
+    };
+    let title = val["title"].as_str().unwrap_or("Playlist").to_string();
+    let count = entries.len() as u64;
+
+    // Numbered before unavailable entries are dropped, so numbers match the playlist
+    let mut items = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for (i, entry) in entries.iter().enumerate() {
+        if let Some(video) = parse_flat_entry(entry, true, true, false)
+            && video.video_type == crate::model::VideoType::Video
+            && seen.insert(video.id.clone())
+        {
+            let item = PlaylistItem {
+                id: id.clone(),
+                title: title.clone(),
+                index: i as u64 + 1,
+                count,
+            };
+            items.push((video, item));
+        }
+    }
+    items
 }
 
 pub async fn resolve_video_details(
@@ -496,6 +688,7 @@ pub async fn resolve_video_details(
                 parent_playlist_url,
                 parent_playlist_title,
                 live_status,
+                chapters: parse_chapters(&val),
             };
             if tx.send(Ok(video)).is_err() {
                 // Receiver dropped, so we can stop.
@@ -515,7 +708,33 @@ pub async fn resolve_video_details(
     Ok(())
 }
 
-pub async fn get_video_formats(url: &str, settings: &Settings) -> Result<Vec<VideoFormat>> {
+/// Subtitle languages from the `subtitles` and `automatic_captions` maps of yt-dlp's JSON.
+/// Auto captions are machine translated into every language, so only the original
+/// track and the preferred languages are kept of those.
+pub fn parse_subtitles(val: &Value, preferred: &[String]) -> Vec<SubtitleTrack> {
+    let mut tracks = Vec::new();
+    for (key, auto) in [("subtitles", false), ("automatic_captions", true)] {
+        let mut found: Vec<SubtitleTrack> = val[key]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(lang, _)| *lang != "live_chat")
+            .map(|(lang, entries)| SubtitleTrack {
+                lang: lang.clone(),
+                name: entries[0]["name"].as_str().unwrap_or(lang).to_string(),
+                auto,
+            })
+            .filter(|t| {
+                !t.auto || t.lang.ends_with("-orig") || preferred.iter().any(|p| t.matches(p))
+            })
+            .collect();
+        found.sort_by(|a, b| a.lang.cmp(&b.lang));
+        tracks.extend(found);
+    }
+    tracks
+}
+
+pub async fn get_video_formats(url: &str, settings: &Settings) -> Result<MediaFormats> {
     log::info!("Fetching formats for URL: {}", url);
     let output = build_base_command(settings)
         .arg("--dump-json")
@@ -613,15 +832,27 @@ pub async fn get_video_formats(url: &str, settings: &Settings) -> Result<Vec<Vid
         }
     });
 
-    Ok(formats)
+    Ok(MediaFormats {
+        formats,
+        subtitles: parse_subtitles(&val, &settings.subtitles.languages),
+    })
 }
 
 pub async fn get_best_stream_url(url: &str, settings: &Settings) -> Result<String> {
+    // Terminal rendering is small, a capped stream loads faster
+    let best = match settings.terminal_video.max_height {
+        0 => "best".to_string(),
+        height => format!("best[height<=?{}]/best", height),
+    };
+
     // We use -g to get the URL.
     let output = build_base_command(settings)
         .arg("-g")
         .arg("-f")
-        .arg("bestvideo+bestaudio/best")
+        .arg(match settings.terminal_video.max_height {
+            0 => "bestvideo+bestaudio/best".to_string(),
+            height => format!("bestvideo[height<=?{}]+bestaudio/{}", height, best),
+        })
         .arg("--no-playlist")
         .arg(url)
         .output()
@@ -635,7 +866,7 @@ pub async fn get_best_stream_url(url: &str, settings: &Settings) -> Result<Strin
             let fallback = build_base_command(settings)
                 .arg("-g")
                 .arg("-f")
-                .arg("best")
+                .arg(&best)
                 .arg("--no-playlist")
                 .arg(url)
                 .output()
@@ -650,7 +881,7 @@ pub async fn get_best_stream_url(url: &str, settings: &Settings) -> Result<Strin
             let fallback = build_base_command(settings)
                 .arg("-g")
                 .arg("-f")
-                .arg("best")
+                .arg(&best)
                 .arg("--no-playlist")
                 .arg(url)
                 .output()
@@ -669,7 +900,7 @@ pub async fn get_best_stream_url(url: &str, settings: &Settings) -> Result<Strin
         let fallback = build_base_command(settings)
             .arg("-g")
             .arg("-f")
-            .arg("best")
+            .arg(&best)
             .arg("--no-playlist")
             .arg(url)
             .output()
@@ -687,6 +918,13 @@ pub async fn get_best_stream_url(url: &str, settings: &Settings) -> Result<Strin
     }
 }
 
+/// Seconds in a duration like "1:02:03" or "02:03", the reverse of `format_duration`.
+pub fn parse_duration(duration: &str) -> Option<f64> {
+    duration.split(':').try_fold(0.0, |total, part| {
+        Some(total * 60.0 + part.trim().parse::<f64>().ok()?)
+    })
+}
+
 pub fn format_duration(seconds: f64) -> String {
     let seconds = seconds as u64;
     let h = seconds / 3600;
@@ -698,3 +936,90 @@ pub fn format_duration(seconds: f64) -> String {
         format!("{:02}:{:02}", m, s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{chapter_at, preferred_subtitles};
+
+    #[test]
+    fn test_parse_subtitles() {
+        let val: Value = serde_json::from_str(
+            r#"{
+                "subtitles": {
+                    "live_chat": [{"ext": "json"}],
+                    "en-US": [{"ext": "vtt", "name": "English (United States)"}]
+                },
+                "automatic_captions": {
+                    "de-orig": [{"ext": "vtt", "name": "German (Original)"}],
+                    "en": [{"ext": "vtt", "name": "English"}],
+                    "fr": [{"ext": "vtt", "name": "French"}]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let tracks = parse_subtitles(&val, &["en".to_string()]);
+        let langs: Vec<(&str, bool)> = tracks.iter().map(|t| (t.lang.as_str(), t.auto)).collect();
+        assert_eq!(langs, [("en-US", false), ("de-orig", true), ("en", true)]);
+
+        let languages = ["en".to_string(), "de".to_string()];
+        let selected = preferred_subtitles(&tracks, &languages, true);
+        let langs: Vec<&str> = selected.iter().map(|t| t.lang.as_str()).collect();
+        assert_eq!(langs, ["en-US", "de-orig"]);
+        assert!(preferred_subtitles(&tracks, &["de".to_string()], false).is_empty());
+    }
+
+    #[test]
+    fn test_parse_chapters() {
+        let val: Value = serde_json::from_str(
+            r#"{
+                "chapters": [
+                    {"start_time": 95.0, "end_time": 300.0, "title": "Main"},
+                    {"start_time": 0.0, "end_time": 95.0, "title": "Intro"},
+                    {"title": "Broken"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let chapters = parse_chapters(&val);
+        let titles: Vec<&str> = chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, ["Intro", "Main"]);
+        assert_eq!(chapter_at(&chapters, 42.0), Some(0));
+        assert_eq!(chapter_at(&chapters, 95.0), Some(1));
+        assert_eq!(chapter_at(&[], 42.0), None);
+    }
+
+    #[test]
+    fn test_parse_playlist_items() {
+        let val: Value = serde_json::from_str(
+            r#"{
+                "id": "PLmix",
+                "title": "Mix",
+                "entries": [
+                    {"id": "a", "title": "First", "url": "https://youtu.be/a"},
+                    {"id": "p", "title": "[Private video]", "url": "https://youtu.be/p"},
+                    {"id": "b", "title": "Second", "url": "https://youtu.be/b"},
+                    {"id": "a", "title": "First", "url": "https://youtu.be/a"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let items = parse_playlist_items(&val, "https://youtube.com/playlist?list=PLmix");
+        let places: Vec<(&str, u64)> = items
+            .iter()
+            .map(|(v, item)| (v.id.as_str(), item.index))
+            .collect();
+        assert_eq!(places, [("a", 1), ("b", 3)]);
+        assert!(
+            items
+                .iter()
+                .all(|(_, item)| item.id == "PLmix" && item.count == 4)
+        );
+
+        let val: Value = serde_json::from_str(r#"{"entries": []}"#).unwrap();
+        assert!(parse_playlist_items(&val, "https://example.com/list").is_empty());
+    }
+}
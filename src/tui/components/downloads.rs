@@ -5,8 +5,8 @@ use ratatui::{
     widgets::{Block, BorderType, Borders, Cell, Row, Table},
 };
 
-use crate::app::{App, AppState};
-use crate::model::download::DownloadStatus;
+use crate::app::{App, AppState, DownloadRow};
+use crate::model::download::{DownloadPhase, DownloadStatus, DownloadTask};
 
 use super::widgets::{create_progress_bar_string, truncate_str};
 
@@ -33,6 +33,46 @@ pub fn render_downloads_view(f: &mut ratatui::Frame, app: &mut App, area: Rect)
     }
 }
 
+/// Binary units, as yt-dlp uses them.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+    let mut value = bytes as f64;
+    let mut unit = "B";
+    for u in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = u;
+    }
+    format!("{:.2}{}", value, unit)
+}
+
+fn postprocessor_label(postprocessor: Option<&str>) -> String {
+    let step = match postprocessor {
+        Some("EmbedThumbnail") => "thumbnail",
+        Some("FFmpegMetadata") => "metadata",
+        Some("FFmpegEmbedSubtitle") => "subtitles",
+        Some("SponsorBlock") | Some("ModifyChapters") => "SponsorBlock",
+        Some("FFmpegExtractAudio") => "audio",
+        Some(p) if p.starts_with("FFmpegFixup") => "fixup",
+        _ => return "Processing".to_string(),
+    };
+    format!("Processing: {}", step)
+}
+
+fn size_label(task: &DownloadTask) -> String {
+    match task.stats.total_bytes {
+        Some(total) => format_bytes(total),
+        // Tasks found on disk or restored from the queue never reported a size
+        None if task.status == DownloadStatus::Finished => "Cached".to_string(),
+        None => String::new(),
+    }
+}
+
 fn render_active_downloads(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
     let header = Row::new(vec![
         Cell::from("TITLE"),
@@ -50,69 +90,12 @@ fn render_active_downloads(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
     .height(1)
     .bottom_margin(1);
 
-    let rows: Vec<Row> = app
-        .download_manager
-        .task_order
+    let table_rows = app.download_manager.rows();
+    let rows: Vec<Row> = table_rows
         .iter()
-        .enumerate()
-        .filter_map(|(i, id)| app.download_manager.tasks.get(id).map(|task| (i, task)))
-        .map(|(i, task)| {
-            let is_focused =
-                app.state == AppState::Downloads && app.selected_download_index == Some(i);
-            let indicator = if is_focused { "┃ " } else { "  " };
-            let checkbox = if app.selected_download_indices.contains(&i) {
-                "[x] "
-            } else {
-                "[ ] "
-            };
-
-            let status_span = match &task.status {
-                DownloadStatus::Downloading => {
-                    Span::styled("Downloading", Style::default().fg(Color::Green))
-                }
-                DownloadStatus::Finished => {
-                    Span::styled("Finished", Style::default().fg(Color::Cyan))
-                }
-                DownloadStatus::Error(e) => {
-                    Span::styled(format!("Error: {}", e), Style::default().fg(Color::Red))
-                }
-                DownloadStatus::Paused => {
-                    Span::styled("Paused", Style::default().fg(Color::Yellow))
-                }
-                DownloadStatus::Canceled => {
-                    Span::styled("Canceled", Style::default().fg(Color::DarkGray))
-                }
-                _ => Span::raw("Pending"),
-            };
-
-            let row_style = if is_focused {
-                Style::default()
-                    .bg(app.theme.highlight)
-                    .fg(app.theme.fg)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default()
-            };
-
-            let title_avail = (area.width as f64 * 0.3).round() as usize;
-            let display_title = truncate_str(&task.title, title_avail.saturating_sub(6));
-
-            Row::new(vec![
-                Cell::from(format!("{}{}{}", indicator, checkbox, display_title)),
-                Cell::from(task.total_size.clone()),
-                Cell::from(create_progress_bar_string(
-                    task.progress,
-                    15,
-                    app.theme.accent,
-                    Color::DarkGray,
-                    &app.progress_style,
-                )),
-                Cell::from(task.speed.clone()),
-                Cell::from(task.eta.clone()),
-                Cell::from(status_span),
-            ])
-            .style(row_style)
-            .height(1)
+        .filter_map(|row| match *row {
+            DownloadRow::Playlist(i) => playlist_row(app, i),
+            DownloadRow::Task(i) => task_row(app, i, area),
         })
         .collect();
 
@@ -132,18 +115,201 @@ fn render_active_downloads(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
         Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .title(" Active Tasks "),
+            .title(format!(
+                " Active Tasks ({}/{} running, {} queued{}) ",
+                app.download_manager.active_count(),
+                app.max_concurrent_downloads,
+                app.download_manager.pending_count(),
+                if app.active_rate_limit.is_empty() {
+                    String::new()
+                } else {
+                    format!(", limit {}/s", app.active_rate_limit)
+                }
+            )),
     );
 
-    app.downloads_active_state
-        .select(if app.state == AppState::Downloads {
-            app.selected_download_index
-        } else {
-            None
-        });
+    // The selection is a task index, summary rows shift it down
+    let selected = match app.selected_download_index {
+        Some(idx) if app.state == AppState::Downloads => table_rows
+            .iter()
+            .position(|row| *row == DownloadRow::Task(idx)),
+        _ => None,
+    };
+    app.downloads_active_state.select(selected);
     f.render_stateful_widget(table, area, &mut app.downloads_active_state);
 }
 
+fn task_row(app: &App, i: usize, area: Rect) -> Option<Row<'static>> {
+    let task = app
+        .download_manager
+        .task_order
+        .get(i)
+        .and_then(|id| app.download_manager.tasks.get(id))?;
+    let is_focused = app.state == AppState::Downloads && app.selected_download_index == Some(i);
+    let indicator = if is_focused { "┃ " } else { "  " };
+    let checkbox = if app.selected_download_indices.contains(&i) {
+        "[x] "
+    } else {
+        "[ ] "
+    };
+
+    let status_span = match &task.status {
+        DownloadStatus::Downloading => match task.stats.phase {
+            DownloadPhase::Merging => Span::styled("Merging", Style::default().fg(Color::Magenta)),
+            DownloadPhase::PostProcessing => Span::styled(
+                postprocessor_label(task.stats.postprocessor.as_deref()),
+                Style::default().fg(Color::Magenta),
+            ),
+            DownloadPhase::Downloading => match task.stats.fragment_index {
+                Some(index) => Span::styled(
+                    format!(
+                        "Downloading ({}/{})",
+                        index,
+                        task.stats
+                            .fragment_count
+                            .map(|c| c.to_string())
+                            .unwrap_or_else(|| "?".to_string())
+                    ),
+                    Style::default().fg(Color::Green),
+                ),
+                None => Span::styled("Downloading", Style::default().fg(Color::Green)),
+            },
+        },
+        DownloadStatus::Finished => Span::styled("Finished", Style::default().fg(Color::Cyan)),
+        DownloadStatus::Error(_) if task.retry_at.is_some() => {
+            let wait = task.retry_at.unwrap_or_default() - chrono::Utc::now().timestamp();
+            Span::styled(
+                format!(
+                    "Retry {}/{} in {}s",
+                    task.attempts,
+                    app.settings.retry_max_attempts,
+                    wait.max(0)
+                ),
+                Style::default().fg(Color::Yellow),
+            )
+        }
+        DownloadStatus::Error(e) if task.attempts > 0 => Span::styled(
+            format!("Error after {} retries: {}", task.attempts, e),
+            Style::default().fg(Color::Red),
+        ),
+        DownloadStatus::Error(e) => {
+            Span::styled(format!("Error: {}", e), Style::default().fg(Color::Red))
+        }
+        DownloadStatus::Paused => Span::styled("Paused", Style::default().fg(Color::Yellow)),
+        DownloadStatus::Canceled => Span::styled("Canceled", Style::default().fg(Color::DarkGray)),
+        _ => Span::styled("Queued", Style::default().fg(app.theme.border)),
+    };
+
+    let row_style = if is_focused {
+        Style::default()
+            .bg(app.theme.highlight)
+            .fg(app.theme.fg)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+
+    let speed = task
+        .stats
+        .speed
+        .map(|s| format!("{}/s", format_bytes(s as u64)))
+        .unwrap_or_default();
+    // Per-task limits are shown next to the speed, the global one is in the title
+    let speed = match task.rate_limit.as_deref() {
+        Some(rate) if !rate.is_empty() => format!("{} ≤{}", speed, rate),
+        _ => speed,
+    };
+    let eta = task
+        .stats
+        .eta
+        .map(|e| crate::sys::yt::format_duration(e as f64))
+        .unwrap_or_default();
+
+    let title_avail = (area.width as f64 * 0.3).round() as usize;
+    let title = match &task.playlist {
+        Some(item) => format!("{}. {}", item.number(), task.title),
+        None => task.title.clone(),
+    };
+    let display_title = truncate_str(&title, title_avail.saturating_sub(6));
+
+    Some(
+        Row::new(vec![
+            Cell::from(format!("{}{}{}", indicator, checkbox, display_title)),
+            Cell::from(size_label(task)),
+            Cell::from(create_progress_bar_string(
+                task.progress,
+                15,
+                app.theme.accent,
+                Color::DarkGray,
+                &app.progress_style,
+            )),
+            Cell::from(speed),
+            Cell::from(eta),
+            Cell::from(status_span),
+        ])
+        .style(row_style)
+        .height(1),
+    )
+}
+
+/// Summary of a playlist downloaded as a whole, above its tasks.
+fn playlist_row(app: &App, i: usize) -> Option<Row<'static>> {
+    let item = app
+        .download_manager
+        .task_order
+        .get(i)
+        .and_then(|id| app.download_manager.tasks.get(id))
+        .and_then(|t| t.playlist.clone())?;
+    let tasks: Vec<&DownloadTask> = app.download_manager.playlist_tasks(&item.id).collect();
+    let finished = tasks
+        .iter()
+        .filter(|t| t.status == DownloadStatus::Finished)
+        .count();
+    let failed = tasks
+        .iter()
+        .filter(|t| matches!(t.status, DownloadStatus::Error(_)))
+        .count();
+    let progress = tasks.iter().map(|t| t.progress).sum::<f64>() / tasks.len().max(1) as f64;
+    let size: u64 = tasks.iter().filter_map(|t| t.stats.total_bytes).sum();
+    let speed: f64 = tasks.iter().filter_map(|t| t.stats.speed).sum();
+
+    let mut status = format!("{}/{} done", finished, tasks.len());
+    if failed > 0 {
+        status.push_str(&format!(", {} failed", failed));
+    }
+
+    Some(
+        Row::new(vec![
+            Cell::from(format!("▾ {}", item.title)),
+            Cell::from(if size > 0 {
+                format_bytes(size)
+            } else {
+                String::new()
+            }),
+            Cell::from(create_progress_bar_string(
+                progress,
+                15,
+                app.theme.accent,
+                Color::DarkGray,
+                &app.progress_style,
+            )),
+            Cell::from(if speed > 0.0 {
+                format!("{}/s", format_bytes(speed as u64))
+            } else {
+                String::new()
+            }),
+            Cell::from(""),
+            Cell::from(status),
+        ])
+        .style(
+            Style::default()
+                .fg(app.theme.accent)
+                .add_modifier(Modifier::BOLD),
+        )
+        .height(1),
+    )
+}
+
 fn render_local_files(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
     let header = Row::new(vec![
         Cell::from("FILENAME"),
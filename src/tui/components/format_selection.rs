@@ -7,34 +7,53 @@ use ratatui::{
 
 use super::widgets::centered_rect;
 use crate::app::App;
+use crate::app::state::FormatSelectionMode;
 
 pub fn render_format_selection(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
-    let width_percent =
-        if app.format_selection_mode == crate::app::state::FormatSelectionMode::Download {
-            40
-        } else {
-            20
-        };
+    let mode = app.format_selection_mode;
+    // Downloads show format details and post-processing toggles, playback only the quality
+    let detailed = mode != FormatSelectionMode::Watch;
+    let width_percent = if detailed { 40 } else { 20 };
     let area = centered_rect(width_percent, 30, area);
     app.format_selection_area = Some(area);
     f.render_widget(ratatui::widgets::Clear, area);
 
-    let formats = &app.formats;
     let selected_index = app.selected_format_index;
+    let title = if mode == FormatSelectionMode::Batch && app.batch_playlist.is_some() {
+        " Download Playlist ".to_string()
+    } else if mode == FormatSelectionMode::Batch {
+        format!(" Download {} Videos ", app.batch_videos.len())
+    } else {
+        " Select Quality ".to_string()
+    };
 
-    let block = Block::default()
-        .title(" Select Quality ")
+    let mut block = Block::default()
+        .title(title)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(app.theme.highlight))
         .style(Style::default().bg(app.theme.bg));
 
+    if detailed {
+        let pp = &app.format_postprocess;
+        let toggle = |key: &str, name: &str, on: bool| {
+            format!("{}:{}{} ", key, name, if on { "✓" } else { "✗" })
+        };
+        block = block.title_bottom(format!(
+            " {}{}{}s:SponsorBlock {} ",
+            toggle("t", "Thumb", pp.embed_thumbnail),
+            toggle("m", "Meta", pp.embed_metadata),
+            toggle("u", "Subs", pp.embed_subtitles),
+            pp.sponsorblock.name()
+        ));
+    }
+
     let header_style = Style::default()
         .fg(app.theme.accent)
         .add_modifier(Modifier::BOLD);
 
     let mut header_cells = vec![Cell::from(" QUALITY")];
-    if app.format_selection_mode == crate::app::state::FormatSelectionMode::Download {
+    if detailed {
         header_cells.push(Cell::from(" FORMAT"));
         header_cells.push(Cell::from(" SIZE"));
     }
@@ -44,8 +63,43 @@ pub fn render_format_selection(f: &mut ratatui::Frame, app: &mut App, area: Rect
         .height(1)
         .bottom_margin(1);
 
-    let rows: Vec<Row> = formats
+    let mut rows: Vec<Row> = app
+        .format_profiles()
         .iter()
+        .map(|profile| {
+            let format = if !profile.audio_format.is_empty() {
+                profile.audio_format.clone()
+            } else if !profile.merge_format.is_empty() {
+                profile.merge_format.clone()
+            } else {
+                "Profile".to_string()
+            };
+            Row::new(vec![
+                Cell::from(format!(" {}", profile.name)),
+                Cell::from(format),
+                Cell::from(profile.directory.clone()),
+            ])
+            .style(Style::default().fg(app.theme.accent))
+            .height(1)
+        })
+        .collect();
+
+    if mode == FormatSelectionMode::Batch {
+        rows.push(
+            Row::new(vec![
+                Cell::from(" Best available"),
+                Cell::from("Auto"),
+                Cell::from(""),
+            ])
+            .style(Style::default().fg(app.theme.fg))
+            .height(1),
+        );
+    }
+
+    let format_rows = app
+        .formats
+        .iter()
+        .filter(|_| mode != FormatSelectionMode::Batch)
         .map(|fmt| {
             let quality = if fmt.resolution == "audio only" {
                 "Audio".to_string()
@@ -68,7 +122,7 @@ pub fn render_format_selection(f: &mut ratatui::Frame, app: &mut App, area: Rect
 
             let mut cells = vec![Cell::from(format!(" {}", quality))];
 
-            if app.format_selection_mode == crate::app::state::FormatSelectionMode::Download {
+            if detailed {
                 cells.push(Cell::from(fmt.ext.clone()));
                 let size = fmt
                     .filesize
@@ -87,19 +141,39 @@ pub fn render_format_selection(f: &mut ratatui::Frame, app: &mut App, area: Rect
             Row::new(cells)
                 .style(Style::default().fg(app.theme.fg))
                 .height(1)
-        })
-        .collect();
+        });
+    rows.extend(format_rows);
 
-    let constraints =
-        if app.format_selection_mode == crate::app::state::FormatSelectionMode::Download {
-            vec![
-                Constraint::Percentage(40),
-                Constraint::Percentage(30),
-                Constraint::Percentage(30),
-            ]
+    // Subtitles are toggled, checked ones are downloaded next to the media
+    rows.extend(app.format_subtitles().iter().map(|track| {
+        let checked = app.selected_subtitles.contains(track);
+        let style = if checked {
+            Style::default().fg(app.theme.accent)
         } else {
-            vec![Constraint::Percentage(100)]
+            Style::default().fg(app.theme.border)
         };
+        Row::new(vec![
+            Cell::from(format!(
+                " [{}] {}",
+                if checked { "x" } else { " " },
+                track.name
+            )),
+            Cell::from(track.lang.clone()),
+            Cell::from(if track.auto { "Auto CC" } else { "Subtitles" }),
+        ])
+        .style(style)
+        .height(1)
+    }));
+
+    let constraints = if detailed {
+        vec![
+            Constraint::Percentage(40),
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+        ]
+    } else {
+        vec![Constraint::Percentage(100)]
+    };
 
     let table = Table::new(rows, constraints)
         .header(header)
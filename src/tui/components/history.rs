@@ -0,0 +1,123 @@
+use super::widgets::{centered_rect, centered_rect_fixed, truncate_str};
+use crate::app::App;
+use crate::sys::yt::format_duration;
+use ratatui::{prelude::*, widgets::*};
+
+pub fn render_history(f: &mut Frame, app: &mut App, area: Rect) {
+    let area = centered_rect(60, 70, area);
+    app.history_area = Some(area);
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" History ({}) ", app.history.len()))
+        .title_bottom(" Enter: Replay | x: Remove | c: Clear | h/Esc: Close ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().bg(app.theme.bg).fg(app.theme.fg))
+        .border_style(Style::default().fg(app.theme.highlight));
+
+    if app.history.is_empty() {
+        let p = Paragraph::new("\n Nothing played yet.")
+            .style(Style::default().fg(app.theme.border))
+            .block(block);
+        f.render_widget(p, area);
+        return;
+    }
+
+    let width = (area.width as usize).saturating_sub(6);
+    let items: Vec<ListItem> = app
+        .history
+        .entries
+        .iter()
+        .map(|entry| {
+            let tag = if entry.is_audio { "[AUDIO] " } else { "" };
+            let progress = if entry.duration > 0.0 {
+                format!(
+                    "{}/{}",
+                    format_duration(entry.position),
+                    format_duration(entry.duration)
+                )
+            } else if entry.position > 0.0 {
+                format_duration(entry.position)
+            } else {
+                "--:--".to_string()
+            };
+            let played = chrono::DateTime::from_timestamp(entry.last_played, 0)
+                .map(|d| {
+                    d.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_default();
+
+            let mut details = Vec::new();
+            if !entry.video.channel.is_empty() {
+                details.push(entry.video.channel.clone());
+            }
+            details.push(progress);
+            details.push(played);
+
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::styled(tag, Style::default().fg(app.theme.highlight)),
+                    Span::styled(
+                        truncate_str(&entry.video.title, width.saturating_sub(tag.len())),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                ]),
+                Line::from(Span::styled(
+                    format!("  {}", details.join(" • ")),
+                    Style::default().fg(app.theme.accent),
+                )),
+            ])
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(
+            Style::default()
+                .bg(app.theme.highlight)
+                .fg(app.theme.fg)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("┃ ");
+
+    app.history_state.select(app.selected_history_index);
+    f.render_stateful_widget(list, area, &mut app.history_state);
+}
+
+pub fn render_resume_prompt(f: &mut Frame, app: &App, area: Rect) {
+    if let Some((pending, pos)) = &app.resume_prompt {
+        let area = centered_rect_fixed(60, 6, area);
+        f.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Resume Playback ")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .style(Style::default().bg(app.theme.bg).fg(app.theme.fg))
+            .border_style(Style::default().fg(app.theme.accent));
+
+        let width = (area.width as usize).saturating_sub(4);
+        let text = vec![
+            Line::from(Span::styled(
+                truncate_str(&pending.title, width),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(format!("Resume from {}?", format_duration(*pos))),
+            Line::from(""),
+            Line::from(Span::styled(
+                "[Y/Enter] Resume  [N] Start over  [Esc] Cancel",
+                Style::default().fg(app.theme.highlight),
+            )),
+        ];
+
+        f.render_widget(
+            Paragraph::new(text)
+                .block(block)
+                .alignment(Alignment::Center),
+            area,
+        );
+    }
+}
@@ -13,7 +13,7 @@ use super::logo::Logo;
 use super::widgets::{centered_rect, truncate_str};
 
 pub fn render_main_area(f: &mut ratatui::Frame, app: &mut App, area: Rect, picker: &mut Picker) {
-    if app.search_query.is_empty() {
+    if app.search_query.is_empty() && !app.is_feed_mode {
         render_greeting_section(f, app, area);
         return;
     }
@@ -105,6 +105,31 @@ pub fn render_main_area(f: &mut ratatui::Frame, app: &mut App, area: Rect, picke
                             .add_modifier(Modifier::BOLD),
                     ),
                 ])
+            } else if app.is_feed_mode && !app.subscriptions.is_seen(&v.id) {
+                let tag = "[NEW] ";
+                let avail = list_inner_width.saturating_sub(prefix_len + tag.len());
+                let display_title = truncate_str(&v.title, avail);
+
+                Line::from(vec![
+                    Span::styled(
+                        index_prefix,
+                        Style::default()
+                            .fg(app.theme.fg)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        tag,
+                        Style::default()
+                            .fg(app.theme.highlight)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        display_title,
+                        Style::default()
+                            .fg(app.theme.fg)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                ])
             } else if v.parent_playlist_id.is_some() {
                 let tag = "[FROM PLAYLIST] ";
                 let avail = list_inner_width.saturating_sub(prefix_len + tag.len());
@@ -218,6 +243,8 @@ pub fn render_main_area(f: &mut ratatui::Frame, app: &mut App, area: Rect, picke
                 .border_style(Style::default().fg(app.theme.border))
                 .title(if let Some((parent, _, _)) = app.playlist_stack.last() {
                     format!(" Playlist: {} ", parent.title)
+                } else if app.is_feed_mode {
+                    " Feed ".to_string()
                 } else {
                     " Results ".to_string()
                 }),
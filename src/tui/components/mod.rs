@@ -1,11 +1,15 @@
 pub mod action_menu;
 pub mod downloads;
 pub mod format_selection;
+pub mod history;
 pub mod logo;
 pub mod main_content;
 pub mod playback_bar;
+pub mod queue;
 pub mod search_bar;
 pub mod settings;
 pub mod status_bar;
+pub mod subtitles;
 pub mod theme;
+pub mod video_pane;
 pub mod widgets;
@@ -2,13 +2,31 @@ use ratatui::{
     prelude::Rect,
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Paragraph},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph},
 };
 
+use super::widgets::{centered_rect, truncate_str};
 use crate::app::App;
+use crate::model::chapter_at;
+use crate::sys::yt::format_duration;
 
 pub fn render_playback_bar(f: &mut ratatui::Frame, app: &App, area: Rect) {
-    let title = app.playback_title.as_deref().unwrap_or("Unknown");
+    let mut title = app
+        .playback_title
+        .as_deref()
+        .unwrap_or("Unknown")
+        .to_string();
+    if app.player_playlist.len() > 1 {
+        // Entries of a playlist loaded in parts are counted in the whole playlist
+        let total = app
+            .playlist_source
+            .as_ref()
+            .and_then(|s| s.total)
+            .map_or(app.player_playlist.len(), |t| {
+                (t as usize).max(app.player_playlist.len())
+            });
+        title = format!("{}/{} — {}", app.player_playlist_pos + 1, total, title);
+    }
 
     let duration_str = app
         .playback_duration_str
@@ -26,7 +44,25 @@ pub fn render_playback_bar(f: &mut ratatui::Frame, app: &App, area: Rect) {
         app.theme.accent
     };
 
-    let overhead = 70;
+    // Volume, speed and loop, speed only when it is not normal
+    let mut player_state = if app.muted {
+        "Muted".to_string()
+    } else {
+        format!("Vol {:.0}%", app.volume)
+    };
+    if (app.playback_speed - 1.0).abs() > 0.001 {
+        player_state.push_str(&format!(" {}x", app.playback_speed));
+    }
+    if app.loop_file {
+        player_state.push_str(" Loop");
+    }
+    let player_state_color = if app.muted {
+        Color::Gray
+    } else {
+        app.theme.border
+    };
+
+    let overhead = 70 + player_state.chars().count() as u16;
     let available_width = area.width.saturating_sub(overhead) as usize;
     let displayed_title = if title.chars().count() > available_width && available_width > 3 {
         format!(
@@ -37,10 +73,10 @@ pub fn render_playback_bar(f: &mut ratatui::Frame, app: &App, area: Rect) {
                 .collect::<String>()
         )
     } else {
-        title.to_string()
+        title
     };
 
-    let p = Paragraph::new(Line::from(vec![
+    let mut lines = vec![Line::from(vec![
         Span::styled(
             status_str,
             Style::default()
@@ -55,6 +91,10 @@ pub fn render_playback_bar(f: &mut ratatui::Frame, app: &App, area: Rect) {
                 .fg(app.theme.highlight)
                 .add_modifier(Modifier::BOLD),
         ),
+        Span::styled(
+            format!("{} ", player_state),
+            Style::default().fg(player_state_color),
+        ),
         Span::styled(
             displayed_title,
             Style::default()
@@ -83,8 +123,29 @@ pub fn render_playback_bar(f: &mut ratatui::Frame, app: &App, area: Rect) {
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw(": Stop"),
-    ]))
-    .block(
+    ])];
+
+    let chapters = app.playback_chapters();
+    if !chapters.is_empty() {
+        let inner_width = area.width.saturating_sub(2) as usize;
+        let label = match chapter_at(chapters, app.playback_time) {
+            Some(i) => format!(" Ch {}/{}: {}", i + 1, chapters.len(), chapters[i].title),
+            None => format!(" {} chapters", chapters.len()),
+        };
+        let label = truncate_str(&label, inner_width / 3);
+        let timeline_width = inner_width.saturating_sub(label.chars().count() + 1);
+
+        let mut spans = chapter_timeline(app, timeline_width);
+        spans.push(Span::styled(
+            label,
+            Style::default()
+                .fg(app.theme.highlight)
+                .add_modifier(Modifier::BOLD),
+        ));
+        lines.push(Line::from(spans));
+    }
+
+    let p = Paragraph::new(lines).block(
         Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
@@ -92,3 +153,91 @@ pub fn render_playback_bar(f: &mut ratatui::Frame, app: &App, area: Rect) {
     );
     f.render_widget(p, area);
 }
+
+/// Progress line with a tick at the start of every chapter.
+fn chapter_timeline(app: &App, width: usize) -> Vec<Span<'static>> {
+    let mut spans = vec![Span::raw(" ")];
+    if width == 0 {
+        return spans;
+    }
+
+    let total = app.playback_total;
+    let cell = |t: f64| {
+        if total > 0.0 {
+            ((t / total) * width as f64) as usize
+        } else {
+            0
+        }
+    };
+    let ticks: Vec<usize> = app
+        .playback_chapters()
+        .iter()
+        .filter(|c| c.start_time > 0.0 && total > 0.0)
+        .map(|c| cell(c.start_time).min(width - 1))
+        .collect();
+    let played = cell(app.playback_time).min(width);
+
+    for i in 0..width {
+        let (symbol, color) = if ticks.contains(&i) {
+            ("┃", app.theme.highlight)
+        } else if i < played {
+            ("━", app.theme.accent)
+        } else {
+            ("─", app.theme.border)
+        };
+        spans.push(Span::styled(symbol, Style::default().fg(color)));
+    }
+    spans
+}
+
+pub fn render_chapter_list(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
+    let area = centered_rect(50, 50, area);
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Chapters ({}) ", app.playback_chapters().len()))
+        .title_bottom(" Enter: Jump | Esc: Close ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().bg(app.theme.bg).fg(app.theme.fg))
+        .border_style(Style::default().fg(app.theme.highlight));
+
+    let current = chapter_at(app.playback_chapters(), app.playback_time);
+    let title_width = (area.width as usize).saturating_sub(16);
+    let items: Vec<ListItem> = app
+        .playback_chapters()
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| {
+            let is_current = current == Some(i);
+            let title_style = if is_current {
+                Style::default()
+                    .fg(app.theme.accent)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(app.theme.fg)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    if is_current { "▶ " } else { "  " },
+                    Style::default().fg(app.theme.accent),
+                ),
+                Span::styled(
+                    format!("{:>8}  ", format_duration(chapter.start_time)),
+                    Style::default().fg(app.theme.border),
+                ),
+                Span::styled(truncate_str(&chapter.title, title_width), title_style),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(block).highlight_style(
+        Style::default()
+            .bg(app.theme.highlight)
+            .fg(app.theme.fg)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    app.chapter_list_state.select(app.selected_chapter_index);
+    f.render_stateful_widget(list, area, &mut app.chapter_list_state);
+}
@@ -0,0 +1,73 @@
+use super::widgets::{centered_rect, truncate_str};
+use crate::app::App;
+use crate::model::queue::QueueMode;
+use ratatui::{prelude::*, widgets::*};
+
+pub fn render_queue(f: &mut Frame, app: &mut App, area: Rect) {
+    let area = centered_rect(60, 60, area);
+    app.queue_area = Some(area);
+    f.render_widget(Clear, area);
+
+    let title = format!(" Play Queue ({}) ", app.play_queue.len());
+    let block = Block::default()
+        .title(title)
+        .title_bottom(" Enter: Play | x: Remove | J/K: Move | c: Clear | </>: Prev/Next ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().bg(app.theme.bg).fg(app.theme.fg))
+        .border_style(Style::default().fg(app.theme.highlight));
+
+    if app.play_queue.is_empty() {
+        let p = Paragraph::new("\n Queue is empty. Use 'Add to Queue' from the action menu.")
+            .style(Style::default().fg(app.theme.border))
+            .block(block);
+        f.render_widget(p, area);
+        return;
+    }
+
+    let title_width = (area.width as usize).saturating_sub(20);
+    let items: Vec<ListItem> = app
+        .play_queue
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let is_current = app.play_queue.current == Some(i);
+            let marker = if is_current { "▶ " } else { "  " };
+            let tag = match item.mode {
+                QueueMode::Video => "[VIDEO]",
+                QueueMode::Audio => "[AUDIO]",
+            };
+            let title_style = if is_current {
+                Style::default()
+                    .fg(app.theme.accent)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(app.theme.fg)
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::styled(marker, Style::default().fg(app.theme.accent)),
+                Span::styled(
+                    format!("{:>3}. ", i + 1),
+                    Style::default().fg(app.theme.border),
+                ),
+                Span::styled(
+                    format!("{} ", tag),
+                    Style::default().fg(app.theme.highlight),
+                ),
+                Span::styled(truncate_str(&item.video.title, title_width), title_style),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(block).highlight_style(
+        Style::default()
+            .bg(app.theme.highlight)
+            .fg(app.theme.fg)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    app.queue_state.select(app.selected_queue_index);
+    f.render_stateful_widget(list, area, &mut app.queue_state);
+}
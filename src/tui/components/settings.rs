@@ -8,6 +8,9 @@ pub enum SettingItem {
     Animation,
     SearchLimit,
     PlaylistLimit,
+    MaxDownloads,
+    AutoResume,
+    RateLimit,
     DownloadDirectory,
     ShowLive,
     ShowPlaylists,
@@ -24,6 +27,9 @@ impl SettingItem {
             Self::Animation,
             Self::SearchLimit,
             Self::PlaylistLimit,
+            Self::MaxDownloads,
+            Self::AutoResume,
+            Self::RateLimit,
             Self::DownloadDirectory,
             Self::ShowLive,
             Self::ShowPlaylists,
@@ -40,6 +46,9 @@ impl SettingItem {
             Self::Animation => "Logo Animation",
             Self::SearchLimit => "Search Results Limit",
             Self::PlaylistLimit => "Playlist Items Limit",
+            Self::MaxDownloads => "Parallel Downloads",
+            Self::AutoResume => "Resume Downloads",
+            Self::RateLimit => "Download Speed Limit",
             Self::DownloadDirectory => "Download Directory",
             Self::ShowLive => "Show Live Streams",
             Self::ShowPlaylists => "Show Playlists",
@@ -69,6 +78,20 @@ pub fn render_settings_menu(f: &mut Frame, app: &mut App, area: Rect) {
                 SettingItem::Animation => app.animation_mode.name().to_string(),
                 SettingItem::SearchLimit => app.search_limit.to_string(),
                 SettingItem::PlaylistLimit => app.playlist_limit.to_string(),
+                SettingItem::MaxDownloads => app.max_concurrent_downloads.to_string(),
+                SettingItem::AutoResume => (if app.auto_resume_downloads {
+                    "On"
+                } else {
+                    "Off"
+                })
+                .to_string(),
+                SettingItem::RateLimit => {
+                    if app.settings.download_rate_limit.is_empty() {
+                        "Unlimited".to_string()
+                    } else {
+                        format!("{}/s", app.settings.download_rate_limit)
+                    }
+                }
                 SettingItem::DownloadDirectory => app.download_directory.clone(),
                 SettingItem::ShowLive => (if app.show_live { "On" } else { "Off" }).to_string(),
                 SettingItem::ShowPlaylists => {
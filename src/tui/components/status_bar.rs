@@ -15,8 +15,18 @@ pub fn render_status_bar(f: &mut ratatui::Frame, app: &App, area: Rect, has_down
 
     let key_hints = match app.input_mode {
         InputMode::Normal => match app.state {
+            AppState::History => {
+                "h/Esc: Close | j/k: Nav | Enter: Replay | x: Remove | c: Clear".to_string()
+            }
+            AppState::ResumePrompt => "y: Resume | n: Start Over | Esc: Cancel".to_string(),
+            AppState::SubtitlePicker => "C/Esc: Close | j/k: Nav | Enter: Select".to_string(),
+            AppState::ChapterList => "g/Esc: Close | j/k: Nav | Enter: Jump".to_string(),
+            AppState::Queue => {
+                "u/Esc: Close | j/k: Nav | J/K: Move | Enter: Play | x: Remove | c: Clear"
+                    .to_string()
+            }
             AppState::Downloads => {
-                "q: Quit | Tab: Back | d/b: Toggle | j/k: Nav | Space: Select | Enter: Options"
+                "q: Quit | Tab: Back | d/b: Toggle | j/k: Nav | J/K: Move | n: Next | Space: Select | Enter: Options"
                     .to_string()
             }
             _ => {
@@ -32,7 +42,7 @@ pub fn render_status_bar(f: &mut ratatui::Frame, app: &App, area: Rect, has_down
                     )
                 } else {
                     format!(
-                        "q: Quit | d/b: Toggle | /: Search{} | j/k: Nav | Enter: Open",
+                        "q: Quit | d/b: Toggle | /: Search{} | j/k: Nav | u: Queue | h: History | f: Feed | Enter: Open",
                         tab_hint
                     )
                 }
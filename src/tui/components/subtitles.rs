@@ -0,0 +1,52 @@
+use super::widgets::centered_rect;
+use crate::app::App;
+use ratatui::{prelude::*, widgets::*};
+
+pub fn render_subtitle_picker(f: &mut Frame, app: &mut App, area: Rect) {
+    let area = centered_rect(40, 40, area);
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Subtitles ")
+        .title_bottom(" Enter: Select | Esc: Close ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().bg(app.theme.bg).fg(app.theme.fg))
+        .border_style(Style::default().fg(app.theme.highlight));
+
+    let any_selected = app.player_subtitles.iter().any(|t| t.selected);
+    let row = |label: String, selected: bool| {
+        let style = if selected {
+            Style::default()
+                .fg(app.theme.accent)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(app.theme.fg)
+        };
+        ListItem::new(Line::from(vec![
+            Span::styled(
+                if selected { "● " } else { "  " },
+                Style::default().fg(app.theme.accent),
+            ),
+            Span::styled(label, style),
+        ]))
+    };
+
+    let mut items = vec![row("Off".to_string(), !any_selected)];
+    items.extend(
+        app.player_subtitles
+            .iter()
+            .map(|track| row(track.label(), track.selected)),
+    );
+
+    let list = List::new(items).block(block).highlight_style(
+        Style::default()
+            .bg(app.theme.highlight)
+            .fg(app.theme.fg)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    app.subtitle_picker_state
+        .select(app.selected_player_subtitle_index);
+    f.render_stateful_widget(list, area, &mut app.subtitle_picker_state);
+}
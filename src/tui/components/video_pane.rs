@@ -0,0 +1,92 @@
+use ratatui::{
+    prelude::Rect,
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Paragraph},
+};
+
+use crate::app::App;
+use crate::sys::video::{VideoFeed, VideoFrame};
+
+/// Share of the main area taken by the video pane during terminal playback.
+pub const VIDEO_PANE_PERCENT: u16 = 55;
+
+/// Cells available for video inside the pane on a terminal of the given size,
+/// used as mpv's output size so little scaling is needed.
+pub fn output_size(columns: u16, rows: u16) -> (u16, u16) {
+    // Search bar, playback bar with chapters and status bar take the rest. Pictures must
+    // not be larger than the pane, they would cover the playback bar.
+    let main_rows = rows.saturating_sub(3 + 4 + 3);
+    let height = main_rows * VIDEO_PANE_PERCENT / 100;
+    (
+        columns.saturating_sub(2).max(1),
+        height.saturating_sub(2).max(1),
+    )
+}
+
+pub fn render_video_pane(f: &mut ratatui::Frame, app: &mut App, area: Rect) {
+    let title = app.playback_title.as_deref().unwrap_or("Terminal Playback");
+    let block = Block::default()
+        .title(format!(" {} ", title))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(app.theme.accent))
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    app.video_area = Some(inner);
+    if inner.width == 0 || inner.height == 0 {
+        return;
+    }
+
+    // Kitty and sixel pictures are written over the empty pane after drawing
+    let started = match &app.video_feed {
+        Some(VideoFeed::Pixels(frame)) => match frame.lock() {
+            Ok(frame) => {
+                if frame.updated {
+                    render_pixels(f, &frame, inner);
+                }
+                frame.updated
+            }
+            Err(_) => false,
+        },
+        Some(VideoFeed::Graphics(frame)) => frame.lock().is_ok_and(|frame| frame.started),
+        None => false,
+    };
+    if !started {
+        let loading = Paragraph::new(Line::from("Loading video..."))
+            .alignment(ratatui::layout::Alignment::Center)
+            .style(Style::default().fg(app.theme.fg));
+        let y = inner.y + inner.height / 2;
+        f.render_widget(loading, Rect::new(inner.x, y, inner.width, 1));
+    }
+}
+
+fn render_pixels(f: &mut ratatui::Frame, frame: &VideoFrame, inner: Rect) {
+    if frame.width == 0 || frame.height == 0 {
+        return;
+    }
+
+    // Nearest neighbour scaling that keeps the aspect ratio, two pixels per cell
+    let scale = (inner.width as f64 / frame.width as f64)
+        .min(inner.height as f64 * 2.0 / frame.height as f64);
+    let width = ((frame.width as f64 * scale) as u16).clamp(1, inner.width);
+    let height = ((frame.height as f64 * scale / 2.0) as u16).clamp(1, inner.height);
+    let left = inner.x + (inner.width - width) / 2;
+    let top = inner.y + (inner.height - height) / 2;
+
+    let pixel_rows = height as usize * 2;
+    let buf = f.buffer_mut();
+    for row in 0..height {
+        for column in 0..width {
+            let x = column as usize * frame.width / width as usize;
+            let y = row as usize * 2;
+            let upper = frame.pixel(x, y * frame.height / pixel_rows);
+            let lower = frame.pixel(x, (y + 1) * frame.height / pixel_rows);
+            buf[(left + column, top + row)]
+                .set_char('▀')
+                .set_fg(Color::Rgb(upper[0], upper[1], upper[2]))
+                .set_bg(Color::Rgb(lower[0], lower[1], lower[2]));
+        }
+    }
+}
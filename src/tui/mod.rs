@@ -13,11 +13,15 @@ pub mod components;
 use components::action_menu;
 use components::downloads;
 use components::format_selection;
+use components::history;
 use components::main_content;
 use components::playback_bar;
+use components::queue;
 use components::search_bar;
 use components::settings;
 use components::status_bar;
+use components::subtitles;
+use components::video_pane;
 use components::widgets::create_progress_bar_string;
 
 pub fn ui(f: &mut Frame, app: &mut App, picker: &mut Picker) {
@@ -27,7 +31,13 @@ pub fn ui(f: &mut Frame, app: &mut App, picker: &mut Picker) {
     ];
 
     if app.playback_title.is_some() {
-        constraints.push(Constraint::Length(3)); // Playback Bar
+        // Chapters get a timeline below the title
+        let height = if app.playback_chapters().is_empty() {
+            3
+        } else {
+            4
+        };
+        constraints.push(Constraint::Length(height)); // Playback Bar
     }
 
     if app.terminal_loading {
@@ -77,6 +87,22 @@ pub fn ui(f: &mut Frame, app: &mut App, picker: &mut Picker) {
     app.search_bar_area = main_layout[0];
     search_bar::render_search_bar(f, app, main_layout[0]);
 
+    // Terminal playback takes the top of the main area, the rest stays usable below
+    let mut content_area = main_layout[1];
+    if app.video_feed.is_some() {
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(video_pane::VIDEO_PANE_PERCENT),
+                Constraint::Min(0),
+            ])
+            .split(content_area);
+        video_pane::render_video_pane(f, app, chunks[0]);
+        content_area = chunks[1];
+    } else {
+        app.video_area = None;
+    }
+
     let main_content_area;
     let downloads_area;
 
@@ -84,7 +110,7 @@ pub fn ui(f: &mut Frame, app: &mut App, picker: &mut Picker) {
         let content_chunks = Layout::default()
             .direction(ratatui::layout::Direction::Horizontal)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(main_layout[1]);
+            .split(content_area);
         main_content_area = content_chunks[0];
         downloads_area = content_chunks[1];
 
@@ -94,7 +120,7 @@ pub fn ui(f: &mut Frame, app: &mut App, picker: &mut Picker) {
         main_content::render_main_area(f, app, main_content_area, picker);
         downloads::render_downloads_view(f, app, downloads_area);
     } else {
-        main_content_area = main_layout[1];
+        main_content_area = content_area;
 
         app.main_content_area = main_content_area;
         app.downloads_area = None;
@@ -161,6 +187,30 @@ pub fn ui(f: &mut Frame, app: &mut App, picker: &mut Picker) {
     if app.state == AppState::Settings {
         settings::render_settings_menu(f, app, f.area());
     }
+
+    if app.state == AppState::Queue {
+        queue::render_queue(f, app, f.area());
+    } else {
+        app.queue_area = None;
+    }
+
+    if app.state == AppState::History {
+        history::render_history(f, app, f.area());
+    } else {
+        app.history_area = None;
+    }
+
+    if app.state == AppState::ResumePrompt {
+        history::render_resume_prompt(f, app, f.area());
+    }
+
+    if app.state == AppState::SubtitlePicker {
+        subtitles::render_subtitle_picker(f, app, f.area());
+    }
+
+    if app.state == AppState::ChapterList {
+        playback_bar::render_chapter_list(f, app, f.area());
+    }
 }
 
 fn render_download_gauge(f: &mut Frame, app: &App, progress: f32, status: &str, area: Rect) {